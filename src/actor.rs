@@ -0,0 +1,168 @@
+// Actor adapter serializing the writes through a single task.
+// Available with the `actor` feature.
+
+use crate::UniRcLock;
+use alloc::boxed::Box;
+use core::{fmt, future::poll_fn, future::Future, pin::Pin};
+use futures_channel::{mpsc, oneshot};
+use futures_core::Stream;
+
+type Message<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// Conversion of any [UniRcLock] handle to an actor.
+/// Implemented for all handles.
+pub trait IntoActor<T>: UniRcLock<T> {
+    /// Consume the handle and return the [Address] of the actor
+    /// together with the future driving it, which should be spawned
+    /// on any executor or awaited.
+    ///
+    /// The future applies the messages sent to the address one by one
+    /// under the write lock, which is released between the messages.
+    /// It completes and returns the handle once all the addresses are dropped.
+    /// Reads could still go through a clone of the handle.
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// use std::sync::{Arc, RwLock};
+    /// use uni_rc_lock::{IntoActor, UniRcLock};
+    ///
+    /// let h = Arc::new(RwLock::new(vec![1]));
+    /// let (addr, actor) = h.clone().into_actor();
+    /// let actor = tokio::spawn(actor);
+    /// addr.cast(|v| v.push(2));
+    /// assert_eq!(addr.call(|v| v.len()).await, 2);
+    /// assert_eq!(*h.read(), [1, 2]);
+    /// drop(addr);
+    /// actor.await.unwrap();
+    /// # }
+    /// ```
+    fn into_actor(self) -> (Address<T>, impl Future<Output = Self>)
+    where
+        T: 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded::<Message<T>>();
+        let actor = async move {
+            while let Some(msg) = poll_fn(|cx| Pin::new(&mut rx).poll_next(cx)).await {
+                msg(&mut self.write());
+            }
+            self
+        };
+        (Address { tx }, actor)
+    }
+}
+
+impl<T, H: UniRcLock<T>> IntoActor<T> for H {}
+
+/// Cloneable address of the actor returned by [IntoActor::into_actor].
+///
+/// The messages are applied in the order they are sent,
+/// also when they are sent from the different clones.
+pub struct Address<T> {
+    tx: mpsc::UnboundedSender<Message<T>>,
+}
+
+impl<T> Clone for Address<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Address<T> {
+    /// Send the function to the actor and wait for its result.
+    /// The message is sent right away, not when the future is first polled.
+    ///
+    /// Panics when awaited if the actor future was dropped before running the function.
+    pub fn call<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut T) -> R + Send + 'static,
+    ) -> impl Future<Output = R> {
+        let (tx, rx) = oneshot::channel();
+        self.cast(move |value| {
+            // The caller may not wait for the result anymore
+            let _ = tx.send(f(value));
+        });
+        async move { rx.await.expect("The actor should be running") }
+    }
+
+    /// Send the function to the actor without waiting for it to be applied.
+    /// The function is silently dropped if the actor future was dropped.
+    pub fn cast(&self, f: impl FnOnce(&mut T) + Send + 'static) {
+        let _ = self.tx.unbounded_send(Box::new(f));
+    }
+
+    /// Returns `true` if the actor future was dropped, so the messages are discarded
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl<T> fmt::Debug for Address<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Address").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{Arc, RwLock},
+    };
+
+    use super::IntoActor;
+    use crate::UniRcLock;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn actor_order() {
+        let h = Arc::new(RwLock::new(Vec::new()));
+        let (addr, actor) = h.clone().into_actor();
+        let actor = tokio::spawn(actor);
+        let other = addr.clone();
+        for i in 0..100 {
+            if i % 2 == 0 {
+                addr.cast(move |v| v.push(i));
+            } else {
+                other.cast(move |v| v.push(i));
+            }
+        }
+        // Applied after all the casts
+        let len = addr.call(|v| v.len()).await;
+        assert_eq!(len, 100);
+        assert_eq!(*h.read(), (0..100).collect::<Vec<_>>());
+
+        drop(addr);
+        drop(other);
+        // Stops once all addresses are dropped and returns the handle
+        let h2 = actor.await.unwrap();
+        assert!(h2.ptr_eq(&h));
+        assert_eq!(h.strong_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn actor_call() {
+        let h = Rc::new(RefCell::new(1));
+        let (addr, actor) = h.clone().into_actor();
+        let call = addr.call(|v| {
+            *v += 1;
+            format!("{v}")
+        });
+        // The message is already sent, so the actor stops after applying it
+        drop(addr);
+        let (s, h2) = tokio::join!(call, actor);
+        assert_eq!(s, "2");
+        assert_eq!(*h2.read(), 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "actor should be running")]
+    async fn actor_dropped() {
+        let h = Rc::new(RefCell::new(1));
+        let (addr, actor) = h.into_actor();
+        drop(actor);
+        assert!(addr.is_closed());
+        addr.call(|v| *v).await;
+    }
+}
@@ -0,0 +1,248 @@
+// Async counterpart of UniRcLock. Available with the `async` feature.
+//
+// The trait itself has no dependencies. The async locks are provided
+// by the backends, while Rc<RefCell<T>> is implemented here.
+
+use crate::OwnedGuard;
+use alloc::rc::Rc;
+use core::{
+    cell::{Ref, RefCell, RefMut},
+    future::Future,
+    ops::{Deref, DerefMut},
+};
+#[cfg(feature = "timeout")]
+use core::{fmt, future::poll_fn, pin::pin, task::Poll, time::Duration};
+
+/// An async counterpart of [UniRcLock](crate::UniRcLock) for the locks which
+/// are acquired by awaiting, like `Arc<tokio::sync::RwLock<T>>`.
+///
+/// The guards are ordinary scoped guards. Holding a guard across an `.await`
+/// is allowed, but it keeps the lock held while the task is suspended,
+/// so other tasks waiting for the lock are stalled until it is released.
+/// For `Rc<RefCell<T>>` this is even worse: the lock is never awaited,
+/// so a conflicting access from another task panics instead of waiting.
+/// Release the guards before awaiting whenever possible.
+pub trait AsyncUniRcLock<T>: Clone {
+    type OutRead<'a>: Deref<Target = T> where Self: 'a;
+    type OutWrite<'a>: DerefMut<Target = T> where Self: 'a;
+    /// Create a new handle owning the value
+    fn new(value: T) -> Self;
+    /// Wait until the value could be read and obtain a scoped guard for reading
+    fn read<'a>(&'a self) -> impl Future<Output = Self::OutRead<'a>>;
+    /// Wait until the value could be written and obtain a scoped guard for writing
+    fn write<'a>(&'a self) -> impl Future<Output = Self::OutWrite<'a>>;
+
+    /// Like [read](AsyncUniRcLock::read), but gives up with [Elapsed]
+    /// if the value couldn't be read within the timeout.
+    /// With the zero timeout the lock is tried once without waiting.
+    ///
+    /// The timer comes from `futures-timer`, so it works with any executor.
+    #[cfg(feature = "timeout")]
+    fn read_timeout<'a>(
+        &'a self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Self::OutRead<'a>, Elapsed>> {
+        with_timeout(self.read(), timeout)
+    }
+
+    /// Like [write](AsyncUniRcLock::write), but gives up with [Elapsed]
+    /// if the value couldn't be written within the timeout.
+    /// With the zero timeout the lock is tried once without waiting.
+    #[cfg(feature = "timeout")]
+    fn write_timeout<'a>(
+        &'a self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Self::OutWrite<'a>, Elapsed>> {
+        with_timeout(self.write(), timeout)
+    }
+}
+
+/// The error returned when the lock couldn't be acquired within the timeout
+/// by [read_timeout](AsyncUniRcLock::read_timeout) or [write_timeout](AsyncUniRcLock::write_timeout)
+#[cfg(feature = "timeout")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+#[cfg(feature = "timeout")]
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lock timeout elapsed")
+    }
+}
+
+#[cfg(feature = "timeout")]
+impl core::error::Error for Elapsed {}
+
+// Awaits the future until the timeout. The future is always polled first,
+// so the ready lock is acquired even with the zero timeout.
+#[cfg(feature = "timeout")]
+async fn with_timeout<F: Future>(fut: F, timeout: Duration) -> Result<F::Output, Elapsed> {
+    let mut fut = pin!(fut);
+    let mut delay = (!timeout.is_zero()).then(|| futures_timer::Delay::new(timeout));
+    poll_fn(|cx| {
+        if let Poll::Ready(v) = fut.as_mut().poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+        match &mut delay {
+            Some(delay) => pin!(delay).poll(cx).map(|()| Err(Elapsed)),
+            None => Poll::Ready(Err(Elapsed)),
+        }
+    })
+    .await
+}
+
+/// Implementation for `Rc<RefCell<T>>` in single-threaded async code.
+///
+/// `RefCell` never waits, so the futures resolve immediately
+/// and panic if the value is already borrowed, exactly like
+/// [UniRcLock::read](crate::UniRcLock::read) and [UniRcLock::write](crate::UniRcLock::write).
+impl<T> AsyncUniRcLock<T> for Rc<RefCell<T>> {
+    type OutRead<'a> = Ref<'a, T> where T: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where T: 'a;
+
+    fn new(value: T) -> Self {
+        Rc::new(RefCell::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Rc::deref(self).borrow()
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Rc::deref(self).borrow_mut()
+    }
+}
+
+/// The thread-safe [AsyncUniRcLock] handles, which could be used in the generic
+/// tasks spawned on a multi-threaded executor, like `tokio::spawn()`.
+///
+/// The futures returned by [AsyncUniRcLock::read] and [AsyncUniRcLock::write]
+/// are opaque, so the generic code can't tell that they are `Send`,
+/// and the executor rejects the task. The methods of this trait return
+/// the same guards, but their futures and guards are known to be `Send`:
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # #[tokio::main]
+/// # async fn main() {
+/// use std::sync::Arc;
+/// use uni_rc_lock::SpawnableAsyncLock;
+///
+/// async fn incr<H: SpawnableAsyncLock<i32>>(h: H) {
+///     let mut g = h.write_send().await;
+///     // The guard may be held across an await in a spawned task
+///     tokio::task::yield_now().await;
+///     *g += 1;
+/// }
+///
+/// fn spawn_incr<H: SpawnableAsyncLock<i32>>(h: &H) -> tokio::task::JoinHandle<()> {
+///     tokio::spawn(incr(h.clone()))
+/// }
+///
+/// let h = Arc::new(tokio::sync::RwLock::new(0));
+/// spawn_incr(&h).await.unwrap();
+/// assert_eq!(*h.read().await, 1);
+/// # }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+pub trait SpawnableAsyncLock<T>: AsyncUniRcLock<T> + Send + Sync + 'static {
+    /// The read guard, which is `Send`. The same as [AsyncUniRcLock::OutRead].
+    type SendRead<'a>: Deref<Target = T> + Send where Self: 'a;
+    /// The write guard, which is `Send`. The same as [AsyncUniRcLock::OutWrite].
+    type SendWrite<'a>: DerefMut<Target = T> + Send where Self: 'a;
+    /// Like [read](AsyncUniRcLock::read), but the future is `Send`
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send;
+    /// Like [write](AsyncUniRcLock::write), but the future is `Send`
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send;
+}
+
+/// The [AsyncUniRcLock] handles which provide owned guards. They don't borrow
+/// the handle, so they could be stored in a struct without a lifetime
+/// or moved into a spawned task, like tokio's `OwnedRwLockReadGuard`.
+///
+/// Both methods consume the handle, which is kept alive by the guard.
+/// Clone the handle first to keep using it.
+pub trait AsyncOwnedLock<T>: AsyncUniRcLock<T> {
+    type OwnedRead: Deref<Target = T>;
+    type OwnedWrite: DerefMut<Target = T>;
+    /// Wait until the value could be read and obtain an owned guard for reading
+    fn read_owned(self) -> impl Future<Output = Self::OwnedRead>;
+    /// Wait until the value could be written and obtain an owned guard for writing
+    fn write_owned(self) -> impl Future<Output = Self::OwnedWrite>;
+}
+
+/// Owned guards of `Rc<RefCell<T>>` keep a clone of the `Rc` alive.
+impl<T: 'static> AsyncOwnedLock<T> for Rc<RefCell<T>> {
+    type OwnedRead = OwnedGuard<Self, Ref<'static, T>>;
+    type OwnedWrite = OwnedGuard<Self, RefMut<'static, T>>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        let guard = Rc::deref(&self).borrow();
+        // SAFETY: the RefCell is kept alive by the handle stored with the guard
+        unsafe {
+            let guard = core::mem::transmute::<Ref<'_, T>, Ref<'static, T>>(guard);
+            OwnedGuard::new(self, guard)
+        }
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        let guard = Rc::deref(&self).borrow_mut();
+        // SAFETY: the RefCell is kept alive by the handle stored with the guard
+        unsafe {
+            let guard = core::mem::transmute::<RefMut<'_, T>, RefMut<'static, T>>(guard);
+            OwnedGuard::new(self, guard)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::AsyncUniRcLock;
+
+    #[derive(Debug)]
+    struct Foo(i32);
+
+    async fn incr_foo(v: impl AsyncUniRcLock<Foo>) {
+        v.write().await.0 += 1;
+    }
+
+    #[tokio::test]
+    async fn async_rc() {
+        let ptr: Rc<RefCell<Foo>> = AsyncUniRcLock::new(Foo(0));
+        incr_foo(ptr.clone()).await;
+        incr_foo(ptr.clone()).await;
+        assert_eq!(ptr.read().await.0, 2);
+    }
+
+    #[tokio::test]
+    async fn async_rc_owned() {
+        use super::AsyncOwnedLock;
+
+        struct Holder {
+            foo: <Rc<RefCell<Foo>> as AsyncOwnedLock<Foo>>::OwnedWrite,
+        }
+
+        let ptr = Rc::new(RefCell::new(Foo(0)));
+        let mut h = Holder {
+            foo: ptr.clone().write_owned().await,
+        };
+        h.foo.0 += 1;
+        assert!(ptr.try_borrow().is_err());
+        drop(h);
+        let r = ptr.clone().read_owned().await;
+        drop(ptr);
+        // The guard keeps the value alive
+        assert_eq!(r.0, 1);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn async_rc_conflict() {
+        let ptr = Rc::new(RefCell::new(Foo(0)));
+        let _r = AsyncUniRcLock::read(&ptr).await;
+        AsyncUniRcLock::write(&ptr).await.0 += 1;
+    }
+}
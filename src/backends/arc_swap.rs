@@ -0,0 +1,278 @@
+// Implementation for arc_swap::ArcSwap.
+// Reading loads a snapshot of the current value without locking.
+// Writing clones the current value into a staging copy, which is
+// published as a whole when the write guard is dropped.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use arc_swap::{ArcSwap, Guard};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{self, Arc},
+};
+
+/// Handle to a value stored in `Arc<arc_swap::ArcSwap<T>>`.
+///
+/// Intended for the read-mostly data like configuration.
+/// `read()` never blocks and returns a snapshot of the value, which stays
+/// valid and unchanged even if a new value is published in the meantime.
+///
+/// `write()` never blocks either. It returns a guard holding a clone
+/// of the current value, which replaces the stored value when the guard
+/// is dropped. Concurrent writes are not serialized: if two guards
+/// are alive at the same time, the value published last wins and the changes
+/// made through the other guard are lost. The changes are not visible
+/// to the readers until the write guard is dropped.
+pub struct SwapHandle<T> {
+    inner: Arc<ArcSwap<T>>,
+}
+
+impl<T> Clone for SwapHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// Weak counterpart of [SwapHandle]
+pub struct SwapWeak<T> {
+    inner: sync::Weak<ArcSwap<T>>,
+}
+
+impl<T> Clone for SwapWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: sync::Weak::clone(&self.inner),
+        }
+    }
+}
+
+/// Read guard of [SwapHandle]. Holds a snapshot of the value.
+pub struct SwapReadGuard<T> {
+    guard: Guard<Arc<T>>,
+}
+
+impl<T> Deref for SwapReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// Write guard of [SwapHandle]. Holds a staging copy of the value,
+/// which is published when the guard is dropped.
+pub struct SwapWriteGuard<'a, T> {
+    swap: &'a ArcSwap<T>,
+    // Boxed, so that the value doesn't move together with the guard
+    value: Option<Box<T>>,
+}
+
+impl<T> Deref for SwapWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for SwapWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for SwapWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // The staging copy may be half-modified if the write panicked
+        if let Some(value) = self.value.take().filter(|_| !std::thread::panicking()) {
+            self.swap.store(Arc::from(value));
+        }
+    }
+}
+
+// Implementation for SwapHandle<T>
+//
+// get_mut() always returns None since ArcSwap gives no mutable access
+// to the stored value.
+impl<T: Clone> UniRcLock<T> for SwapHandle<T> {
+    type OutRead<'a> = SwapReadGuard<T> where T: 'a;
+    type OutWrite<'a> = SwapWriteGuard<'a, T> where T: 'a;
+    type Weak = SwapWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<SwapReadGuard<T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<SwapWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        SwapReadGuard {
+            guard: self.inner.load(),
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        SwapWriteGuard {
+            swap: &self.inner,
+            value: Some(Box::new(T::clone(&self.inner.load()))),
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(self.read())
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(self.write())
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.inner)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    fn lock_id(&self) -> *const () {
+        Arc::as_ptr(&self.inner).cast()
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        SwapWeak {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(|swap| Arc::unwrap_or_clone(swap.into_inner()))
+            .map_err(|inner| Self { inner })
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the snapshot lives in the Arc, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the staging value is boxed, not stored in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Clone> UniNew<T> for SwapHandle<T> {
+    fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(ArcSwap::from_pointee(value)),
+        }
+    }
+}
+
+impl<T: Clone> UniWeak<T> for SwapWeak<T> {
+    type Strong = SwapHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.inner.upgrade().map(|inner| SwapHandle { inner })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::SwapHandle;
+    use crate::{UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug, Clone)]
+    struct Config {
+        version: usize,
+        name: String,
+    }
+
+    #[test]
+    fn arc_swap() {
+        let h = SwapHandle::new(Config {
+            version: 0,
+            name: "initial".into(),
+        });
+        let snapshot = h.read();
+        {
+            let mut w = h.write();
+            w.version += 1;
+            w.name.push('!');
+            // Not published yet
+            assert_eq!(h.read().version, 0);
+        }
+        assert_eq!(h.read().version, 1);
+        assert_eq!(&*h.map_read(|c| c.name.as_str()), "initial!");
+        // The old snapshot is still intact
+        assert_eq!(snapshot.version, 0);
+
+        // A panicked write is not published
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut w = h.write();
+            w.version += 1;
+            panic!("interrupted write");
+        }));
+        assert!(res.is_err());
+        assert_eq!(h.read().version, 1);
+
+        let w = h.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&h));
+        drop(w);
+        assert_eq!(h.try_unwrap().ok().unwrap().version, 1);
+    }
+
+    #[test]
+    fn arc_swap_lost_write() {
+        let h = SwapHandle::new(0);
+        let mut w1 = h.write();
+        let mut w2 = h.write();
+        *w1 += 1;
+        *w2 += 10;
+        drop(w2);
+        drop(w1);
+        assert_eq!(*h.read(), 1);
+    }
+
+    #[test]
+    fn arc_swap_threads() {
+        let h = SwapHandle::new(Config {
+            version: 0,
+            name: "v0".into(),
+        });
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let mut last = 0;
+                    for _ in 0..10000 {
+                        let c = h.read();
+                        // Snapshots are consistent and never go back in time
+                        assert_eq!(c.name, format!("v{}", c.version));
+                        assert!(c.version >= last);
+                        last = c.version;
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 1..=100 {
+                    let mut w = h.write();
+                    w.version = i;
+                    w.name = format!("v{i}");
+                }
+            });
+        });
+        assert_eq!(h.read().version, 100);
+    }
+}
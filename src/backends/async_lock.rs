@@ -0,0 +1,206 @@
+// Implementations for the async-lock locks. Available with the `async-lock` feature.
+// These locks don't depend on any runtime, so they work with smol, async-std
+// or any other executor.
+
+use crate::{AsyncOwnedLock, AsyncUniRcLock, SpawnableAsyncLock};
+use async_lock::{
+    Mutex, MutexGuard, MutexGuardArc, RwLock, RwLockReadGuard, RwLockReadGuardArc,
+    RwLockWriteGuard, RwLockWriteGuardArc,
+};
+use std::{future::Future, ops::Deref, sync::Arc};
+
+/// Implementation for `Arc<async_lock::RwLock<T>>`.
+///
+/// async-lock locks are never poisoned, thus `read()` and `write()` never panic.
+impl<T> AsyncUniRcLock<T> for Arc<RwLock<T>> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+
+    fn new(value: T) -> Self {
+        Arc::new(RwLock::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).read().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).write().await
+    }
+}
+
+impl<T> AsyncOwnedLock<T> for Arc<RwLock<T>> {
+    type OwnedRead = RwLockReadGuardArc<T>;
+    type OwnedWrite = RwLockWriteGuardArc<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        RwLock::read_arc(&self).await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        RwLock::write_arc(&self).await
+    }
+}
+
+impl<T: Send + Sync + 'static> SpawnableAsyncLock<T> for Arc<RwLock<T>> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+/// Implementation for `Arc<async_lock::Mutex<T>>`.
+///
+/// Like for std `Mutex`, `read()` is exclusive.
+impl<T> AsyncUniRcLock<T> for Arc<Mutex<T>> {
+    type OutRead<'a> = MutexGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = MutexGuard<'a, T> where T: 'a;
+
+    fn new(value: T) -> Self {
+        Arc::new(Mutex::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).lock().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).lock().await
+    }
+}
+
+impl<T> AsyncOwnedLock<T> for Arc<Mutex<T>> {
+    type OwnedRead = MutexGuardArc<T>;
+    type OwnedWrite = MutexGuardArc<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        Mutex::lock_arc(&self).await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        Mutex::lock_arc(&self).await
+    }
+}
+
+impl<T: Send + 'static> SpawnableAsyncLock<T> for Arc<Mutex<T>> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_lock::{Mutex, RwLock};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::{AsyncOwnedLock, AsyncUniRcLock};
+
+    #[derive(Debug, Default)]
+    struct Conn {
+        queries: Vec<String>,
+    }
+
+    // The same generic code as in the tokio tests
+    async fn query(conn: impl AsyncUniRcLock<Conn>, q: String) -> usize {
+        let mut c = conn.write().await;
+        c.queries.push(q);
+        c.queries.len()
+    }
+
+    async fn run<H: AsyncUniRcLock<Conn>>() -> H {
+        let conn = H::new(Conn::default());
+        for i in 0..10 {
+            assert_eq!(query(conn.clone(), format!("q{i}")).await, i + 1);
+        }
+        conn
+    }
+
+    #[test]
+    fn async_lock_rwlock() {
+        let conn = block_on(run::<Arc<RwLock<Conn>>>());
+        block_on(async {
+            let r1 = AsyncUniRcLock::read(&conn).await;
+            let r2 = AsyncUniRcLock::read(&conn).await;
+            assert_eq!(r1.queries.len() + r2.queries.len(), 20);
+            assert!(conn.try_write().is_none());
+        });
+    }
+
+    #[test]
+    fn async_lock_mutex() {
+        let conn = block_on(run::<Arc<Mutex<Conn>>>());
+        block_on(async {
+            let _r = AsyncUniRcLock::read(&conn).await;
+            // Reading locks the mutex exclusively
+            assert!(conn.try_lock().is_none());
+        });
+    }
+
+    #[test]
+    fn async_lock_threads() {
+        let ptr: Arc<Mutex<i32>> = AsyncUniRcLock::new(0);
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let p = ptr.clone();
+                thread::spawn(move || block_on(async { *p.write().await += 1 }))
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(*block_on(AsyncUniRcLock::read(&ptr)), 10);
+    }
+
+    #[test]
+    fn async_lock_owned() {
+        let ptr: Arc<RwLock<i32>> = AsyncUniRcLock::new(1);
+        let mut w = block_on(ptr.clone().write_owned());
+        assert!(ptr.try_read().is_none());
+        // The owned guard could be moved to another thread
+        thread::spawn(move || *w += 1).join().unwrap();
+        assert_eq!(*block_on(ptr.clone().read_owned()), 2);
+
+        let m: Arc<Mutex<i32>> = AsyncUniRcLock::new(0);
+        let g = block_on(m.clone().read_owned());
+        assert!(m.try_lock().is_none());
+        drop(g);
+        *block_on(m.clone().write_owned()) += 1;
+        assert_eq!(*block_on(m.lock()), 1);
+    }
+
+    #[cfg(feature = "timeout")]
+    #[test]
+    fn async_lock_timeout() {
+        use crate::Elapsed;
+        use std::time::{Duration, Instant};
+
+        let ptr: Arc<Mutex<i32>> = AsyncUniRcLock::new(0);
+        let g = block_on(AsyncUniRcLock::write(&ptr));
+        let h = ptr.clone();
+        let waiter = thread::spawn(move || {
+            let start = Instant::now();
+            let res = block_on(h.read_timeout(Duration::from_millis(50))).map(|r| *r);
+            (res, start.elapsed())
+        });
+        let (res, elapsed) = waiter.join().unwrap();
+        assert_eq!(res, Err(Elapsed));
+        assert!(elapsed >= Duration::from_millis(50));
+        drop(g);
+        assert_eq!(block_on(ptr.read_timeout(Duration::ZERO)).map(|r| *r), Ok(0));
+    }
+}
@@ -0,0 +1,174 @@
+// Implementation for atomic_refcell::AtomicRefCell.
+// It has the semantics of RefCell but is Sync: conflicting borrows panic
+// instead of blocking, just like RefCell::borrow_mut() does.
+// Only core and alloc are used here, so this backend is usable without std.
+
+use crate::{CheckedResult, LockError, RecursiveRead, UniLockError, UniNew, UniRcLock, UniWeak};
+use ::atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use alloc::sync::{Arc, Weak};
+use core::ops::Deref;
+
+// Implementation for Arc<AtomicRefCell<T>>
+impl<T> UniRcLock<T> for Arc<AtomicRefCell<T>> {
+    type OutRead<'a> = AtomicRef<'a, T> where T: 'a;
+    type OutWrite<'a> = AtomicRefMut<'a, T> where T: 'a;
+    type Weak = Weak<AtomicRefCell<T>>;
+    type MappedRead<'a, U: ?Sized + 'a> = AtomicRef<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = AtomicRefMut<'a, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).borrow()
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).borrow_mut()
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Arc::deref(self)
+            .try_borrow()
+            .map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Arc::deref(self)
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)
+    }
+
+    // The conflicting borrow may be held by another thread, which can't be waited for
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        Arc::deref(self)
+            .try_borrow()
+            .map_err(|_| UniLockError::WouldBlock)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        Arc::deref(self)
+            .try_borrow_mut()
+            .map_err(|_| UniLockError::WouldBlock)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(|cell| cell.into_inner())
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self).map(|lock| lock.get_mut())
+    }
+
+    fn as_ptr(&self) -> *const T {
+        AtomicRefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        AtomicRef::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        AtomicRefMut::map(self.write(), f)
+    }
+}
+
+impl<T> UniNew<T> for Arc<AtomicRefCell<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(AtomicRefCell::new(value))
+    }
+}
+
+impl<T> UniWeak<T> for Weak<AtomicRefCell<T>> {
+    type Strong = Arc<AtomicRefCell<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self)
+    }
+}
+
+// Shared borrows of AtomicRefCell never block, so nested reads are fine
+impl<T> RecursiveRead<T> for Arc<AtomicRefCell<T>> {
+    fn read_recursive<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self)
+            .try_borrow()
+            .expect("Recursive read while the AtomicRefCell is borrowed for writing")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::atomic_refcell::AtomicRefCell;
+    use alloc::sync::Arc;
+
+    use crate::{LockError, UniLockError, UniRcLock};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    #[test]
+    fn atomic_refcell() {
+        let st = Arc::new(AtomicRefCell::new(State { val: 42 }));
+        st.write().val += 1;
+        let _r = st.read();
+        assert_eq!(st.read().val, 43);
+        assert_eq!(st.try_write().err(), Some(LockError::WouldBlock));
+    }
+
+    #[test]
+    fn atomic_refcell_checked() {
+        let st = Arc::new(AtomicRefCell::new(State { val: 42 }));
+        {
+            let _r = st.read();
+            assert_eq!(st.read_checked().unwrap().val, 42);
+            assert!(matches!(st.write_checked(), Err(UniLockError::WouldBlock)));
+        }
+        st.write_checked().unwrap().val += 1;
+        assert_eq!(st.read().val, 43);
+    }
+
+    #[test]
+    fn threads_test_atomic_refcell() {
+        use std::thread;
+        let st = Arc::new(AtomicRefCell::new(State { val: 0 }));
+        let threads: Vec<_> = (0..10)
+            .map(|i| {
+                let h = st.clone();
+                thread::spawn(move || h.read().val + i)
+            })
+            .collect();
+
+        let sum: i32 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+        st.write().val = sum;
+        assert_eq!(st.read().val, 45);
+    }
+
+    #[test]
+    #[should_panic]
+    fn atomic_refcell_conflict() {
+        let st = Arc::new(AtomicRefCell::new(State { val: 42 }));
+        let _r = st.read();
+        st.write().val += 1;
+    }
+}
@@ -0,0 +1,278 @@
+// Implementation for critical_section::Mutex<RefCell<T>>.
+// Each guard keeps the critical section entered while it is alive.
+// The guards may be dropped in any order, so the nesting of the sections
+// entered by them is tracked here: the section is actually entered by
+// the first guard and left when the last guard is dropped.
+// Only core and alloc are used here, so this backend is usable without std.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, NoWeak, UniRcLock};
+use ::critical_section::{acquire, release, CriticalSection, Mutex, RestoreState};
+use core::{
+    cell::{Cell, Ref, RefCell, RefMut},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+#[derive(Clone, Copy)]
+struct Nesting {
+    depth: usize,
+    restore: RestoreState,
+}
+
+// Only accessed inside the critical section
+static NESTING: Mutex<Cell<Nesting>> = Mutex::new(Cell::new(Nesting {
+    depth: 0,
+    restore: RestoreState::invalid(),
+}));
+
+// The critical section entered by a guard.
+// Not Send, since the section has to be left by the same thread.
+struct Section(PhantomData<*const ()>);
+
+impl Section {
+    fn enter() -> Self {
+        // SAFETY: released below if the section is already entered by
+        // another guard, or when the last guard is dropped otherwise
+        let restore = unsafe { acquire() };
+        // SAFETY: the critical section is entered
+        let nesting = NESTING.borrow(unsafe { CriticalSection::new() });
+        let mut n = nesting.get();
+        if n.depth == 0 {
+            n.restore = restore;
+        } else {
+            // SAFETY: nested in the section held by the other guards
+            unsafe { release(restore) };
+        }
+        n.depth += 1;
+        nesting.set(n);
+        Section(PhantomData)
+    }
+}
+
+impl Drop for Section {
+    fn drop(&mut self) {
+        // SAFETY: the critical section is entered until the last guard is dropped
+        let nesting = NESTING.borrow(unsafe { CriticalSection::new() });
+        let mut n = nesting.get();
+        n.depth -= 1;
+        nesting.set(n);
+        if n.depth == 0 {
+            // SAFETY: the state was obtained by the outermost acquire()
+            unsafe { release(n.restore) };
+        }
+    }
+}
+
+// Obtains the inner RefCell for the lifetime of the mutex itself.
+// Must only be called while the critical section is entered.
+fn cell<'a, T>(mutex: &'a Mutex<RefCell<T>>, _section: &Section) -> &'a RefCell<T> {
+    // SAFETY: the section is entered while the guard holding it is alive
+    mutex.borrow(unsafe { CriticalSection::new() })
+}
+
+/// Read guard of `&critical_section::Mutex<RefCell<T>>`.
+///
+/// Holds the critical section and the shared borrow of the inner `RefCell`.
+pub struct CsReadGuard<'a, T> {
+    // Declared first to be dropped before the section is left
+    borrow: Ref<'a, T>,
+    _section: Section,
+}
+
+impl<T> Deref for CsReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+/// Write guard of `&critical_section::Mutex<RefCell<T>>`.
+///
+/// Holds the critical section and the exclusive borrow of the inner `RefCell`.
+pub struct CsWriteGuard<'a, T> {
+    // Declared first to be dropped before the section is left
+    borrow: RefMut<'a, T>,
+    _section: Section,
+}
+
+impl<T> Deref for CsWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+impl<T> DerefMut for CsWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.borrow
+    }
+}
+
+/// Implementation for `&critical_section::Mutex<RefCell<T>>`,
+/// usually a `static` shared with the interrupt handlers.
+///
+/// Each guard keeps the critical section entered, so the interrupts
+/// are disabled while any guard is alive. Keep the guards short-lived.
+/// Conflicting `read()` and `write()` panic like for `RefCell`.
+/// The guards may be dropped in any order, but they must not escape
+/// the critical sections entered outside of this crate, like
+/// the closure of `critical_section::with()`, since leaving such
+/// section would leave the section of the guard as well.
+///
+/// Like `&RefCell<T>`, it is not reference-counted, has no weak references,
+/// can't be unwrapped and doesn't implement [UniNew](crate::UniNew).
+impl<T> UniRcLock<T> for &Mutex<RefCell<T>> {
+    type OutRead<'a> = CsReadGuard<'a, T> where Self: 'a;
+    type OutWrite<'a> = CsWriteGuard<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<CsReadGuard<'a, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<CsWriteGuard<'a, T>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let section = Section::enter();
+        let borrow = cell(self, &section).borrow();
+        CsReadGuard {
+            borrow,
+            _section: section,
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let section = Section::enter();
+        let borrow = cell(self, &section).borrow_mut();
+        CsWriteGuard {
+            borrow,
+            _section: section,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let section = Section::enter();
+        let borrow = cell(self, &section)
+            .try_borrow()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(CsReadGuard {
+            borrow,
+            _section: section,
+        })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let section = Section::enter();
+        let borrow = cell(self, &section)
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(CsWriteGuard {
+            borrow,
+            _section: section,
+        })
+    }
+
+    fn strong_count(&self) -> usize {
+        1
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        core::ptr::eq(*self, *other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the mutex, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the mutex, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+// The tests use the std implementation of the critical section,
+// which is a global lock re-entrant for the thread holding it.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use ::critical_section::Mutex;
+    use core::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::{thread, time::Duration};
+
+    use crate::{LockError, UniRcLock};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr(v: impl UniRcLock<State>) {
+        v.write().val += 1;
+    }
+
+    // Checks if another thread could enter the critical section
+    fn section_is_free() -> bool {
+        let entered = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| ::critical_section::with(|_| entered.store(true, Ordering::SeqCst)));
+            thread::sleep(Duration::from_millis(50));
+            entered.load(Ordering::SeqCst)
+        })
+    }
+
+    #[test]
+    fn critical_section() {
+        static STATE: Mutex<RefCell<State>> = Mutex::new(RefCell::new(State { val: 0 }));
+
+        incr(&STATE);
+        incr(&STATE);
+        let h = &STATE;
+        assert_eq!(h.read().val, 2);
+        {
+            let _r = h.read();
+            assert_eq!(h.try_read().unwrap().val, 2);
+            assert_eq!(h.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(*h.map_read(|s| &s.val), 2);
+    }
+
+    #[test]
+    fn critical_section_drop_order() {
+        let a = &Mutex::new(RefCell::new(State { val: 1 }));
+        let b = &Mutex::new(RefCell::new(State { val: 2 }));
+        let ra = a.read();
+        let mut wb = b.write();
+        wb.val += ra.val;
+        // Dropping the outer guard first doesn't leave the section
+        drop(ra);
+        thread::scope(|s| {
+            let waiter = s.spawn(|| ::critical_section::with(|_| ()));
+            thread::sleep(Duration::from_millis(50));
+            assert!(!waiter.is_finished());
+            drop(wb);
+            waiter.join().unwrap();
+        });
+        assert!(section_is_free());
+        assert_eq!(b.read().val, 3);
+    }
+}
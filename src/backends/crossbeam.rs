@@ -0,0 +1,454 @@
+// Implementations for crossbeam ShardedLock, AtomicCell and CachePadded.
+// Available with the `crossbeam` feature.
+
+use crate::{
+    CheckedResult, LockError, MappedReadGuard, MappedWriteGuard, UniCell, UniNew, UniRcCopy,
+    UniRcLock, UniWeak, ValueGuard, ValueWriteGuard,
+};
+use crossbeam_utils::atomic::AtomicCell;
+use crossbeam_utils::CachePadded;
+use crossbeam_utils::sync::{ShardedLock, ShardedLockReadGuard, ShardedLockWriteGuard};
+use std::ops::Deref;
+use std::sync::{Arc, PoisonError, RwLock, Weak};
+
+/// Handle to a value in `RwLock` padded to the cache line, for the values
+/// shared by many handles side by side, like one shard per thread in a `Vec`.
+///
+/// This is `Arc<crossbeam_utils::CachePadded<RwLock<T>>>`, a [UniRcLock] handle
+/// with the same guards as `Arc<RwLock<T>>`. The adjacent locks don't share
+/// a cache line, so the threads writing them don't slow down each other.
+/// Any other cell could be padded the same way, like `Arc<CachePadded<Mutex<T>>>`.
+pub type PaddedHandle<T> = Arc<CachePadded<RwLock<T>>>;
+
+/// Create `n` padded handles holding the values returned by `init` for each index.
+/// ```
+/// use uni_rc_lock::{padded_shards, UniRcLock};
+///
+/// let shards = padded_shards(4, |_| 0u64);
+/// std::thread::scope(|s| {
+///     for shard in &shards {
+///         s.spawn(move || *shard.write() += 1);
+///     }
+/// });
+/// assert_eq!(shards.iter().map(|h| *h.read()).sum::<u64>(), 4);
+/// ```
+pub fn padded_shards<T>(n: usize, init: impl FnMut(usize) -> T) -> Vec<PaddedHandle<T>> {
+    (0..n).map(init).map(UniNew::new).collect()
+}
+
+/// Implementation for any cell padded to the cache line, shared by
+/// `Arc<CachePadded<RwLock<T>>>` or any other [UniPtr](crate::UniPtr).
+/// Everything is forwarded to the padded cell.
+impl<C: UniCell> UniCell for CachePadded<C> {
+    type Value = C::Value;
+    type Read<'a> = C::Read<'a> where Self: 'a;
+    type Write<'a> = C::Write<'a> where Self: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = C::MappedRead<'a, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = C::MappedWrite<'a, U> where Self: 'a;
+    const ALLOWS_CONCURRENT_READS: bool = C::ALLOWS_CONCURRENT_READS;
+
+    fn new(value: C::Value) -> Self {
+        CachePadded::new(C::new(value))
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        C::read(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        C::write(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        C::try_read(self)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        C::try_write(self)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::Read<'a>> {
+        C::read_checked(self)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::Write<'a>> {
+        C::write_checked(self)
+    }
+
+    fn into_inner(self) -> C::Value {
+        CachePadded::into_inner(self).into_inner()
+    }
+
+    fn get_mut(&mut self) -> &mut C::Value {
+        C::get_mut(self)
+    }
+
+    fn as_ptr(&self) -> *const C::Value {
+        C::as_ptr(self)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        C::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        C::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&C::Value) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        C::map_read(self, f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut C::Value) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        C::map_write(self, f)
+    }
+}
+
+/// Implementation for `Arc<crossbeam_utils::sync::ShardedLock<T>>`.
+///
+/// Reads are cheaper and scale better than with `std::sync::RwLock`
+/// because each thread locks its own shard, but `write()` has to lock
+/// all the shards and is considerably more expensive.
+///
+/// `ShardedLock` has no way to clear poisoning, so `clear_poison()` does nothing.
+impl<T> UniRcLock<T> for Arc<ShardedLock<T>> {
+    type OutRead<'a> = ShardedLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = ShardedLockWriteGuard<'a, T> where T: 'a;
+    type Weak = Weak<ShardedLock<T>>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<ShardedLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<ShardedLockWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self)
+            .read()
+            .expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self)
+            .write()
+            .expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(Arc::deref(self).try_read()?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(Arc::deref(self).try_write()?)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self)
+            .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self)
+            .map(|lock| lock.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn is_poisoned(&self) -> bool {
+        Arc::deref(self).is_poisoned()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T> UniNew<T> for Arc<ShardedLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(ShardedLock::new(value))
+    }
+}
+
+impl<T> UniWeak<T> for Weak<ShardedLock<T>> {
+    type Strong = Arc<ShardedLock<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self)
+    }
+}
+
+/// Implementation for `Arc<crossbeam_utils::atomic::AtomicCell<T>>` with `T: Copy`.
+///
+/// Like for `Rc<Cell<T>>`, the guards own a copy of the value: `read()` loads it
+/// and `write()` stores the modified copy back when the guard is dropped.
+/// Nothing is locked, so the value may be changed by other threads between
+/// the load and the store. Such changes are silently overwritten,
+/// thus read-modify-write through `write()` is not atomic: concurrent
+/// increments may be lost.
+impl<T: Copy> UniRcLock<T> for Arc<AtomicCell<T>> {
+    type OutRead<'a> = ValueGuard<T> where T: 'a;
+    type OutWrite<'a> = ValueWriteGuard<'a, AtomicCell<T>, T> where T: 'a;
+    type Weak = Weak<AtomicCell<T>>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<Box<T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<Box<Self::OutWrite<'a>>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        ValueGuard::new(self.load())
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        ValueWriteGuard::new(self, self.load(), AtomicCell::store)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(self.read())
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(self.write())
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(AtomicCell::into_inner)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: the cell is borrowed mutably, so nobody else accesses it
+        Arc::get_mut(self).map(|cell| unsafe { &mut *cell.as_ptr() })
+    }
+
+    fn as_ptr(&self) -> *const T {
+        AtomicCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the copy lives in the box, not in the guard
+        unsafe { MappedReadGuard::new(Box::new(self.load()), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        let guard = Box::new(self.write());
+        // SAFETY: the copy lives in the boxed guard, which doesn't move
+        unsafe { MappedWriteGuard::new(guard, |g| f(&mut **g)) }
+    }
+}
+
+impl<T: Copy> UniNew<T> for Arc<AtomicCell<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(AtomicCell::new(value))
+    }
+}
+
+impl<T: Copy> UniWeak<T> for Weak<AtomicCell<T>> {
+    type Strong = Arc<AtomicCell<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self)
+    }
+}
+
+/// `Arc<AtomicCell<T>>` as a lock-free [UniRcCopy] handle
+impl<T: Copy> UniRcCopy<T> for Arc<AtomicCell<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(AtomicCell::new(value))
+    }
+
+    fn get(&self) -> T {
+        self.load()
+    }
+
+    fn set(&self, value: T) {
+        self.store(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam_utils::atomic::AtomicCell;
+    use crossbeam_utils::sync::ShardedLock;
+    use std::sync::Arc;
+
+    use crate::{LockError, UniNew, UniRcLock};
+
+    #[test]
+    fn cache_padded() {
+        use super::{padded_shards, PaddedHandle};
+        use crossbeam_utils::CachePadded;
+        use std::{sync::Mutex, thread};
+
+        let shards = padded_shards(8, |i| i as u64);
+        // Adjacent locks don't share a cache line
+        let a = Arc::as_ptr(&shards[0]) as usize;
+        assert_eq!(a % std::mem::align_of::<CachePadded<u8>>(), 0);
+        assert!(std::mem::size_of::<CachePadded<std::sync::RwLock<u64>>>() >= 32);
+
+        thread::scope(|s| {
+            for shard in &shards {
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        *shard.write() += 1;
+                    }
+                });
+            }
+        });
+        let total: u64 = shards.iter().map(|h| *h.read()).sum();
+        assert_eq!(total, (0..8).sum::<u64>() + 800);
+        assert!(format!("{:?}", shards[0]).contains("100"));
+
+        let h: PaddedHandle<i32> = UniNew::new(1);
+        let _r = h.read();
+        assert!(h.try_read().is_ok());
+        assert_eq!(h.try_write().err(), Some(LockError::WouldBlock));
+
+        let m: Arc<CachePadded<Mutex<i32>>> = UniNew::new(1);
+        const { assert!(!<Arc<CachePadded<Mutex<i32>>> as UniRcLock<i32>>::ALLOWS_CONCURRENT_READS) };
+        *m.write() += 1;
+        assert_eq!(m.try_unwrap().unwrap(), 2);
+    }
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    #[test]
+    fn sharded_lock() {
+        let st = Arc::new(ShardedLock::new(State { val: 42 }));
+        st.write().val += 1;
+        let _r = st.read();
+        assert_eq!(st.try_read().unwrap().val, 43);
+        assert_eq!(st.try_write().err(), Some(LockError::WouldBlock));
+    }
+
+    #[test]
+    fn threads_test_sharded_lock() {
+        use std::thread;
+        let st = Arc::new(ShardedLock::new(State { val: 0 }));
+
+        let writer = {
+            let h = st.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    h.write().val += 1;
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..10)
+            .map(|_| {
+                let h = st.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    for _ in 0..1000 {
+                        let v = h.read().val;
+                        assert!(v >= last);
+                        last = v;
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for t in readers {
+            t.join().unwrap();
+        }
+
+        assert_eq!(st.read().val, 100);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Counter(i32);
+
+    fn incr(v: &impl UniRcLock<Counter>) {
+        v.write().0 += 1;
+    }
+
+    #[test]
+    fn atomic_cell() {
+        let st = Arc::new(AtomicCell::new(Counter(0)));
+        incr(&st);
+        let r = st.read();
+        incr(&st);
+        // The copy is not changed by the write
+        assert_eq!(*r, Counter(1));
+        assert_eq!(*st.read(), Counter(2));
+        *st.map_write(|c| &mut c.0) += 10;
+        assert_eq!(st.load(), Counter(12));
+    }
+
+    #[test]
+    fn atomic_cell_unmodified_guard() {
+        let st = Arc::new(AtomicCell::new(Counter(0)));
+        let w = st.write();
+        assert_eq!(*w, Counter(0));
+        st.store(Counter(5));
+        // The guard wasn't written to, so it doesn't store anything
+        drop(w);
+        assert_eq!(st.load(), Counter(5));
+    }
+
+    #[test]
+    fn atomic_cell_copy() {
+        use crate::UniRcCopy;
+
+        let st: Arc<AtomicCell<Counter>> = UniRcCopy::new(Counter(1));
+        let h = st.clone();
+        std::thread::spawn(move || UniRcCopy::set(&h, Counter(2)))
+            .join()
+            .unwrap();
+        assert_eq!(UniRcCopy::get(&st), Counter(2));
+    }
+}
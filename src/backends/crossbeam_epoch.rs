@@ -0,0 +1,422 @@
+// Implementation of read-copy-update (RCU) on crossbeam-epoch.
+// Reading pins the epoch and dereferences the current value without locking.
+// Writing clones the current value into a staging copy, which is published
+// as a whole when the write guard is dropped. The replaced value is destroyed
+// only after all the threads pinned at the time of publishing are unpinned.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned};
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::{self, atomic::Ordering, Arc, Mutex, MutexGuard, PoisonError, TryLockError},
+};
+
+// The shared state of all clones of RcuHandle
+struct Rcu<T> {
+    // Never null, except while the value is moved out in try_unwrap()
+    current: Atomic<T>,
+    // Serializes the writers, so no write is lost
+    writer: Mutex<()>,
+}
+
+impl<T> Drop for Rcu<T> {
+    fn drop(&mut self) {
+        // SAFETY: no guards are alive since they borrow a handle,
+        // and the previous values are deferred separately
+        unsafe {
+            let current = self.current.load(Ordering::Acquire, epoch::unprotected());
+            if !current.is_null() {
+                drop(current.into_owned());
+            }
+        }
+    }
+}
+
+/// Handle to a value updated with read-copy-update (RCU) on `crossbeam-epoch`.
+///
+/// Intended for the data read very often and replaced rarely, like routing tables.
+/// `read()` is wait-free: it pins the current epoch and returns the current
+/// snapshot of the value, which stays valid and unchanged while the guard
+/// is alive, even if a new value is published in the meantime.
+///
+/// `write()` returns a guard holding a clone of the current value, which
+/// replaces the stored value when the guard is dropped. The writers are
+/// serialized by a mutex, so no write is lost, but the readers are never blocked.
+/// The replaced value is destroyed later, when no reader could observe it.
+/// The changes are not visible to the readers until the write guard is dropped.
+pub struct RcuHandle<T> {
+    inner: Arc<Rcu<T>>,
+}
+
+impl<T> Clone for RcuHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// Weak counterpart of [RcuHandle]
+pub struct RcuWeak<T> {
+    inner: sync::Weak<Rcu<T>>,
+}
+
+impl<T> Clone for RcuWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: sync::Weak::clone(&self.inner),
+        }
+    }
+}
+
+/// Read guard of [RcuHandle]. Keeps the epoch pinned,
+/// so the snapshot of the value is not destroyed.
+pub struct RcuReadGuard<'a, T> {
+    value: *const T,
+    _guard: Guard,
+    _handle: PhantomData<&'a T>,
+}
+
+impl<T> Deref for RcuReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: the value is never destroyed while the epoch is pinned
+        unsafe { &*self.value }
+    }
+}
+
+/// Write guard of [RcuHandle]. Holds a staging copy of the value,
+/// which is published when the guard is dropped.
+pub struct RcuWriteGuard<'a, T> {
+    rcu: &'a Rcu<T>,
+    // Boxed, so that the value doesn't move together with the guard
+    value: Option<Box<T>>,
+    // Released after publishing
+    _lock: MutexGuard<'a, ()>,
+}
+
+impl<T> Deref for RcuWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for RcuWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for RcuWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // The staging copy may be half-modified if the write panicked,
+        // so it is dropped without publishing
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(value) = self.value.take() {
+            let guard = epoch::pin();
+            let old = self
+                .rcu
+                .current
+                .swap(Owned::from(value), Ordering::AcqRel, &guard);
+            // SAFETY: the old value is unreachable for the new readers,
+            // and the current ones keep their epochs pinned
+            unsafe { guard.defer_destroy(old) };
+        }
+    }
+}
+
+impl<T: Clone> Rcu<T> {
+    fn write_guard<'a>(&'a self, lock: MutexGuard<'a, ()>) -> RcuWriteGuard<'a, T> {
+        let guard = epoch::pin();
+        // SAFETY: the current value is never null and is protected by the pin
+        let value = unsafe { self.current.load(Ordering::Acquire, &guard).deref() }.clone();
+        RcuWriteGuard {
+            rcu: self,
+            value: Some(Box::new(value)),
+            _lock: lock,
+        }
+    }
+}
+
+// Implementation for RcuHandle<T>
+//
+// The replaced values are destroyed by any thread, hence T: Send + 'static.
+// Panics in write guards don't poison the handle since the staging copy
+// of a panicked write is discarded, so the writer mutex poisoning is ignored.
+impl<T: Clone + Send + 'static> UniRcLock<T> for RcuHandle<T> {
+    type OutRead<'a> = RcuReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = RcuWriteGuard<'a, T> where T: 'a;
+    type Weak = RcuWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<RcuReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<RcuWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let guard = epoch::pin();
+        let value = self.inner.current.load(Ordering::Acquire, &guard).as_raw();
+        RcuReadGuard {
+            value,
+            _guard: guard,
+            _handle: PhantomData,
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let lock = self
+            .inner
+            .writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.inner.write_guard(lock)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(self.read())
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let lock = match self.inner.writer.try_lock() {
+            Ok(lock) => lock,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(LockError::WouldBlock),
+        };
+        Ok(self.inner.write_guard(lock))
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.inner)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    fn lock_id(&self) -> *const () {
+        Arc::as_ptr(&self.inner).cast()
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        RcuWeak {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        let mut rcu = Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })?;
+        let current = std::mem::replace(&mut rcu.current, Atomic::null());
+        // SAFETY: the handle was unique, so no guards are alive
+        Ok(*unsafe { current.into_owned() }.into_box())
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        let rcu = Arc::get_mut(&mut self.inner)?;
+        // SAFETY: the handle is unique, so no guards are alive,
+        // and the current value is never null
+        Some(unsafe {
+            rcu.current
+                .load(Ordering::Relaxed, epoch::unprotected())
+                .deref_mut()
+        })
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the snapshot lives on the heap, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the staging value is boxed, not stored in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Clone + Send + 'static> UniNew<T> for RcuHandle<T> {
+    fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(Rcu {
+                current: Atomic::new(value),
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> UniWeak<T> for RcuWeak<T> {
+    type Strong = RcuHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.inner.upgrade().map(|inner| RcuHandle { inner })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::RcuHandle;
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug, Clone)]
+    struct Route {
+        version: usize,
+        hops: Vec<usize>,
+    }
+
+    #[test]
+    fn rcu() {
+        let h = RcuHandle::new(Route {
+            version: 0,
+            hops: vec![0],
+        });
+        let snapshot = h.read();
+        {
+            let mut w = h.write();
+            w.version += 1;
+            w.hops.push(1);
+            // Not published yet, and the writers are serialized
+            assert_eq!(h.read().version, 0);
+            assert_eq!(h.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(h.read().version, 1);
+        assert_eq!(*h.map_read(|r| &r.hops), [0, 1]);
+        // The old snapshot is still intact
+        assert_eq!(snapshot.version, 0);
+        drop(snapshot);
+
+        // A panicked write is not published and doesn't poison the handle
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut w = h.write();
+            w.version += 1;
+            panic!("interrupted write");
+        }));
+        assert!(res.is_err());
+        assert_eq!(h.read().version, 1);
+        assert!(h.try_write().is_ok());
+
+        let w = h.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&h));
+        drop(w);
+        let mut h2 = h.clone();
+        assert!(h2.get_mut().is_none());
+        drop(h);
+        h2.get_mut().unwrap().version += 1;
+        assert_eq!(h2.try_unwrap().ok().unwrap().version, 2);
+    }
+
+    #[test]
+    fn rcu_swap() {
+        // Each write publishes a new snapshot, which must not change the lock order
+        let a = RcuHandle::new(1);
+        let b = RcuHandle::new(2);
+        assert_eq!(a.lock_id(), a.clone().lock_id());
+        let id = b.lock_id();
+        *b.write() += 0;
+        assert_eq!(b.lock_id(), id);
+        thread::scope(|s| {
+            for i in 0..4 {
+                let (a, b) = (&a, &b);
+                s.spawn(move || {
+                    for _ in 0..1000 {
+                        match i {
+                            0 => a.swap(b),
+                            1 => b.swap(a),
+                            _ => *b.write() += 0,
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(*a.read() + *b.read(), 3);
+
+        // The handles of the same value are locked once, whatever is published
+        let handles = [b.clone(), a.clone(), b.clone()];
+        let mut guards = crate::write_all(&handles);
+        assert_eq!(guards.len(), 2);
+        *guards[0] += 10;
+        drop(guards);
+        assert_eq!(*a.read() + *b.read(), 13);
+    }
+
+    // Counts the live values, so leaks and double frees are detected
+    static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(usize, Vec<usize>);
+
+    impl Counted {
+        fn new(v: usize) -> Self {
+            LIVE.fetch_add(1, Ordering::SeqCst);
+            Counted(v, vec![v; 16])
+        }
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            LIVE.fetch_add(1, Ordering::SeqCst);
+            Counted(self.0, self.1.clone())
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            LIVE.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Also checked under miri with tree borrows, since crossbeam-epoch itself
+    // violates stacked borrows, and the garbage of the global collector may
+    // outlive the test: MIRIFLAGS="-Zmiri-tree-borrows -Zmiri-ignore-leaks"
+    #[test]
+    fn rcu_stress() {
+        const WRITES: usize = if cfg!(miri) { 20 } else { 2000 };
+        let h = RcuHandle::new(Counted::new(0));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut last = 0;
+                    while last < WRITES {
+                        let c = h.read();
+                        // Snapshots are consistent and never go back in time
+                        assert!(c.1.iter().all(|&v| v == c.0));
+                        assert!(c.0 >= last);
+                        last = c.0;
+                    }
+                });
+            }
+            for _ in 0..2 {
+                s.spawn(|| loop {
+                    let mut w = h.write();
+                    if w.0 == WRITES {
+                        break;
+                    }
+                    *w = Counted::new(w.0 + 1);
+                });
+            }
+        });
+        assert_eq!(h.read().0, WRITES);
+        drop(h);
+        // The replaced values are destroyed once the epoch advances
+        for _ in 0..1000 {
+            if LIVE.load(Ordering::SeqCst) == 0 {
+                break;
+            }
+            crossbeam_epoch::pin().flush();
+        }
+        assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+    }
+}
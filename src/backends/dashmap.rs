@@ -0,0 +1,306 @@
+// Implementation for the entries of dashmap::DashMap.
+// Each handle refers to a single entry of a shared map, and locks
+// only the shard of the map holding its key.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use dashmap::{
+    mapref::one::{Ref, RefMut},
+    try_result::TryResult,
+    DashMap,
+};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::{self, Arc},
+};
+
+/// Handle to the value of a single entry of a shared `Arc<DashMap<K, V>>`.
+///
+/// Intended for many independent states kept in one map, like the sessions
+/// keyed by their ids: the generic code sees each of them as a separate handle.
+/// `read()` and `write()` lock only the shard of the map holding the key,
+/// so the entries in different shards are accessed in parallel,
+/// while the accesses to the same entry are serialized.
+/// Beware that locking another key of the same shard, or calling
+/// the methods of the map which lock all the shards, while a guard is alive
+/// deadlocks.
+///
+/// The handle is created by [entry_handle], which inserts the default value
+/// if the key is missing. If the entry is removed from the map later,
+/// `read()` and `write()` panic.
+///
+/// The handles share the map rather than the entry, so `strong_count()`
+/// counts all the handles of the map and the other `Arc`s pointing to it,
+/// and `try_unwrap()` succeeds only if this handle is the only one left.
+/// `new()` creates a new map with a single entry under the default key.
+pub struct MapEntryHandle<K, V> {
+    map: Arc<DashMap<K, V>>,
+    key: K,
+}
+
+/// Create a handle to the entry of the map, inserting the default value
+/// if the key is missing.
+pub fn entry_handle<K, V>(map: &Arc<DashMap<K, V>>, key: K) -> MapEntryHandle<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Default,
+{
+    map.entry(key.clone()).or_default();
+    MapEntryHandle {
+        map: Arc::clone(map),
+        key,
+    }
+}
+
+impl<K, V> MapEntryHandle<K, V> {
+    /// The key of the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The map holding the entry
+    pub fn map(&self) -> &Arc<DashMap<K, V>> {
+        &self.map
+    }
+}
+
+impl<K: Clone, V> Clone for MapEntryHandle<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: Arc::clone(&self.map),
+            key: self.key.clone(),
+        }
+    }
+}
+
+/// Weak counterpart of [MapEntryHandle]
+pub struct MapEntryWeak<K, V> {
+    map: sync::Weak<DashMap<K, V>>,
+    key: K,
+}
+
+impl<K: Clone, V> Clone for MapEntryWeak<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: sync::Weak::clone(&self.map),
+            key: self.key.clone(),
+        }
+    }
+}
+
+#[cold]
+fn missing(key: &impl Debug) -> ! {
+    panic!("Key {key:?} should be present in the map")
+}
+
+// Implementation for MapEntryHandle<K, V>
+//
+// get_mut() always returns None since the entries of a shared map
+// are never accessed without locking their shard.
+impl<K, V> UniRcLock<V> for MapEntryHandle<K, V>
+where
+    K: Eq + Hash + Clone + Debug + Default,
+{
+    type OutRead<'a> = Ref<'a, K, V> where Self: 'a;
+    type OutWrite<'a> = RefMut<'a, K, V> where Self: 'a;
+    type Weak = MapEntryWeak<K, V>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<Ref<'a, K, V>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<RefMut<'a, K, V>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.map.get(&self.key).unwrap_or_else(|| missing(&self.key))
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.map
+            .get_mut(&self.key)
+            .unwrap_or_else(|| missing(&self.key))
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        match self.map.try_get(&self.key) {
+            TryResult::Present(r) => Ok(r),
+            TryResult::Locked => Err(LockError::WouldBlock),
+            TryResult::Absent => missing(&self.key),
+        }
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        match self.map.try_get_mut(&self.key) {
+            TryResult::Present(r) => Ok(r),
+            TryResult::Locked => Err(LockError::WouldBlock),
+            TryResult::Absent => missing(&self.key),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.map)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.map)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.map, &other.map) && self.key == other.key
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        MapEntryWeak {
+            map: Arc::downgrade(&self.map),
+            key: self.key.clone(),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<V, Self> {
+        let key = self.key;
+        Arc::try_unwrap(self.map)
+            .map(|map| map.remove(&key).unwrap_or_else(|| missing(&key)).1)
+            .map_err(|map| Self { map, key })
+    }
+
+    fn get_mut(&mut self) -> Option<&mut V> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&V) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the map, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut V) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the map, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<K, V> UniNew<V> for MapEntryHandle<K, V>
+where
+    K: Eq + Hash + Clone + Debug + Default,
+{
+    fn new(value: V) -> Self {
+        let map = DashMap::new();
+        map.insert(K::default(), value);
+        Self {
+            map: Arc::new(map),
+            key: K::default(),
+        }
+    }
+}
+
+impl<K, V> UniWeak<V> for MapEntryWeak<K, V>
+where
+    K: Eq + Hash + Clone + Debug + Default,
+{
+    type Strong = MapEntryHandle<K, V>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.map.upgrade().map(|map| MapEntryHandle {
+            map,
+            key: self.key.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use dashmap::DashMap;
+
+    use super::{entry_handle, MapEntryHandle};
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug, Default)]
+    struct Session {
+        requests: usize,
+        user: String,
+    }
+
+    // The generic code, which knows nothing about the map
+    fn serve<H: UniRcLock<Session>>(h: &H) {
+        h.write().requests += 1;
+    }
+
+    #[test]
+    fn dashmap() {
+        let map = Arc::new(DashMap::new());
+        map.insert(1, Session {
+            requests: 0,
+            user: "alice".into(),
+        });
+        let alice = entry_handle(&map, 1);
+        let bob = entry_handle(&map, 2);
+        serve(&alice);
+        serve(&bob);
+        bob.write().user = "bob".into();
+        assert_eq!(map.get(&1).unwrap().requests, 1);
+        assert_eq!(&*bob.map_read(|s| s.user.as_str()), "bob");
+        assert!(alice.ptr_eq(&alice.clone()));
+        assert!(!alice.ptr_eq(&bob));
+
+        let w = alice.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&alice));
+        let alice = alice.try_unwrap().err().unwrap();
+        drop((map, bob));
+        assert_eq!(alice.try_unwrap().ok().unwrap().user, "alice");
+        assert!(w.upgrade().is_none());
+
+        let h = MapEntryHandle::<String, Session>::new(Session::default());
+        assert_eq!(h.key(), "");
+        serve(&h);
+        assert_eq!(h.read().requests, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Key 2 should be present")]
+    fn dashmap_removed() {
+        let map = Arc::new(DashMap::<usize, Session>::new());
+        let h = entry_handle(&map, 2);
+        map.remove(&2);
+        serve(&h);
+    }
+
+    #[test]
+    fn dashmap_parallel() {
+        let map = Arc::new(DashMap::<usize, Session>::new());
+        let handles: Vec<_> = (0..64).map(|key| entry_handle(&map, key)).collect();
+        let first = &handles[0];
+        let mut w = first.write();
+        // Another key in an unlocked shard
+        let other = handles.iter().find(|h| h.try_write().is_ok()).unwrap();
+        let same = first.clone();
+        assert_eq!(same.try_read().err(), Some(LockError::WouldBlock));
+
+        let released = AtomicBool::new(false);
+        thread::scope(|s| {
+            // Proceeds while the first entry is locked
+            s.spawn(|| serve(other)).join().unwrap();
+            // Waits until the first entry is unlocked
+            let t = s.spawn(|| {
+                serve(&same);
+                assert!(released.load(Ordering::SeqCst));
+            });
+            thread::sleep(Duration::from_millis(50));
+            w.requests += 10;
+            released.store(true, Ordering::SeqCst);
+            drop(w);
+            t.join().unwrap();
+        });
+        assert_eq!(other.read().requests, 1);
+        assert_eq!(first.read().requests, 11);
+    }
+}
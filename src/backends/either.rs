@@ -0,0 +1,222 @@
+// Implementation for either::Either of two handles, which allows choosing
+// the backend at runtime. The guards are Either of the guards of both sides,
+// which deref to T since both sides deref to it.
+
+use crate::{CheckedResult, LockError, UniNew, UniRcLock, UniWeak};
+use either::Either::{self, Left, Right};
+
+/// Implementation for `Either<L, R>` of two handles to the same value type,
+/// like `Either<Rc<RefCell<T>>, Arc<RwLock<T>>>`, for choosing at runtime
+/// whether the value is shared between threads.
+///
+/// Every operation is delegated to the handle which is present.
+/// `new()` creates the left handle, while the right one is created
+/// explicitly by `Right(R::new(value))`. The handles of different sides
+/// are never `ptr_eq()`.
+impl<T, L, R> UniRcLock<T> for Either<L, R>
+where
+    L: UniRcLock<T>,
+    R: UniRcLock<T>,
+{
+    type OutRead<'a> = Either<L::OutRead<'a>, R::OutRead<'a>> where Self: 'a;
+    type OutWrite<'a> = Either<L::OutWrite<'a>, R::OutWrite<'a>> where Self: 'a;
+    type Weak = Either<L::Weak, R::Weak>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        Either<L::MappedRead<'a, U>, R::MappedRead<'a, U>> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        Either<L::MappedWrite<'a, U>, R::MappedWrite<'a, U>> where Self: 'a;
+    const ALLOWS_CONCURRENT_READS: bool =
+        L::ALLOWS_CONCURRENT_READS && R::ALLOWS_CONCURRENT_READS;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.as_ref().map_either(L::read, R::read)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.as_ref().map_either(L::write, R::write)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        match self {
+            Left(h) => h.try_read().map(Left),
+            Right(h) => h.try_read().map(Right),
+        }
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        match self {
+            Left(h) => h.try_write().map(Left),
+            Right(h) => h.try_write().map(Right),
+        }
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        match self {
+            Left(h) => h.read_checked().map(Left).map_err(|err| err.map(Left)),
+            Right(h) => h.read_checked().map(Right).map_err(|err| err.map(Right)),
+        }
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        match self {
+            Left(h) => h.write_checked().map(Left).map_err(|err| err.map(Left)),
+            Right(h) => h.write_checked().map(Right).map_err(|err| err.map(Right)),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        either::for_both!(self, h => h.strong_count())
+    }
+
+    fn weak_count(&self) -> usize {
+        either::for_both!(self, h => h.weak_count())
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Left(a), Left(b)) => a.ptr_eq(b),
+            (Right(a), Right(b)) => a.ptr_eq(b),
+            _ => false,
+        }
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        self.as_ref().map_either(L::downgrade, R::downgrade)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        match self {
+            Left(h) => h.try_unwrap().map_err(Left),
+            Right(h) => h.try_unwrap().map_err(Right),
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        either::for_both!(self, h => h.get_mut())
+    }
+
+    fn as_ptr(&self) -> *const T {
+        either::for_both!(self, h => h.as_ptr())
+    }
+
+    fn lock_id(&self) -> *const () {
+        either::for_both!(self, h => h.lock_id())
+    }
+
+    fn is_poisoned(&self) -> bool {
+        either::for_both!(self, h => h.is_poisoned())
+    }
+
+    fn clear_poison(&self) {
+        either::for_both!(self, h => h.clear_poison())
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        match self {
+            Left(h) => Left(h.map_read(f)),
+            Right(h) => Right(h.map_read(f)),
+        }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        match self {
+            Left(h) => Left(h.map_write(f)),
+            Right(h) => Right(h.map_write(f)),
+        }
+    }
+}
+
+impl<T, L, R> UniNew<T> for Either<L, R>
+where
+    L: UniNew<T>,
+    R: UniRcLock<T>,
+{
+    fn new(value: T) -> Self {
+        Left(L::new(value))
+    }
+}
+
+impl<T, L, R> UniWeak<T> for Either<L, R>
+where
+    L: UniWeak<T>,
+    R: UniWeak<T>,
+{
+    type Strong = Either<L::Strong, R::Strong>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        match self {
+            Left(w) => w.upgrade().map(Left),
+            Right(w) => w.upgrade().map(Right),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UniNew, UniRcLock, UniWeak};
+    use either::Either::{self, Right};
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{Arc, RwLock},
+    };
+
+    struct State {
+        val: i32,
+    }
+
+    struct StateHandler<T: UniRcLock<State>> {
+        state: T,
+    }
+
+    type Local = Rc<RefCell<State>>;
+    type Shared = Arc<RwLock<State>>;
+
+    fn make_handler(val: i32, shared: bool) -> StateHandler<Either<Local, Shared>> {
+        let state = State { val };
+        StateHandler {
+            state: if shared {
+                Right(UniNew::new(state))
+            } else {
+                Either::new(state)
+            },
+        }
+    }
+
+    #[test]
+    fn either() {
+        for shared in [false, true] {
+            let h = make_handler(1, shared);
+            assert_eq!(h.state.is_right(), shared);
+            h.state.write().val += 1;
+            *h.state.map_write(|st| &mut st.val) *= 10;
+            assert_eq!(h.state.read().val, 20);
+            assert_eq!(*h.state.map_read(|st| &st.val), 20);
+
+            let other = h.state.clone();
+            assert!(h.state.ptr_eq(&other));
+            assert_eq!(h.state.strong_count(), 2);
+            let _r = other.read();
+            assert!(h.state.try_write().is_err());
+        }
+        let local = make_handler(0, false).state;
+        let shared = make_handler(0, true).state;
+        assert!(!local.ptr_eq(&shared));
+    }
+
+    #[test]
+    fn either_weak() {
+        let h = make_handler(5, true).state;
+        let weak = h.downgrade();
+        assert!(weak.is_right());
+        assert_eq!(weak.upgrade().unwrap().read().val, 5);
+        assert_eq!(h.try_unwrap().ok().unwrap().val, 5);
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -0,0 +1,105 @@
+// Implementations for the embassy-sync async mutex.
+// Available with the `embassy-sync` feature. Only core and alloc are used here,
+// so these backends are usable on the embedded targets without std.
+
+use crate::AsyncUniRcLock;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+};
+
+/// Implementation for `&embassy_sync::mutex::Mutex<M, T>`,
+/// usually a `static` shared by the tasks of the embassy executor.
+///
+/// Since the mutex has no notion of shared readers, both `read()` and `write()`
+/// lock it exclusively. `new()` leaks the value to obtain a static reference.
+impl<M: RawMutex, T> AsyncUniRcLock<T> for &Mutex<M, T> {
+    type OutRead<'a> = MutexGuard<'a, M, T> where Self: 'a;
+    type OutWrite<'a> = MutexGuard<'a, M, T> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        alloc::boxed::Box::leak(alloc::boxed::Box::new(Mutex::new(value)))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.lock().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.lock().await
+    }
+}
+
+/// Implementation for `Arc<embassy_sync::mutex::Mutex<M, T>>`.
+///
+/// Only available on the targets with atomic pointers, which provide `Arc`.
+/// Both `read()` and `write()` lock the mutex exclusively.
+#[cfg(target_has_atomic = "ptr")]
+impl<M: RawMutex, T> AsyncUniRcLock<T> for Arc<Mutex<M, T>> {
+    type OutRead<'a> = MutexGuard<'a, M, T> where Self: 'a;
+    type OutWrite<'a> = MutexGuard<'a, M, T> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        Arc::new(Mutex::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.lock().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.lock().await
+    }
+}
+
+// The tests use the std implementation of the critical section
+// for CriticalSectionRawMutex.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, NoopRawMutex};
+    use embassy_sync::mutex::Mutex;
+    use std::sync::Arc;
+
+    use crate::AsyncUniRcLock;
+
+    #[derive(Debug)]
+    struct Foo(i32);
+
+    async fn incr_foo(v: impl AsyncUniRcLock<Foo>) {
+        v.write().await.0 += 1;
+    }
+
+    #[tokio::test]
+    async fn embassy_static() {
+        static FOO: Mutex<CriticalSectionRawMutex, Foo> = Mutex::new(Foo(0));
+
+        let h = &FOO;
+        incr_foo(h).await;
+        incr_foo(h).await;
+        let r = AsyncUniRcLock::read(&h).await;
+        assert_eq!(r.0, 2);
+        // The reads are exclusive
+        assert!(FOO.try_lock().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn embassy_arc() {
+        let ptr: Arc<Mutex<CriticalSectionRawMutex, Foo>> = AsyncUniRcLock::new(Foo(0));
+        let tasks: Vec<_> = (0..10)
+            .map(|_| tokio::spawn(incr_foo(ptr.clone())))
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        assert_eq!(ptr.read().await.0, 10);
+    }
+
+    #[tokio::test]
+    async fn embassy_noop() {
+        let ptr: &Mutex<NoopRawMutex, Foo> = AsyncUniRcLock::new(Foo(1));
+        incr_foo(ptr).await;
+        assert_eq!(ptr.read().await.0, 2);
+    }
+}
@@ -0,0 +1,158 @@
+// Implementations for the futures-locks locks. Available with the `futures-locks` feature.
+// These locks are reference counted internally and cloning them clones the handle,
+// so they are used directly instead of being wrapped into Arc.
+
+use crate::{AsyncOwnedLock, AsyncUniRcLock, SpawnableAsyncLock};
+use futures_locks::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::future::Future;
+
+/// Implementation for `futures_locks::RwLock<T>`.
+///
+/// The guards own a reference to the lock, so they don't borrow the handle.
+impl<T> AsyncUniRcLock<T> for RwLock<T> {
+    type OutRead<'a> = RwLockReadGuard<T> where Self: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<T> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        RwLock::new(value)
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RwLock::read(self).await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RwLock::write(self).await
+    }
+}
+
+/// The guards are owned already.
+impl<T> AsyncOwnedLock<T> for RwLock<T> {
+    type OwnedRead = RwLockReadGuard<T>;
+    type OwnedWrite = RwLockWriteGuard<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        RwLock::read(&self).await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        RwLock::write(&self).await
+    }
+}
+
+impl<T: Send + Sync + 'static> SpawnableAsyncLock<T> for RwLock<T> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+/// Implementation for `futures_locks::Mutex<T>`.
+///
+/// Like for std `Mutex`, `read()` is exclusive.
+impl<T> AsyncUniRcLock<T> for Mutex<T> {
+    type OutRead<'a> = MutexGuard<T> where Self: 'a;
+    type OutWrite<'a> = MutexGuard<T> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        Mutex::new(value)
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.lock().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.lock().await
+    }
+}
+
+impl<T> AsyncOwnedLock<T> for Mutex<T> {
+    type OwnedRead = MutexGuard<T>;
+    type OwnedWrite = MutexGuard<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        self.lock().await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        self.lock().await
+    }
+}
+
+impl<T: Send + 'static> SpawnableAsyncLock<T> for Mutex<T> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_locks::{Mutex, RwLock};
+
+    use crate::{AsyncOwnedLock, AsyncUniRcLock};
+
+    #[derive(Debug, Default)]
+    struct Log(Vec<usize>);
+
+    // Checks that no other task writes while the guard is held
+    async fn append(v: impl AsyncUniRcLock<Log>, i: usize) {
+        let mut log = v.write().await;
+        let len = log.0.len();
+        tokio::task::yield_now().await;
+        assert_eq!(log.0.len(), len);
+        log.0.push(i);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn futures_locks_rwlock() {
+        let ptr: RwLock<Log> = AsyncUniRcLock::new(Log::default());
+        let tasks: Vec<_> = (0..10)
+            .map(|i| tokio::spawn(append(ptr.clone(), i)))
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        let r1 = AsyncUniRcLock::read(&ptr).await;
+        let r2 = AsyncUniRcLock::read(&ptr).await;
+        assert_eq!(r1.0.len() + r2.0.len(), 20);
+        assert!(ptr.try_write().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn futures_locks_mutex() {
+        let ptr: Mutex<Log> = AsyncUniRcLock::new(Log::default());
+        let tasks: Vec<_> = (0..10)
+            .map(|i| tokio::spawn(append(ptr.clone(), i)))
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        let mut log = AsyncUniRcLock::read(&ptr).await;
+        log.0.sort();
+        assert_eq!(log.0, (0..10).collect::<Vec<_>>());
+        // Reading locks the mutex exclusively
+        assert!(ptr.try_lock().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn futures_locks_owned() {
+        let ptr: RwLock<Log> = AsyncUniRcLock::new(Log::default());
+        let mut w = ptr.clone().write_owned().await;
+        tokio::spawn(async move { w.0.push(1) }).await.unwrap();
+        assert_eq!(ptr.clone().read_owned().await.0, [1]);
+    }
+}
@@ -0,0 +1,286 @@
+// Implementation for ghost_cell::GhostCell.
+// The cells are branded by the lifetime of the GhostToken they belong to,
+// and the token is shared by all handles in Rc<RefCell<GhostToken>>.
+// The guards borrow the token itself, so there is a single runtime check
+// per guard and no per-cell borrow tracking at all.
+// Only core and alloc are used here, so this backend is usable without std.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniRcLock, UniWeak};
+use alloc::rc::{self, Rc};
+use core::{
+    cell::{Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+};
+use ghost_cell::{GhostCell, GhostToken};
+
+/// Handle to a value stored in `Rc<GhostCell<'brand, T>>` together with
+/// the shared `GhostToken<'brand>` used to access it.
+///
+/// A `GhostToken` only exists inside the closure passed to `GhostToken::new`,
+/// and the `'brand` lifetime ties each handle to that closure.
+/// Handles can't escape it and can't be used with any other token:
+/// ```compile_fail
+/// # use std::{rc::Rc, cell::RefCell};
+/// # use ghost_cell::GhostToken;
+/// # use uni_rc_lock::GhostHandle;
+/// let escaped = GhostToken::new(|token| {
+///     let token = Rc::new(RefCell::new(token));
+///     GhostHandle::with_token(&token, 42)
+/// });
+/// ```
+/// For the same reason it can't create the token, so it doesn't implement
+/// [UniNew](crate::UniNew). The handles are created by [GhostHandle::with_token] instead.
+///
+/// Reading through any handle of the token borrows it immutably, while
+/// writing borrows it mutably, so a write guard of one handle can't coexist
+/// with any other guard of the handles sharing this token.
+///
+/// ```
+/// # use std::{rc::Rc, cell::RefCell};
+/// # use ghost_cell::GhostToken;
+/// # use uni_rc_lock::{GhostHandle, UniRcLock};
+/// struct State {val: i32}
+///
+/// #[derive(Clone)]
+/// struct StateHandler<T: UniRcLock<State>> {
+///     state: T,
+/// }
+///
+/// let res = GhostToken::new(|token| {
+///     let token = Rc::new(RefCell::new(token));
+///     let st_handler = StateHandler {
+///         state: GhostHandle::with_token(&token, State { val: 42 }),
+///     };
+///     st_handler.state.write().val += 1;
+///     let val = st_handler.state.read().val;
+///     val
+/// });
+/// assert_eq!(res, 43);
+/// ```
+pub struct GhostHandle<'brand, T> {
+    cell: Rc<GhostCell<'brand, T>>,
+    token: Rc<RefCell<GhostToken<'brand>>>,
+}
+
+impl<'brand, T> GhostHandle<'brand, T> {
+    /// Create a handle accessed through the given token
+    pub fn with_token(token: &Rc<RefCell<GhostToken<'brand>>>, value: T) -> Self {
+        Self {
+            cell: Rc::new(GhostCell::new(value)),
+            token: Rc::clone(token),
+        }
+    }
+
+    /// The token used to access this handle
+    pub fn token(&self) -> &Rc<RefCell<GhostToken<'brand>>> {
+        &self.token
+    }
+}
+
+impl<T> Clone for GhostHandle<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: Rc::clone(&self.cell),
+            token: Rc::clone(&self.token),
+        }
+    }
+}
+
+/// Weak counterpart of [GhostHandle]
+pub struct GhostWeak<'brand, T> {
+    cell: rc::Weak<GhostCell<'brand, T>>,
+    token: Rc<RefCell<GhostToken<'brand>>>,
+}
+
+impl<T> Clone for GhostWeak<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: rc::Weak::clone(&self.cell),
+            token: Rc::clone(&self.token),
+        }
+    }
+}
+
+/// Read guard of [GhostHandle]. Holds the shared borrow of the token.
+pub struct GhostReadGuard<'a, 'brand, T> {
+    token: Ref<'a, GhostToken<'brand>>,
+    cell: &'a GhostCell<'brand, T>,
+}
+
+impl<T> Deref for GhostReadGuard<'_, '_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.cell.borrow(&self.token)
+    }
+}
+
+/// Write guard of [GhostHandle]. Holds the exclusive borrow of the token.
+pub struct GhostWriteGuard<'a, 'brand, T> {
+    token: RefMut<'a, GhostToken<'brand>>,
+    cell: &'a GhostCell<'brand, T>,
+}
+
+impl<T> Deref for GhostWriteGuard<'_, '_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.cell.borrow(&self.token)
+    }
+}
+
+impl<T> DerefMut for GhostWriteGuard<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.cell.borrow_mut(&mut self.token)
+    }
+}
+
+// Implementation for GhostHandle<'brand, T>
+impl<'brand, T> UniRcLock<T> for GhostHandle<'brand, T> {
+    type OutRead<'a> = GhostReadGuard<'a, 'brand, T> where Self: 'a;
+    type OutWrite<'a> = GhostWriteGuard<'a, 'brand, T> where Self: 'a;
+    type Weak = GhostWeak<'brand, T>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<GhostReadGuard<'a, 'brand, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<GhostWriteGuard<'a, 'brand, T>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        GhostReadGuard {
+            token: self.token.borrow(),
+            cell: &self.cell,
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        GhostWriteGuard {
+            token: self.token.borrow_mut(),
+            cell: &self.cell,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let token = self.token.try_borrow().map_err(|_| LockError::WouldBlock)?;
+        Ok(GhostReadGuard {
+            token,
+            cell: &self.cell,
+        })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let token = self
+            .token
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(GhostWriteGuard {
+            token,
+            cell: &self.cell,
+        })
+    }
+
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.cell)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(&self.cell)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.cell, &other.cell)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        GhostWeak {
+            cell: Rc::downgrade(&self.cell),
+            token: Rc::clone(&self.token),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        let token = self.token;
+        Rc::try_unwrap(self.cell)
+            .map(GhostCell::into_inner)
+            .map_err(|cell| Self { cell, token })
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Rc::get_mut(&mut self.cell).map(GhostCell::get_mut)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<'brand, T> UniWeak<T> for GhostWeak<'brand, T> {
+    type Strong = GhostHandle<'brand, T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.cell.upgrade().map(|cell| GhostHandle {
+            cell,
+            token: Rc::clone(&self.token),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use ghost_cell::GhostToken;
+
+    use super::GhostHandle;
+    use crate::{LockError, UniRcLock, UniWeak};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr(p: &impl UniRcLock<State>) {
+        p.write().val += 1;
+    }
+
+    #[test]
+    fn ghost_cell() {
+        GhostToken::new(|token| {
+            let token = Rc::new(RefCell::new(token));
+            let st1 = GhostHandle::with_token(&token, State { val: 1 });
+            let st2 = GhostHandle::with_token(&token, State { val: 2 });
+            incr(&st1);
+            incr(&st2);
+            {
+                let r1 = st1.read();
+                let r2 = st2.read();
+                assert_eq!(r1.val + r2.val, 5);
+                assert_eq!(st1.try_write().err(), Some(LockError::WouldBlock));
+            }
+            let w = st1.downgrade();
+            assert!(w.upgrade().unwrap().ptr_eq(&st1));
+            assert_eq!(st2.try_unwrap().ok().unwrap().val, 3);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn ghost_cell_conflicting_write() {
+        GhostToken::new(|token| {
+            let token = Rc::new(RefCell::new(token));
+            let st1 = GhostHandle::with_token(&token, State { val: 1 });
+            let st2 = GhostHandle::with_token(&token, State { val: 2 });
+            let _r = st1.read();
+            incr(&st2);
+        });
+    }
+}
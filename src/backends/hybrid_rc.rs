@@ -0,0 +1,272 @@
+// Implementations for hybrid_rc::HybridRc, which could be converted
+// between the local (non-atomic) and the shared (atomic) reference counting.
+// The handle could start local and become shared with to_shared()
+// before crossing the thread boundary.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use core::marker::PhantomData;
+use hybrid_rc::{
+    state::{Local, Shared},
+    HybridRc,
+};
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Weak counterpart of `hybrid_rc::HybridRc` handles.
+///
+/// Upgrades to `hybrid_rc::Rc` for local handles and to `hybrid_rc::Arc`
+/// for shared handles. Upgrading a local handle fails if the value is
+/// currently owned by local handles of another thread.
+pub struct HybridWeak<L, S> {
+    weak: hybrid_rc::Weak<L>,
+    _state: PhantomData<S>,
+}
+
+impl<L, S> Clone for HybridWeak<L, S> {
+    fn clone(&self) -> Self {
+        Self {
+            weak: self.weak.clone(),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<L, S> HybridWeak<L, S> {
+    fn new(weak: hybrid_rc::Weak<L>) -> Self {
+        Self {
+            weak,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<T> UniWeak<T> for HybridWeak<RefCell<T>, Local> {
+    type Strong = hybrid_rc::Rc<RefCell<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.weak.upgrade_local().ok()
+    }
+}
+
+impl<T> UniWeak<T> for HybridWeak<RwLock<T>, Local> {
+    type Strong = hybrid_rc::Rc<RwLock<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.weak.upgrade_local().ok()
+    }
+}
+
+impl<T> UniWeak<T> for HybridWeak<RwLock<T>, Shared> {
+    type Strong = hybrid_rc::Arc<RwLock<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.weak.upgrade().ok()
+    }
+}
+
+// Implementation for hybrid_rc::Rc<RefCell<T>>
+impl<T> UniRcLock<T> for hybrid_rc::Rc<RefCell<T>> {
+    type OutRead<'a> = Ref<'a, T> where T: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where T: 'a;
+    type Weak = HybridWeak<RefCell<T>, Local>;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RefCell::borrow(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RefCell::borrow_mut(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        RefCell::try_borrow(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        RefCell::try_borrow_mut(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn strong_count(&self) -> usize {
+        HybridRc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        HybridRc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        HybridRc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        HybridWeak::new(HybridRc::downgrade(self))
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        HybridRc::try_unwrap(self).map(RefCell::into_inner)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        HybridRc::get_mut(self).map(RefCell::get_mut)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        RefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(self.write(), f)
+    }
+}
+
+impl<T> UniNew<T> for hybrid_rc::Rc<RefCell<T>> {
+    fn new(value: T) -> Self {
+        HybridRc::new(RefCell::new(value))
+    }
+}
+
+// Implementations for hybrid_rc::Rc<RwLock<T>> and hybrid_rc::Arc<RwLock<T>>.
+// The state marker trait is sealed, so they are generated for each state.
+macro_rules! impl_hybrid_rwlock {
+    ($state:ty) => {
+        impl<T> UniRcLock<T> for HybridRc<RwLock<T>, $state> {
+            type OutRead<'a> = RwLockReadGuard<'a, T> where Self: 'a;
+            type OutWrite<'a> = RwLockWriteGuard<'a, T> where Self: 'a;
+            type Weak = HybridWeak<RwLock<T>, $state>;
+            type MappedRead<'a, U: ?Sized + 'a> =
+                MappedReadGuard<RwLockReadGuard<'a, T>, U> where Self: 'a;
+            type MappedWrite<'a, U: ?Sized + 'a> =
+                MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where Self: 'a;
+
+            fn read<'a>(&'a self) -> Self::OutRead<'a> {
+                RwLock::read(self).expect("Read lock should not be poisoned")
+            }
+
+            fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+                RwLock::write(self).expect("Write lock should not be poisoned")
+            }
+
+            fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+                Ok(RwLock::try_read(self)?)
+            }
+
+            fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+                Ok(RwLock::try_write(self)?)
+            }
+
+            fn strong_count(&self) -> usize {
+                HybridRc::strong_count(self)
+            }
+
+            fn weak_count(&self) -> usize {
+                HybridRc::weak_count(self)
+            }
+
+            fn ptr_eq(&self, other: &Self) -> bool {
+                HybridRc::ptr_eq(self, other)
+            }
+
+            fn downgrade(&self) -> Self::Weak {
+                HybridWeak::new(HybridRc::downgrade(self))
+            }
+
+            fn try_unwrap(self) -> Result<T, Self> {
+                HybridRc::try_unwrap(self)
+                    .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+            }
+
+            fn get_mut(&mut self) -> Option<&mut T> {
+                HybridRc::get_mut(self)
+                    .map(|lock| lock.get_mut().unwrap_or_else(PoisonError::into_inner))
+            }
+
+            fn is_poisoned(&self) -> bool {
+                RwLock::is_poisoned(self)
+            }
+
+            fn clear_poison(&self) {
+                RwLock::clear_poison(self)
+            }
+
+            fn map_read<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&T) -> &U,
+            ) -> Self::MappedRead<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedReadGuard::new(self.read(), f) }
+            }
+
+            fn map_write<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&mut T) -> &mut U,
+            ) -> Self::MappedWrite<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedWriteGuard::new(self.write(), f) }
+            }
+        }
+
+        impl<T> UniNew<T> for HybridRc<RwLock<T>, $state> {
+            fn new(value: T) -> Self {
+                HybridRc::new(RwLock::new(value))
+            }
+        }
+    };
+}
+
+impl_hybrid_rwlock!(Local);
+impl_hybrid_rwlock!(Shared);
+
+#[cfg(test)]
+mod tests {
+    use hybrid_rc::HybridRc;
+    use std::{cell::RefCell, sync::RwLock};
+
+    use crate::{UniRcLock, UniWeak};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr(p: &impl UniRcLock<State>) {
+        p.write().val += 1;
+    }
+
+    #[test]
+    fn hybrid_local_to_shared() {
+        let local = hybrid_rc::Rc::new(RwLock::new(State { val: 42 }));
+        incr(&local);
+
+        let shared = HybridRc::to_shared(&local);
+        assert!(shared.ptr_eq(&HybridRc::to_shared(&local)));
+        std::thread::spawn(move || incr(&shared)).join().unwrap();
+
+        assert_eq!(local.read().val, 44);
+        let w = local.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&local));
+        drop(local);
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn hybrid_refcell() {
+        let local = hybrid_rc::Rc::new(RefCell::new(State { val: 42 }));
+        incr(&local);
+        assert_eq!(local.clone().strong_count(), 2);
+        assert_eq!(local.try_unwrap().ok().unwrap().val, 43);
+    }
+}
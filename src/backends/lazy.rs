@@ -0,0 +1,174 @@
+// Implementations for the lazily initialized global locks. Available with the `std` feature.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, NoWeak, UniRcLock};
+use std::sync::{LazyLock, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Declare global variables holding a value in a lazily initialized `RwLock`.
+///
+/// Each variable is a `static LazyLock<RwLock<T>>` and a reference to it
+/// is a [UniRcLock] handle. The value is initialized by the first access
+/// in a thread-safe way.
+/// ```
+/// # use uni_rc_lock::{uni_static, UniRcLock};
+/// uni_static! {
+///     static COUNTER: i32 = 40 + 1;
+/// }
+///
+/// fn incr(v: impl UniRcLock<i32>) {
+///     *v.write() += 1;
+/// }
+///
+/// incr(&COUNTER);
+/// assert_eq!(*COUNTER.read().unwrap(), 42);
+/// ```
+#[macro_export]
+macro_rules! uni_static {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr;)*) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: ::std::sync::LazyLock<::std::sync::RwLock<$ty>> =
+                ::std::sync::LazyLock::new(|| ::std::sync::RwLock::new($init));
+        )*
+    };
+}
+
+/// Initialize the `OnceLock` with a `RwLock` holding the value returned by `init`
+/// if it is not initialized yet, and return a reference to the lock.
+///
+/// The reference is a [UniRcLock] handle.
+pub fn get_or_init<T>(cell: &OnceLock<RwLock<T>>, init: impl FnOnce() -> T) -> &RwLock<T> {
+    cell.get_or_init(|| RwLock::new(init()))
+}
+
+/// Implementation for `&LazyLock<RwLock<T>>`,
+/// usually declared by [uni_static!](crate::uni_static).
+///
+/// The value is initialized by the first access to it.
+/// Like `&RwLock<T>`, it is not reference-counted, has no weak references
+/// and can't be unwrapped. It doesn't implement [UniNew](crate::UniNew),
+/// because `LazyLock` is created from the initializer rather than from the value:
+/// use `OnceLock` with [get_or_init] to initialize the global lock at runtime.
+impl<T, F: FnOnce() -> RwLock<T>> UniRcLock<T> for &LazyLock<RwLock<T>, F> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where Self: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<RwLockReadGuard<'a, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        LazyLock::force(self)
+            .read()
+            .expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        LazyLock::force(self)
+            .write()
+            .expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(LazyLock::force(self).try_read()?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(LazyLock::force(self).try_write()?)
+    }
+
+    fn strong_count(&self) -> usize {
+        1
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn is_poisoned(&self) -> bool {
+        LazyLock::force(self).is_poisoned()
+    }
+
+    fn clear_poison(&self) {
+        LazyLock::force(self).clear_poison()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniRcLock::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniRcLock::write(self), f) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{OnceLock, RwLock};
+    use std::thread;
+
+    use crate::UniRcLock;
+
+    #[derive(Debug)]
+    struct Config {
+        val: i32,
+    }
+
+    fn incr(v: impl UniRcLock<Config> + Send + 'static) -> thread::JoinHandle<()> {
+        thread::spawn(move || v.write().val += 1)
+    }
+
+    crate::uni_static! {
+        static CONFIG: Config = Config { val: 0 };
+        /// Attributes and visibility are passed through
+        pub(crate) static OTHER: Vec<i32> = vec![1, 2, 3];
+    }
+
+    #[test]
+    fn lazy_static() {
+        let threads: Vec<_> = (0..10).map(|_| incr(&CONFIG)).collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(UniRcLock::read(&&CONFIG).val, 10);
+        assert_eq!(*(&OTHER).map_read(|v| &v[1]), 2);
+        assert!((&CONFIG).ptr_eq(&&CONFIG));
+    }
+
+    #[test]
+    fn once_lock() {
+        static CONFIG: OnceLock<RwLock<Config>> = OnceLock::new();
+
+        let threads: Vec<_> = (0..10)
+            .map(|i| incr(super::get_or_init(&CONFIG, || Config { val: i * 100 })))
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(CONFIG.get().unwrap().read().unwrap().val % 100, 10);
+    }
+}
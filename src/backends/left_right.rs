@@ -0,0 +1,348 @@
+// Implementation for left_right.
+// Readers access one of the two copies of the value without any locking,
+// while the single writer modifies the other copy and then swaps them.
+// The writer is shared by all handles behind a mutex. Each write guard
+// replaces the whole value, so the operation log consists of the
+// new values only.
+
+use crate::{LockError, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use left_right::{Absorb, ReadGuard, ReadHandle, WriteHandle};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{self, Arc, Mutex, MutexGuard, PoisonError},
+};
+
+// The wrappers are needed to implement the foreign Absorb trait for any T
+#[derive(Clone)]
+struct Value<T>(T);
+
+struct Replace<T>(T);
+
+impl<T: Clone> Absorb<Replace<T>> for Value<T> {
+    fn absorb_first(&mut self, operation: &mut Replace<T>, _: &Self) {
+        self.0 = operation.0.clone();
+    }
+
+    fn absorb_second(&mut self, operation: Replace<T>, _: &Self) {
+        self.0 = operation.0;
+    }
+
+    fn sync_with(&mut self, first: &Self) {
+        self.0 = first.0.clone();
+    }
+}
+
+type Writer<T> = Mutex<WriteHandle<Value<T>, Replace<T>>>;
+
+/// Handle to a value stored in a `left_right` pair of copies.
+///
+/// Reads are wait-free: `read()` never blocks and never waits for the writer.
+/// Writes are serialized by a mutex shared by all handles. `write()` returns
+/// a guard holding a clone of the current value, which is published to the
+/// readers when the guard is dropped. Until then the readers keep seeing
+/// the old value. The readers that entered before the publication also keep
+/// seeing the old value until they release their guards.
+///
+/// Publishing waits until all readers leave the stale copy, so dropping
+/// a write guard while holding a read guard in the same thread may deadlock.
+///
+/// Each handle has its own reader, so the handle is `Send` but not `Sync`:
+/// clone it for every thread instead of sharing it by reference.
+pub struct LeftRightHandle<T: Clone> {
+    writer: Arc<Writer<T>>,
+    reader: ReadHandle<Value<T>>,
+}
+
+impl<T: Clone> Clone for LeftRightHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: Arc::clone(&self.writer),
+            reader: self.reader.clone(),
+        }
+    }
+}
+
+/// Weak counterpart of [LeftRightHandle]
+pub struct LeftRightWeak<T: Clone> {
+    writer: sync::Weak<Writer<T>>,
+    reader: ReadHandle<Value<T>>,
+}
+
+impl<T: Clone> Clone for LeftRightWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: sync::Weak::clone(&self.writer),
+            reader: self.reader.clone(),
+        }
+    }
+}
+
+/// Write guard of [LeftRightHandle]. Holds the writer and a staging copy
+/// of the value, which is published when the guard is dropped.
+pub struct LeftRightWriteGuard<'a, T: Clone> {
+    writer: MutexGuard<'a, WriteHandle<Value<T>, Replace<T>>>,
+    // Boxed, so that the value doesn't move together with the guard
+    value: Option<Box<T>>,
+}
+
+impl<'a, T: Clone> LeftRightWriteGuard<'a, T> {
+    fn new(writer: MutexGuard<'a, WriteHandle<Value<T>, Replace<T>>>) -> Self {
+        let value = writer
+            .enter()
+            .map(|v| Box::new(v.0.clone()))
+            .expect("Writer is alive while the handle exists");
+        Self {
+            writer,
+            value: Some(value),
+        }
+    }
+}
+
+impl<T: Clone> Deref for LeftRightWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T: Clone> DerefMut for LeftRightWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T: Clone> Drop for LeftRightWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // The staging copy may be half-modified if the write panicked,
+        // so the readers keep the previous value, while the mutex is poisoned
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(value) = self.value.take() {
+            self.writer.append(Replace(*value)).publish();
+        }
+    }
+}
+
+// Implementation for LeftRightHandle<T>
+//
+// get_mut() always returns None since the value has two copies.
+impl<T: Clone> UniRcLock<T> for LeftRightHandle<T> {
+    type OutRead<'a> = ReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = LeftRightWriteGuard<'a, T> where T: 'a;
+    type Weak = LeftRightWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = ReadGuard<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<LeftRightWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let guard = self
+            .reader
+            .enter()
+            .expect("Writer is alive while the handle exists");
+        ReadGuard::map(guard, |v| &v.0)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        LeftRightWriteGuard::new(self.writer.lock().expect("Mutex should not be poisoned"))
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(self.read())
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(LeftRightWriteGuard::new(self.writer.try_lock()?))
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.writer)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.writer)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.writer, &other.writer)
+    }
+
+    fn lock_id(&self) -> *const () {
+        Arc::as_ptr(&self.writer).cast()
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        LeftRightWeak {
+            writer: Arc::downgrade(&self.writer),
+            reader: self.reader.clone(),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        let reader = self.reader;
+        match Arc::try_unwrap(self.writer) {
+            Ok(writer) => {
+                drop(reader);
+                let writer = writer.into_inner().unwrap_or_else(PoisonError::into_inner);
+                // SAFETY: Value has the default Absorb::drop_second, which just drops it
+                Ok(unsafe { writer.take().into_box() }.0)
+            }
+            Err(writer) => Err(Self { writer, reader }),
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.writer.is_poisoned()
+    }
+
+    fn clear_poison(&self) {
+        self.writer.clear_poison()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        ReadGuard::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the staging value is boxed, not stored in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Clone> UniNew<T> for LeftRightHandle<T> {
+    fn new(value: T) -> Self {
+        let (writer, reader) = left_right::new_from_empty(Value(value));
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+            reader,
+        }
+    }
+}
+
+impl<T: Clone> UniWeak<T> for LeftRightWeak<T> {
+    type Strong = LeftRightHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.writer.upgrade().map(|writer| LeftRightHandle {
+            writer,
+            reader: self.reader.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::LeftRightHandle;
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug, Clone, Default)]
+    struct Telemetry {
+        events: u64,
+        last: String,
+    }
+
+    fn record(h: &impl UniRcLock<Telemetry>, event: &str) {
+        let mut w = h.write();
+        w.events += 1;
+        w.last = event.into();
+    }
+
+    #[test]
+    fn left_right() {
+        let h = LeftRightHandle::new(Telemetry::default());
+        {
+            let mut w = h.write();
+            w.events += 1;
+            // Not published yet
+            assert_eq!(h.read().events, 0);
+            assert_eq!(h.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(h.read().events, 1);
+        record(&h, "start");
+        assert_eq!(&*h.map_read(|t| t.last.as_str()), "start");
+
+        // A panicked write is not published, but poisons the handle
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut w = h.write();
+            w.events += 1;
+            panic!("interrupted write");
+        }));
+        assert!(res.is_err());
+        assert_eq!(h.read().events, 2);
+        assert!(h.is_poisoned());
+        h.clear_poison();
+
+        let w = h.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&h));
+        drop(w);
+        assert_eq!(h.try_unwrap().ok().unwrap().events, 2);
+    }
+
+    #[test]
+    fn left_right_content_eq() {
+        // The publications of a concurrent writer don't change the lock order
+        let a = LeftRightHandle::new(0);
+        let b = LeftRightHandle::new(0);
+        let id = b.lock_id() as usize;
+        thread::scope(|s| {
+            let (a2, b2) = (a.clone(), b.clone());
+            s.spawn(move || {
+                for _ in 0..1000 {
+                    a2.content_eq(&b2);
+                    assert_eq!(b2.lock_id() as usize, id);
+                }
+            });
+            let b = b.clone();
+            s.spawn(move || {
+                for _ in 0..1000 {
+                    *b.write() ^= 1;
+                }
+            });
+        });
+        assert!(a.content_eq(&b));
+    }
+
+    #[test]
+    fn left_right_threads() {
+        let h = LeftRightHandle::new(Telemetry::default());
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let h = h.clone();
+                thread::spawn(move || {
+                    // Readers eventually observe the write
+                    let start = Instant::now();
+                    while h.read().events < 100 {
+                        assert!(start.elapsed() < Duration::from_secs(10));
+                        thread::yield_now();
+                    }
+                    assert_eq!(h.read().last, "event 99");
+                })
+            })
+            .collect();
+        let writer = {
+            let h = h.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    record(&h, &format!("event {i}"));
+                }
+            })
+        };
+        writer.join().unwrap();
+        for r in readers {
+            r.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,423 @@
+// Implementations for any lock built on lock_api, like the parking_lot locks.
+// lock_api locks are never poisoned, so read() and write() never panic
+// on poisoning and try_read()/try_write() never return LockError::Poisoned.
+// Only core and alloc are used here, so these backends are usable without std.
+
+use crate::{
+    LockError, MappedReadGuard, MutexBackend, RecursiveRead, TimedLock, UniCell, UniRcLock,
+};
+use ::lock_api::{
+    MappedMutexGuard, MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, MutexGuard, RawMutex,
+    RawMutexTimed, RawRwLock, RawRwLockRecursive, RawRwLockTimed, RawRwLockUpgrade, RwLock,
+    RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard,
+};
+use alloc::{rc::Rc, sync::Arc};
+use core::{ops::Deref, time::Duration};
+
+/// Implementation for `lock_api::RwLock<R, T>` with any raw lock `R`, shared by `Arc`
+/// or any other [UniPtr](crate::UniPtr), including `Arc<parking_lot::RwLock<T>>`
+/// and `Rc<parking_lot::RwLock<T>>`.
+impl<R: RawRwLock, T> UniCell for RwLock<R, T> {
+    type Value = T;
+    type Read<'a> = RwLockReadGuard<'a, R, T> where Self: 'a;
+    type Write<'a> = RwLockWriteGuard<'a, R, T> where Self: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedRwLockReadGuard<'a, R, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedRwLockWriteGuard<'a, R, U> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        RwLock::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        RwLock::read(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        RwLock::write(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        RwLock::try_read(self).ok_or(LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        RwLock::try_write(self).ok_or(LockError::WouldBlock)
+    }
+
+    fn into_inner(self) -> T {
+        RwLock::into_inner(self)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        RwLock::get_mut(self)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.data_ptr()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        RwLockReadGuard::map(RwLock::read(self), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RwLockWriteGuard::map(RwLock::write(self), f)
+    }
+}
+
+/// Implementation for `lock_api::Mutex<R, T>` with any raw mutex `R`, shared by `Arc`
+/// or any other [UniPtr](crate::UniPtr), including `Arc<parking_lot::Mutex<T>>`.
+///
+/// Like for std `Mutex`, `read()` is exclusive.
+impl<R: RawMutex, T> UniCell for Mutex<R, T> {
+    type Value = T;
+    type Read<'a> = MutexGuard<'a, R, T> where Self: 'a;
+    type Write<'a> = MutexGuard<'a, R, T> where Self: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<MutexGuard<'a, R, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedMutexGuard<'a, R, U> where Self: 'a;
+    const ALLOWS_CONCURRENT_READS: bool = false;
+
+    fn new(value: T) -> Self {
+        Mutex::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        self.lock()
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        self.lock()
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        self.try_lock().ok_or(LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        self.try_lock().ok_or(LockError::WouldBlock)
+    }
+
+    fn into_inner(self) -> T {
+        Mutex::into_inner(self)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        Mutex::get_mut(self)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.data_ptr()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.lock(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        MutexGuard::map(self.lock(), f)
+    }
+}
+
+/// The [UniRcLock] handles supporting upgradable reads, like `Arc<parking_lot::RwLock<T>>`.
+///
+/// An upgradable read guard coexists with the ordinary read guards, but not
+/// with the writers or other upgradable guards. It could be upgraded
+/// to a write guard without releasing the lock, so the value can't be changed
+/// by another thread between checking it and writing it.
+/// There is no such concept for `RefCell`, so this is a backend-specific extension.
+/// ```
+/// use std::sync::Arc;
+/// use uni_rc_lock::UpgradableRead;
+///
+/// fn insert_missing<H: UpgradableRead<Vec<i32>>>(h: &H, v: i32) {
+///     let r = h.upgradable_read();
+///     if !r.contains(&v) {
+///         H::upgrade(r).push(v);
+///     }
+/// }
+///
+/// let h = Arc::new(parking_lot::RwLock::new(vec![1]));
+/// insert_missing(&h, 1);
+/// insert_missing(&h, 2);
+/// assert_eq!(*h.read(), [1, 2]);
+/// ```
+pub trait UpgradableRead<T>: UniRcLock<T> {
+    type OutUpgradable<'a>: Deref<Target = T> where Self: 'a;
+    /// Obtain an upgradable guard for reading, blocking until the writers
+    /// and other upgradable readers release the lock
+    fn upgradable_read<'a>(&'a self) -> Self::OutUpgradable<'a>;
+    /// Try to obtain an upgradable guard for reading without blocking
+    fn try_upgradable_read<'a>(&'a self) -> Result<Self::OutUpgradable<'a>, LockError>;
+    /// Atomically upgrade the guard to a write guard,
+    /// blocking until the other readers release the lock
+    fn upgrade<'a>(guard: Self::OutUpgradable<'a>) -> Self::OutWrite<'a>;
+    /// Try to upgrade the guard without blocking, returning it back
+    /// if there are other readers
+    fn try_upgrade<'a>(
+        guard: Self::OutUpgradable<'a>,
+    ) -> Result<Self::OutWrite<'a>, Self::OutUpgradable<'a>>;
+}
+
+// The extension traits are implemented for Rc and Arc one by one, since blanket
+// impls over UniPtr would overlap with the ones for RefCell and std Mutex.
+macro_rules! impl_lock_api {
+    ($ptr:ident) => {
+        /// Recursive reads for the raw locks supporting them, like the `parking_lot` one.
+        /// A recursive read succeeds even if a writer is waiting, but still deadlocks
+        /// if the same thread holds a write guard.
+        impl<R: RawRwLockRecursive, T> RecursiveRead<T> for $ptr<RwLock<R, T>> {
+            fn read_recursive<'a>(&'a self) -> Self::OutRead<'a> {
+                $ptr::deref(self).read_recursive()
+            }
+        }
+
+        /// Timed locking for the raw locks supporting it, like the `parking_lot` one.
+        impl<R, T> TimedLock<T> for $ptr<RwLock<R, T>>
+        where
+            R: RawRwLockTimed<Duration = Duration>,
+        {
+            fn read_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutRead<'a>> {
+                $ptr::deref(self).try_read_for(timeout)
+            }
+
+            fn write_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutWrite<'a>> {
+                $ptr::deref(self).try_write_for(timeout)
+            }
+        }
+
+        /// Upgradable reads for the raw locks supporting them, like the `parking_lot` one.
+        impl<R: RawRwLockUpgrade, T> UpgradableRead<T> for $ptr<RwLock<R, T>> {
+            type OutUpgradable<'a> = RwLockUpgradableReadGuard<'a, R, T> where Self: 'a;
+
+            fn upgradable_read<'a>(&'a self) -> Self::OutUpgradable<'a> {
+                $ptr::deref(self).upgradable_read()
+            }
+
+            fn try_upgradable_read<'a>(&'a self) -> Result<Self::OutUpgradable<'a>, LockError> {
+                $ptr::deref(self)
+                    .try_upgradable_read()
+                    .ok_or(LockError::WouldBlock)
+            }
+
+            fn upgrade<'a>(guard: Self::OutUpgradable<'a>) -> Self::OutWrite<'a> {
+                RwLockUpgradableReadGuard::upgrade(guard)
+            }
+
+            fn try_upgrade<'a>(
+                guard: Self::OutUpgradable<'a>,
+            ) -> Result<Self::OutWrite<'a>, Self::OutUpgradable<'a>> {
+                RwLockUpgradableReadGuard::try_upgrade(guard)
+            }
+        }
+
+        impl<R: RawMutex, T> MutexBackend<T> for $ptr<Mutex<R, T>> {}
+
+        /// Timed locking for the raw mutexes supporting it, like the `parking_lot` one.
+        /// Both methods lock the mutex exclusively.
+        impl<R, T> TimedLock<T> for $ptr<Mutex<R, T>>
+        where
+            R: RawMutexTimed<Duration = Duration>,
+        {
+            fn read_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutRead<'a>> {
+                $ptr::deref(self).try_lock_for(timeout)
+            }
+
+            fn write_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutWrite<'a>> {
+                $ptr::deref(self).try_lock_for(timeout)
+            }
+        }
+    };
+}
+
+impl_lock_api!(Rc);
+impl_lock_api!(Arc);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use ::lock_api::{GuardSend, Mutex, RawMutex, RawRwLock, RwLock};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::{LockError, UniNew, UniRcLock};
+
+    // A minimal custom raw lock, which doesn't come from any lock crate
+    struct RawSpinRwLock(AtomicUsize);
+
+    const WRITER: usize = usize::MAX;
+
+    unsafe impl RawRwLock for RawSpinRwLock {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const INIT: Self = RawSpinRwLock(AtomicUsize::new(0));
+        type GuardMarker = GuardSend;
+
+        fn lock_shared(&self) {
+            while !self.try_lock_shared() {
+                std::hint::spin_loop();
+            }
+        }
+
+        fn try_lock_shared(&self) -> bool {
+            let readers = self.0.load(Ordering::Relaxed);
+            readers != WRITER
+                && self
+                    .0
+                    .compare_exchange(readers, readers + 1, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+        }
+
+        unsafe fn unlock_shared(&self) {
+            self.0.fetch_sub(1, Ordering::Release);
+        }
+
+        fn lock_exclusive(&self) {
+            while !self.try_lock_exclusive() {
+                std::hint::spin_loop();
+            }
+        }
+
+        fn try_lock_exclusive(&self) -> bool {
+            self.0
+                .compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }
+
+        unsafe fn unlock_exclusive(&self) {
+            self.0.store(0, Ordering::Release);
+        }
+    }
+
+    struct RawSpinMutex(AtomicBool);
+
+    unsafe impl RawMutex for RawSpinMutex {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const INIT: Self = RawSpinMutex(AtomicBool::new(false));
+        type GuardMarker = GuardSend;
+
+        fn lock(&self) {
+            while !self.try_lock() {
+                std::hint::spin_loop();
+            }
+        }
+
+        fn try_lock(&self) -> bool {
+            self.0
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }
+
+        unsafe fn unlock(&self) {
+            self.0.store(false, Ordering::Release);
+        }
+    }
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr_in_threads<H: UniRcLock<State> + Send + 'static>(st: &H) {
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let h = st.clone();
+                thread::spawn(move || h.write().val += 1)
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn lock_api_rwlock() {
+        let st: Arc<RwLock<RawSpinRwLock, State>> = UniNew::new(State { val: 0 });
+        incr_in_threads(&st);
+        {
+            let _r = st.read();
+            assert!(st.try_read().is_ok());
+            assert_eq!(st.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(*st.map_read(|s| &s.val), 10);
+
+        // The same generic code with the parking_lot raw lock
+        let st: Arc<RwLock<::parking_lot::RawRwLock, State>> = UniNew::new(State { val: 0 });
+        incr_in_threads(&st);
+        assert_eq!(st.try_unwrap().ok().unwrap().val, 10);
+    }
+
+    #[test]
+    fn lock_api_read_recursive() {
+        use crate::RecursiveRead;
+        use std::time::Duration;
+
+        let st: Arc<RwLock<::parking_lot::RawRwLock, State>> = UniNew::new(State { val: 1 });
+        let outer = st.read();
+        thread::scope(|s| {
+            let writer = s.spawn(|| st.write().val += 1);
+            // Wait until the writer is queued
+            thread::sleep(Duration::from_millis(50));
+            // A plain read() would deadlock here behind the waiting writer
+            assert_eq!(st.read_recursive().val, 1);
+            drop(outer);
+            writer.join().unwrap();
+        });
+        assert_eq!(st.read().val, 2);
+    }
+
+    #[test]
+    fn lock_api_mutex() {
+        let st: Arc<Mutex<RawSpinMutex, State>> = UniNew::new(State { val: 0 });
+        incr_in_threads(&st);
+        {
+            let _r = st.read();
+            assert_eq!(st.try_read().err(), Some(LockError::WouldBlock));
+        }
+        *st.map_write(|s| &mut s.val) += 1;
+        assert_eq!(st.read().val, 11);
+
+        let st: Arc<Mutex<::parking_lot::RawMutex, State>> = UniNew::new(State { val: 0 });
+        incr_in_threads(&st);
+        assert_eq!(st.read().val, 10);
+        type SpinMutex = Arc<Mutex<RawSpinMutex, State>>;
+        type SpinRwLock = Arc<RwLock<RawSpinRwLock, State>>;
+        const { assert!(!<SpinMutex as UniRcLock<State>>::ALLOWS_CONCURRENT_READS) };
+        const { assert!(<SpinRwLock as UniRcLock<State>>::ALLOWS_CONCURRENT_READS) };
+    }
+
+    #[test]
+    fn lock_api_any_pointer() {
+        use crate::{OwnedLock, RecursiveRead, UpgradableRead};
+        use std::rc::Rc;
+
+        let st: Rc<RwLock<RawSpinRwLock, State>> = UniNew::new(State { val: 1 });
+        st.write().val += 1;
+        assert_eq!(st.as_ptr(), &*st.read() as *const State);
+
+        let st: Rc<::parking_lot::RwLock<State>> = UniNew::new(State { val: 1 });
+        {
+            let _r = st.read();
+            assert_eq!(st.read_recursive().val, 1);
+        }
+        Rc::upgrade(st.upgradable_read()).val += 1;
+        assert_eq!(st.try_unwrap().ok().unwrap().val, 2);
+
+        let st: Arc<::parking_lot::Mutex<State>> = UniNew::new(State { val: 1 });
+        let w = st.clone().write_owned();
+        assert_eq!(st.try_read().err(), Some(LockError::WouldBlock));
+        drop(w);
+        assert_eq!(st.read().val, 1);
+    }
+}
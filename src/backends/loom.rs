@@ -0,0 +1,130 @@
+// Implementation for the loom mock Arc and RwLock. Available with the `loom` feature.
+// loom is a model checker: the handles only work inside loom::model(),
+// which runs the closure under every possible interleaving of the threads.
+// It is intended for testing only and can't be used for real concurrency.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, NoWeak, UniNew, UniRcLock};
+use loom::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{ops::Deref, sync::PoisonError};
+
+/// Implementation for `loom::sync::Arc<loom::sync::RwLock<T>>`,
+/// which is [MultiThreaded](crate::MultiThreaded) with the `loom` feature.
+///
+/// loom's `Arc` has no weak references, so `downgrade()` returns a [NoWeak].
+/// Poisoning is handled like for std `Arc<RwLock<T>>`.
+impl<T> UniRcLock<T> for Arc<RwLock<T>> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).read().expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).write().expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(Arc::deref(self).try_read()?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(Arc::deref(self).try_write()?)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self).map(|lock| lock.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T> UniNew<T> for Arc<RwLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(RwLock::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MultiThreaded, UniNew, UniRcLock};
+    use loom::thread;
+
+    // Generic code under test, unaware of loom
+    fn incr<H: UniRcLock<i32>>(h: &H) {
+        *h.write() += 1;
+    }
+
+    #[test]
+    fn loom_increment() {
+        loom::model(|| {
+            let ptr: MultiThreaded<i32> = UniNew::new(0);
+            let threads: Vec<_> = (0..2)
+                .map(|_| {
+                    let h = ptr.clone();
+                    thread::spawn(move || incr(&h))
+                })
+                .collect();
+            for t in threads {
+                t.join().unwrap();
+            }
+            assert_eq!(*ptr.read(), 2);
+        });
+    }
+
+    #[test]
+    fn loom_reader_writer() {
+        loom::model(|| {
+            let ptr: MultiThreaded<(i32, i32)> = UniNew::new((0, 0));
+            let h = ptr.clone();
+            let writer = thread::spawn(move || {
+                let mut w = h.write();
+                w.0 += 1;
+                w.1 += 1;
+            });
+            // The reader sees either the old or the new pair, never a half-written one
+            let (a, b) = *ptr.read();
+            assert_eq!(a, b);
+            writer.join().unwrap();
+            assert_eq!(ptr.try_unwrap().ok().unwrap(), (1, 1));
+        });
+    }
+}
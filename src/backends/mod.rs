@@ -0,0 +1,92 @@
+// Backends besides Rc<RefCell<T>>, which lives in the crate root.
+// Each of them is enabled by a cargo feature.
+
+#[cfg(feature = "std")]
+pub(crate) mod std_sync;
+
+#[cfg(feature = "std")]
+pub(crate) mod lazy;
+
+#[cfg(feature = "std")]
+pub(crate) mod thread_local;
+
+#[cfg(feature = "parking_lot")]
+pub(crate) mod parking_lot;
+
+#[cfg(feature = "lock_api")]
+pub(crate) mod lock_api;
+
+#[cfg(feature = "spin")]
+pub(crate) mod spin;
+
+#[cfg(feature = "crossbeam")]
+pub(crate) mod crossbeam;
+
+#[cfg(feature = "crossbeam-epoch")]
+pub(crate) mod crossbeam_epoch;
+
+#[cfg(feature = "atomic_refcell")]
+mod atomic_refcell;
+
+#[cfg(feature = "qcell")]
+pub(crate) mod qcell;
+
+#[cfg(feature = "ghost-cell")]
+pub(crate) mod ghost_cell;
+
+#[cfg(feature = "triomphe")]
+mod triomphe;
+
+#[cfg(feature = "hybrid-rc")]
+pub(crate) mod hybrid_rc;
+
+#[cfg(feature = "arc-swap")]
+pub(crate) mod arc_swap;
+
+#[cfg(feature = "dashmap")]
+pub(crate) mod dashmap;
+
+#[cfg(feature = "left-right")]
+pub(crate) mod left_right;
+
+#[cfg(feature = "seqlock")]
+pub(crate) mod seqlock;
+
+#[cfg(feature = "slotmap")]
+pub(crate) mod slotmap;
+
+#[cfg(feature = "send_wrapper")]
+pub(crate) mod send_wrapper;
+
+#[cfg(feature = "shm")]
+pub(crate) mod shm;
+
+#[cfg(feature = "persistent")]
+pub(crate) mod persistent;
+
+#[cfg(feature = "either")]
+mod either;
+
+#[cfg(feature = "critical-section")]
+pub(crate) mod critical_section;
+
+#[cfg(feature = "embassy-sync")]
+mod embassy_sync;
+
+#[cfg(feature = "tokio")]
+pub(crate) mod tokio;
+
+#[cfg(feature = "tokio-watch")]
+pub(crate) mod tokio_watch;
+
+#[cfg(feature = "async-lock")]
+mod async_lock;
+
+#[cfg(feature = "futures-locks")]
+mod futures_locks;
+
+#[cfg(any(loom, feature = "loom"))]
+mod loom;
+
+#[cfg(any(shuttle, feature = "shuttle"))]
+mod shuttle;
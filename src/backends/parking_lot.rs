@@ -0,0 +1,391 @@
+// Implementations for parking_lot locks.
+// parking_lot locks are never poisoned, so read() and write() never panic
+// on poisoning and try_read()/try_write() never return LockError::Poisoned.
+// parking_lot::RwLock and parking_lot::Mutex are lock_api locks, which are
+// implemented generically in the lock_api module.
+
+use crate::{
+    LockError, MappedReadGuard, MappedWriteGuard, RecursiveRead, UniNew, UniRcLock, UniWeak,
+};
+use ::parking_lot::{ReentrantMutex, ReentrantMutexGuard};
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Weak},
+};
+
+/// Read guard of `Arc<parking_lot::ReentrantMutex<RefCell<T>>>`.
+///
+/// Holds both the re-entrant lock and the shared borrow of the inner `RefCell`.
+pub struct ReentrantReadGuard<'a, T> {
+    // Declared first to be dropped before the lock is released
+    borrow: Ref<'a, T>,
+    _lock: ReentrantMutexGuard<'a, RefCell<T>>,
+}
+
+impl<T> Deref for ReentrantReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+/// Write guard of `Arc<parking_lot::ReentrantMutex<RefCell<T>>>`.
+///
+/// Holds both the re-entrant lock and the exclusive borrow of the inner `RefCell`.
+pub struct ReentrantWriteGuard<'a, T> {
+    // Declared first to be dropped before the lock is released
+    borrow: RefMut<'a, T>,
+    _lock: ReentrantMutexGuard<'a, RefCell<T>>,
+}
+
+impl<T> Deref for ReentrantWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+impl<T> DerefMut for ReentrantWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.borrow
+    }
+}
+
+// Obtains the inner RefCell for the lifetime of the mutex itself.
+// Must only be called while the calling thread holds the lock.
+fn locked_cell<'a, T>(
+    mutex: &'a ReentrantMutex<RefCell<T>>,
+    _lock: &ReentrantMutexGuard<'a, RefCell<T>>,
+) -> &'a RefCell<T> {
+    // SAFETY: the RefCell lives as long as the mutex and the caller holds
+    // the lock, so no other thread can access it concurrently.
+    // The guard returned alongside keeps the lock held while the cell is used.
+    unsafe { &*mutex.data_ptr() }
+}
+
+/// Implementation for `Arc<parking_lot::ReentrantMutex<RefCell<T>>>`.
+///
+/// The lock may be acquired recursively by the same thread, so nested
+/// `read()` calls on the same handle succeed instead of deadlocking.
+/// Nested `write()` while any other guard of this thread is alive, or
+/// `read()` while a write guard of this thread is alive, panics
+/// instead of deadlocking.
+impl<T> UniRcLock<T> for Arc<ReentrantMutex<RefCell<T>>> {
+    type OutRead<'a> = ReentrantReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = ReentrantWriteGuard<'a, T> where T: 'a;
+    type Weak = Weak<ReentrantMutex<RefCell<T>>>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<ReentrantReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<ReentrantWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let lock = Arc::deref(self).lock();
+        let borrow = locked_cell(self, &lock)
+            .try_borrow()
+            .expect("Re-entrant read while writing the same ReentrantMutex");
+        ReentrantReadGuard { borrow, _lock: lock }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let lock = Arc::deref(self).lock();
+        let borrow = locked_cell(self, &lock)
+            .try_borrow_mut()
+            .expect("Re-entrant write to the same ReentrantMutex");
+        ReentrantWriteGuard { borrow, _lock: lock }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let lock = Arc::deref(self).try_lock().ok_or(LockError::WouldBlock)?;
+        let borrow = locked_cell(self, &lock)
+            .try_borrow()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(ReentrantReadGuard { borrow, _lock: lock })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let lock = Arc::deref(self).try_lock().ok_or(LockError::WouldBlock)?;
+        let borrow = locked_cell(self, &lock)
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(ReentrantWriteGuard { borrow, _lock: lock })
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(|lock| lock.into_inner().into_inner())
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self).map(|lock| lock.get_mut().get_mut())
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T> UniNew<T> for Arc<ReentrantMutex<RefCell<T>>> {
+    fn new(value: T) -> Self {
+        Arc::new(ReentrantMutex::new(RefCell::new(value)))
+    }
+}
+
+impl<T> UniWeak<T> for Weak<ReentrantMutex<RefCell<T>>> {
+    type Strong = Arc<ReentrantMutex<RefCell<T>>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self)
+    }
+}
+
+// The re-entrant lock already allows nested reads
+impl<T> RecursiveRead<T> for Arc<ReentrantMutex<RefCell<T>>> {
+    fn read_recursive<'a>(&'a self) -> Self::OutRead<'a> {
+        self.read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::parking_lot::{Mutex, ReentrantMutex, RwLock};
+    use std::{cell::RefCell, sync::Arc};
+
+    use crate::{LockError, UniRcLock};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    #[derive(Debug, Clone)]
+    struct StateHandler<T: UniRcLock<State>> {
+        state: T,
+    }
+
+    impl<T: UniRcLock<State>> StateHandler<T> {
+        fn new(val: T) -> Self {
+            Self { state: val }
+        }
+    }
+
+    #[test]
+    fn parking_lot_rwlock() {
+        let st = Arc::new(RwLock::new(State { val: 42 }));
+        st.write().val += 1;
+        assert_eq!(st.read().val, 43);
+        {
+            let _w = st.write();
+            assert_eq!(st.try_read().err(), Some(LockError::WouldBlock));
+        }
+        assert!(st.try_write().is_ok());
+    }
+
+    #[test]
+    fn threads_test_parking_lot_rwlock() {
+        use std::thread;
+        let st = Arc::new(RwLock::new(State { val: 42 }));
+        let sth = StateHandler::new(st);
+
+        let threads: Vec<_> = (0..10)
+            .map(|i| {
+                let h = sth.clone();
+                thread::spawn(move || {
+                    h.state.write().val += 1;
+                    println!("Thread #{i} incremented");
+                })
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(sth.state.read().val, 52);
+    }
+
+    #[test]
+    fn threads_test_parking_lot_mutex() {
+        use std::thread;
+
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let st = Arc::new(Mutex::new(State { val: 0 }));
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let h = st.clone();
+                thread::spawn(move || incr(h))
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(st.read().val, 10);
+        let _r = st.read();
+        assert_eq!(st.try_read().err(), Some(LockError::WouldBlock));
+    }
+
+    #[test]
+    fn reentrant_read() {
+        fn callback(p: &impl UniRcLock<State>) -> i32 {
+            p.read().val
+        }
+
+        let st = Arc::new(ReentrantMutex::new(RefCell::new(State { val: 42 })));
+        st.write().val += 1;
+        let r = st.read();
+        // Nested read from the same thread doesn't deadlock
+        assert_eq!(callback(&st), 43);
+        assert_eq!(r.val, 43);
+        assert_eq!(st.try_write().err(), Some(LockError::WouldBlock));
+    }
+
+    #[test]
+    #[should_panic(expected = "Re-entrant write")]
+    fn reentrant_write_panics() {
+        let st = Arc::new(ReentrantMutex::new(RefCell::new(State { val: 42 })));
+        let _r = st.read();
+        st.write().val += 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "Re-entrant read")]
+    fn reentrant_read_while_writing_panics() {
+        fn callback(p: &impl UniRcLock<State>) -> i32 {
+            p.read().val
+        }
+
+        let st = Arc::new(ReentrantMutex::new(RefCell::new(State { val: 42 })));
+        let mut w = st.write();
+        w.val += callback(&st);
+    }
+
+    #[test]
+    fn threads_test_reentrant() {
+        use std::thread;
+        let st = Arc::new(ReentrantMutex::new(RefCell::new(State { val: 0 })));
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let h = st.clone();
+                thread::spawn(move || {
+                    let r = h.read();
+                    let v = h.read().val;
+                    drop(r);
+                    h.write().val += 1;
+                    v
+                })
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(st.read().val, 10);
+    }
+
+    #[test]
+    fn parking_lot_upgradable() {
+        use crate::UpgradableRead;
+        use std::thread;
+
+        fn insert_missing<H: UpgradableRead<Vec<i32>>>(h: &H, v: i32) {
+            let r = h.upgradable_read();
+            if !r.contains(&v) {
+                H::upgrade(r).push(v);
+            }
+        }
+
+        let st = Arc::new(RwLock::new(Vec::new()));
+        let threads: Vec<_> = (0..10)
+            .map(|i| {
+                let h = st.clone();
+                thread::spawn(move || insert_missing(&h, i % 2))
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let mut v = st.read().clone();
+        v.sort();
+        assert_eq!(v, [0, 1]);
+
+        // Coexists with readers, but not with other upgradable readers
+        let r = st.read();
+        let u = st.upgradable_read();
+        assert_eq!(st.try_upgradable_read().err(), Some(LockError::WouldBlock));
+        let u = <Arc<RwLock<Vec<i32>>> as UpgradableRead<_>>::try_upgrade(u).unwrap_err();
+        drop(r);
+        let mut w = <Arc<RwLock<Vec<i32>>> as UpgradableRead<_>>::try_upgrade(u).unwrap();
+        w.push(2);
+        drop(w);
+        assert_eq!(st.read().len(), 3);
+    }
+
+    #[test]
+    fn parking_lot_timeout() {
+        use crate::TimedLock;
+        use std::{thread, time::Duration};
+
+        let st = Arc::new(RwLock::new(State { val: 0 }));
+        let w = st.write();
+        let h = st.clone();
+        let timed_out = thread::spawn(move || {
+            h.read_timeout(Duration::from_millis(10)).is_none()
+                && h.write_timeout(Duration::from_millis(10)).is_none()
+        });
+        assert!(timed_out.join().unwrap());
+        drop(w);
+        st.write_timeout(Duration::from_millis(10)).unwrap().val += 1;
+        let _r = st.read_timeout(Duration::ZERO).unwrap();
+        assert_eq!(st.read_timeout(Duration::ZERO).unwrap().val, 1);
+
+        let m = Arc::new(Mutex::new(State { val: 0 }));
+        let _w = m.write();
+        assert!(m.read_timeout(Duration::from_millis(10)).is_none());
+    }
+
+    #[test]
+    fn parking_lot_map() {
+        fn project(p: impl UniRcLock<State>) {
+            *p.map_write(|st| &mut st.val) += 1;
+            assert_eq!(*p.map_read(|st| &st.val), 43);
+        }
+
+        project(Arc::new(RwLock::new(State { val: 42 })));
+        project(Arc::new(Mutex::new(State { val: 42 })));
+        project(Arc::new(ReentrantMutex::new(RefCell::new(State { val: 42 }))));
+    }
+}
@@ -0,0 +1,484 @@
+// Implementation of the write-through persistent handle.
+// The value lives in Arc<RwLock<T>>, and the write guard saves it to the file
+// when dropped, while still holding the lock, so the saves are serialized
+// and each of them writes a consistent snapshot of the value.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniCell, UniNew, UniRcLock, UniWeak};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    sync::{self, Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Format of the file of [PersistentHandle]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Pretty-printed JSON
+    Json,
+    /// Pretty-printed TOML. The value must serialize into a table,
+    /// like a struct or a map.
+    Toml,
+}
+
+impl Format {
+    /// The format guessed from the extension of the path: `Toml` for `.toml`,
+    /// `Json` otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> io::Result<Vec<u8>> {
+        match self {
+            Format::Json => serde_json::to_vec_pretty(value).map_err(invalid),
+            Format::Toml => toml::to_string_pretty(value).map(String::into_bytes).map_err(invalid),
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> io::Result<T> {
+        match self {
+            Format::Json => serde_json::from_slice(bytes).map_err(invalid),
+            Format::Toml => {
+                let text = std::str::from_utf8(bytes).map_err(invalid)?;
+                toml::from_str(text).map_err(invalid)
+            }
+        }
+    }
+}
+
+fn invalid(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+type ErrorCallback = Box<dyn Fn(&Path, io::Error) + Send + Sync>;
+
+// Where and how the value is saved, shared by all the clones of the handle
+struct Storage {
+    path: Option<PathBuf>,
+    format: Format,
+    on_error: Mutex<ErrorCallback>,
+}
+
+impl Storage {
+    // Write the value to a temporary file next to the target and rename it over
+    // the target, so the file is never left partially written. The temporary file
+    // is removed if anything fails before the rename.
+    fn save<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let bytes = self.format.serialize(value)?;
+        let tmp = temp_path(path);
+        let result = File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(&bytes)?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&tmp, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+
+    fn report(&self, err: io::Error) {
+        let path = self.path.as_deref().unwrap_or(Path::new(""));
+        let on_error = self.on_error.lock().unwrap_or_else(sync::PoisonError::into_inner);
+        on_error(path, err);
+    }
+}
+
+// The temporary file used by the saves, `<path>.tmp`
+fn temp_path(path: &Path) -> PathBuf {
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    tmp.into()
+}
+
+/// Handle to a value, which is saved to a file each time it is written.
+///
+/// Intended for the small state of tools, like the recent files or the geometry
+/// of the windows, which should survive restarts. The value is held in
+/// `Arc<RwLock<T>>` and read from memory, while the write guard serializes it
+/// into the file when dropped, in JSON or TOML. The file is written atomically:
+/// the value is written to `<path>.tmp` first, which is renamed over the file,
+/// so a crash in the middle of saving leaves the previous contents intact.
+///
+/// [load_or](Self::load_or) restores the value saved by the previous run.
+/// The errors of saving can't be returned from `Drop`, so they are passed
+/// to the callback set by [on_error](Self::on_error), which prints them
+/// to stderr by default. [save](Self::save) saves the value explicitly
+/// and returns the error. The value is not saved if the code holding
+/// the write guard panics, so the file keeps the previous value.
+///
+/// The value is serialized while the write lock is still held, so the readers
+/// wait for the file to be written: the handle is not intended for the values
+/// which are written often. `new()` creates a handle which is kept in memory
+/// only, and `get_mut()` gives access to the value without saving it.
+/// Like `Arc<RwLock<T>>`, the guards panic if the lock is poisoned.
+pub struct PersistentHandle<T> {
+    lock: Arc<RwLock<T>>,
+    storage: Arc<Storage>,
+}
+
+impl<T: Serialize + DeserializeOwned> PersistentHandle<T> {
+    /// Load the value from the file, or use the default if the file doesn't exist.
+    ///
+    /// The format is guessed by [Format::from_path]. The default is not saved
+    /// until the value is written. Fails if the file can't be read or parsed,
+    /// rather than overwriting it with the default on the next write.
+    pub fn load_or(path: impl AsRef<Path>, default: T) -> io::Result<Self> {
+        let path = path.as_ref();
+        Self::load_or_with_format(path, Format::from_path(path), default)
+    }
+
+    /// Same as [load_or](Self::load_or), with the explicit format of the file
+    pub fn load_or_with_format(
+        path: impl AsRef<Path>,
+        format: Format,
+        default: T,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let value = match fs::read(path) {
+            Ok(bytes) => format.deserialize(&bytes)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => default,
+            Err(err) => return Err(err),
+        };
+        Ok(Self::from_parts(value, Some(path.to_owned()), format))
+    }
+
+    /// Save the current value to the file
+    pub fn save(&self) -> io::Result<()> {
+        self.storage.save(&*self.read())
+    }
+}
+
+impl<T> PersistentHandle<T> {
+    fn from_parts(value: T, path: Option<PathBuf>, format: Format) -> Self {
+        let on_error: ErrorCallback = Box::new(|path: &Path, err: io::Error| {
+            eprintln!("Failed to save {}: {err}", path.display())
+        });
+        Self {
+            lock: Arc::new(RwLock::new(value)),
+            storage: Arc::new(Storage {
+                path,
+                format,
+                on_error: Mutex::new(on_error),
+            }),
+        }
+    }
+
+    /// Set the callback receiving the errors of saving the value when
+    /// the write guards are dropped. Shared by all the clones of the handle.
+    ///
+    /// The default callback prints the path and the error to stderr.
+    pub fn on_error(&self, f: impl Fn(&Path, io::Error) + Send + Sync + 'static) {
+        *self.storage.on_error.lock().unwrap_or_else(sync::PoisonError::into_inner) = Box::new(f);
+    }
+
+    /// The path of the file, or `None` for the handles created by `new()`
+    pub fn path(&self) -> Option<&Path> {
+        self.storage.path.as_deref()
+    }
+
+    /// The format of the file
+    pub fn format(&self) -> Format {
+        self.storage.format
+    }
+}
+
+impl<T> Clone for PersistentHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lock: Arc::clone(&self.lock),
+            storage: Arc::clone(&self.storage),
+        }
+    }
+}
+
+/// Weak counterpart of [PersistentHandle]
+pub struct PersistentWeak<T> {
+    lock: sync::Weak<RwLock<T>>,
+    storage: Arc<Storage>,
+}
+
+impl<T> Clone for PersistentWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lock: sync::Weak::clone(&self.lock),
+            storage: Arc::clone(&self.storage),
+        }
+    }
+}
+
+/// Write guard of [PersistentHandle], which saves the value when dropped
+pub struct PersistentWriteGuard<'a, T: Serialize> {
+    guard: RwLockWriteGuard<'a, T>,
+    storage: &'a Storage,
+}
+
+impl<T: Serialize> Deref for PersistentWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: Serialize> DerefMut for PersistentWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T: Serialize> Drop for PersistentWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // The value may be half-modified if the write panicked, so the file
+        // keeps the last complete value
+        if std::thread::panicking() {
+            return;
+        }
+        if let Err(err) = self.storage.save(&*self.guard) {
+            self.storage.report(err);
+        }
+    }
+}
+
+// Implementation for PersistentHandle<T>
+impl<T: Serialize + DeserializeOwned> UniRcLock<T> for PersistentHandle<T> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = PersistentWriteGuard<'a, T> where T: 'a;
+    type Weak = PersistentWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<PersistentWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RwLock::read(&self.lock).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        PersistentWriteGuard {
+            guard: RwLock::write(&self.lock).expect("Write lock should not be poisoned"),
+            storage: &self.storage,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(RwLock::try_read(&self.lock)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(PersistentWriteGuard {
+            guard: RwLock::try_write(&self.lock)?,
+            storage: &self.storage,
+        })
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.lock)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.lock)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.lock, &other.lock)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        PersistentWeak {
+            lock: Arc::downgrade(&self.lock),
+            storage: Arc::clone(&self.storage),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        match Arc::try_unwrap(self.lock) {
+            Ok(lock) => Ok(UniCell::into_inner(lock)),
+            Err(lock) => Err(Self {
+                lock,
+                storage: self.storage,
+            }),
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(&mut self.lock).map(UniCell::get_mut)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.lock.is_poisoned()
+    }
+
+    fn clear_poison(&self) {
+        self.lock.clear_poison()
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> UniNew<T> for PersistentHandle<T> {
+    fn new(value: T) -> Self {
+        Self::from_parts(value, None, Format::Json)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> UniWeak<T> for PersistentWeak<T> {
+    type Strong = PersistentHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.lock.upgrade().map(|lock| PersistentHandle {
+            lock,
+            storage: Arc::clone(&self.storage),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{temp_path, Format, PersistentHandle};
+    use crate::{UniNew, UniRcLock, UniWeak};
+    use serde::{Deserialize, Serialize};
+    use std::{
+        collections::BTreeMap,
+        env, fs,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    struct State {
+        recent: Vec<String>,
+        width: u32,
+    }
+
+    // A fresh directory for the files of the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("uni_rc_lock-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn persistent() {
+        let dir = temp_dir("persistent");
+        for name in ["state.json", "state.toml"] {
+            let path = dir.join(name);
+            let h = PersistentHandle::load_or(&path, State::default()).unwrap();
+            // The default is not saved until written
+            assert!(!path.exists());
+            h.write().recent.push("a.txt".into());
+            h.map_write(|s| &mut s.width).clone_from(&800);
+            assert!(path.exists());
+            assert!(!temp_path(&path).exists());
+
+            let h = PersistentHandle::load_or(&path, State::default()).unwrap();
+            assert_eq!(
+                *h.read(),
+                State {
+                    recent: vec!["a.txt".into()],
+                    width: 800
+                }
+            );
+        }
+        let text = fs::read_to_string(dir.join("state.toml")).unwrap();
+        assert!(text.contains("width = 800"));
+        assert_eq!(Format::from_path(&dir.join("state.json")), Format::Json);
+
+        // Unparsable files are not replaced by the default
+        fs::write(dir.join("bad.json"), "{").unwrap();
+        let err = PersistentHandle::load_or(dir.join("bad.json"), State::default()).err();
+        assert_eq!(err.unwrap().kind(), std::io::ErrorKind::InvalidData);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn persistent_errors() {
+        let dir = temp_dir("errors");
+        let path = dir.join("state.json");
+        let h = PersistentHandle::load_or(&path, BTreeMap::from([(1, 2)])).unwrap();
+        let errors = Arc::new(Mutex::new(0));
+        let errors2 = Arc::clone(&errors);
+        h.on_error(move |_, _| *errors2.lock().unwrap() += 1);
+        h.write().insert(3, 4);
+        let saved = fs::read(&path).unwrap();
+
+        // The file is never touched if writing the temporary file fails
+        fs::create_dir(temp_path(&path)).unwrap();
+        h.write().insert(5, 6);
+        assert_eq!(*errors.lock().unwrap(), 1);
+        assert_eq!(fs::read(&path).unwrap(), saved);
+        assert!(h.save().is_err());
+        fs::remove_dir(temp_path(&path)).unwrap();
+
+        // A stale temporary file of a crashed save is replaced
+        fs::write(temp_path(&path), "{").unwrap();
+        h.save().unwrap();
+        assert!(!temp_path(&path).exists());
+        let h = PersistentHandle::load_or(&path, BTreeMap::<i32, i32>::new()).unwrap();
+        assert_eq!(h.read().len(), 3);
+
+        // TOML can't hold a bare number, which is reported instead of a panic
+        let h = PersistentHandle::load_or(dir.join("n.toml"), 1).unwrap();
+        let errors2 = Arc::clone(&errors);
+        h.on_error(move |_, _| *errors2.lock().unwrap() += 1);
+        *h.write() = 2;
+        assert_eq!(*errors.lock().unwrap(), 2);
+        assert!(!dir.join("n.toml").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn persistent_panic() {
+        let dir = temp_dir("panic");
+        let path = dir.join("state.json");
+        let h = PersistentHandle::load_or(&path, State::default()).unwrap();
+        h.write().width = 800;
+        let saved = fs::read(&path).unwrap();
+
+        let h2 = h.clone();
+        let res = std::panic::catch_unwind(move || {
+            let mut w = h2.write();
+            w.width = 0;
+            panic!("interrupted write");
+        });
+        assert!(res.is_err());
+        // The half-modified value is not saved
+        assert_eq!(fs::read(&path).unwrap(), saved);
+        assert!(h.is_poisoned());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn persistent_memory() {
+        let h = PersistentHandle::new(1);
+        assert!(h.path().is_none());
+        *h.write() = 2;
+        let weak = h.downgrade();
+        assert_eq!(*weak.upgrade().unwrap().read(), 2);
+        assert_eq!(h.try_unwrap().ok().unwrap(), 2);
+    }
+}
@@ -0,0 +1,265 @@
+// Implementation for qcell::QCell.
+// Each QCell is accessed through the QCellOwner it was created with.
+// The owner is shared by all handles in Rc<RefCell<QCellOwner>>,
+// so a single runtime check of the owner borrow covers the whole guard
+// instead of tracking the borrow state of each cell separately.
+// The owner ID check on each access is a single pointer comparison.
+// Only core and alloc are used here, so this backend is usable without std.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use ::qcell::{QCell, QCellOwner};
+use alloc::rc::{self, Rc};
+use core::{
+    cell::{Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+};
+
+/// Handle to a value stored in `Rc<QCell<T>>` together with
+/// the shared `QCellOwner` used to access it.
+///
+/// Any number of handles may share the same owner. Reading through any of
+/// them borrows the owner immutably, while writing borrows it mutably,
+/// so a write guard of one handle can't coexist with any other guard
+/// of the handles sharing this owner. Conflicting `read()` and `write()`
+/// panic like they do for `Rc<RefCell<T>>`.
+pub struct QHandle<T> {
+    cell: Rc<QCell<T>>,
+    owner: Rc<RefCell<QCellOwner>>,
+}
+
+impl<T> QHandle<T> {
+    /// Create a handle accessed through an existing owner
+    pub fn with_owner(owner: &Rc<RefCell<QCellOwner>>, value: T) -> Self {
+        let cell = owner.borrow().cell(value);
+        Self {
+            cell: Rc::new(cell),
+            owner: Rc::clone(owner),
+        }
+    }
+
+    /// The owner used to access this handle
+    pub fn owner(&self) -> &Rc<RefCell<QCellOwner>> {
+        &self.owner
+    }
+}
+
+impl<T> Clone for QHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: Rc::clone(&self.cell),
+            owner: Rc::clone(&self.owner),
+        }
+    }
+}
+
+/// Weak counterpart of [QHandle]
+pub struct QWeak<T> {
+    cell: rc::Weak<QCell<T>>,
+    owner: Rc<RefCell<QCellOwner>>,
+}
+
+impl<T> Clone for QWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: rc::Weak::clone(&self.cell),
+            owner: Rc::clone(&self.owner),
+        }
+    }
+}
+
+/// Read guard of [QHandle]. Holds the shared borrow of the owner.
+pub struct QReadGuard<'a, T> {
+    owner: Ref<'a, QCellOwner>,
+    cell: &'a QCell<T>,
+}
+
+impl<T> Deref for QReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.owner.ro(self.cell)
+    }
+}
+
+/// Write guard of [QHandle]. Holds the exclusive borrow of the owner.
+pub struct QWriteGuard<'a, T> {
+    owner: RefMut<'a, QCellOwner>,
+    cell: &'a QCell<T>,
+}
+
+impl<T> Deref for QWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.owner.ro(self.cell)
+    }
+}
+
+impl<T> DerefMut for QWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.owner.rw(self.cell)
+    }
+}
+
+// Implementation for QHandle<T>
+impl<T> UniRcLock<T> for QHandle<T> {
+    type OutRead<'a> = QReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = QWriteGuard<'a, T> where T: 'a;
+    type Weak = QWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<QReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<QWriteGuard<'a, T>, U> where T: 'a;
+
+    // Creates a new owner for this handle
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        QReadGuard {
+            owner: self.owner.borrow(),
+            cell: &self.cell,
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        QWriteGuard {
+            owner: self.owner.borrow_mut(),
+            cell: &self.cell,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let owner = self.owner.try_borrow().map_err(|_| LockError::WouldBlock)?;
+        Ok(QReadGuard {
+            owner,
+            cell: &self.cell,
+        })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let owner = self
+            .owner
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(QWriteGuard {
+            owner,
+            cell: &self.cell,
+        })
+    }
+
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.cell)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(&self.cell)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.cell, &other.cell)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        QWeak {
+            cell: Rc::downgrade(&self.cell),
+            owner: Rc::clone(&self.owner),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        let owner = self.owner;
+        Rc::try_unwrap(self.cell)
+            .map(QCell::into_inner)
+            .map_err(|cell| Self { cell, owner })
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Rc::get_mut(&mut self.cell).map(QCell::get_mut)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T> UniNew<T> for QHandle<T> {
+    fn new(value: T) -> Self {
+        Self::with_owner(&Rc::new(RefCell::new(QCellOwner::new())), value)
+    }
+}
+
+impl<T> UniWeak<T> for QWeak<T> {
+    type Strong = QHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.cell.upgrade().map(|cell| QHandle {
+            cell,
+            owner: Rc::clone(&self.owner),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::qcell::QCellOwner;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    use super::QHandle;
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr(p: &impl UniRcLock<State>) {
+        p.write().val += 1;
+    }
+
+    #[test]
+    fn qcell() {
+        let st = QHandle::new(State { val: 42 });
+        incr(&st);
+        assert_eq!(st.read().val, 43);
+        let w = st.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&st));
+        drop(st);
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn qcell_shared_owner() {
+        let owner = Rc::new(RefCell::new(QCellOwner::new()));
+        let st1 = QHandle::with_owner(&owner, State { val: 1 });
+        let st2 = QHandle::with_owner(&owner, State { val: 2 });
+        incr(&st1);
+        incr(&st2);
+        {
+            // Reads of different handles coexist
+            let r1 = st1.read();
+            let r2 = st2.read();
+            assert_eq!(r1.val + r2.val, 5);
+            // Writing to any handle of the owner is blocked
+            assert_eq!(st2.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert!(!st1.ptr_eq(&st2));
+        assert_eq!(st2.try_unwrap().ok().unwrap().val, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn qcell_conflicting_write() {
+        let owner = Rc::new(RefCell::new(QCellOwner::new()));
+        let st1 = QHandle::with_owner(&owner, State { val: 1 });
+        let st2 = QHandle::with_owner(&owner, State { val: 2 });
+        let _w = st1.write();
+        st2.write().val += 1;
+    }
+}
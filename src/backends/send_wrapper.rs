@@ -0,0 +1,151 @@
+// Implementation for Rc<RefCell<T>> wrapped into send_wrapper::SendWrapper.
+// The handle is Send + Sync, but panics if used from any thread other than
+// the one which created it. This is for the single-threaded platforms, like
+// wasm32-unknown-unknown, where the dependencies still demand Send + Sync.
+
+use crate::{LockError, UniNew, UniRcLock, UniWeak};
+use send_wrapper::SendWrapper;
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    rc::{Rc, Weak},
+};
+
+/// `Rc<RefCell<T>>` handle, which is `Send + Sync` for the single-threaded
+/// platforms, like `wasm32-unknown-unknown` without atomics.
+///
+/// The handle is checked at runtime to be used only from the thread which
+/// created it. Any access from another thread panics: locking, counting,
+/// cloning, downgrading, and also dropping the handle there.
+/// The weak handles are checked the same way.
+pub type LocalSendHandle<T> = SendWrapper<Rc<RefCell<T>>>;
+
+impl<T> UniRcLock<T> for SendWrapper<Rc<RefCell<T>>> {
+    type OutRead<'a> = Ref<'a, T> where T: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where T: 'a;
+    type Weak = SendWrapper<Weak<RefCell<T>>>;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RefCell::borrow(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RefCell::borrow_mut(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        RefCell::try_borrow(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        RefCell::try_borrow_mut(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        SendWrapper::new(Rc::downgrade(self))
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Rc::try_unwrap(self.take())
+            .map(RefCell::into_inner)
+            .map_err(SendWrapper::new)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Rc::get_mut(self).map(RefCell::get_mut)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        RefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(self.write(), f)
+    }
+}
+
+impl<T> UniNew<T> for SendWrapper<Rc<RefCell<T>>> {
+    fn new(value: T) -> Self {
+        SendWrapper::new(Rc::new(RefCell::new(value)))
+    }
+}
+
+impl<T> UniWeak<T> for SendWrapper<Weak<RefCell<T>>> {
+    type Strong = SendWrapper<Rc<RefCell<T>>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self).map(SendWrapper::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::LocalSendHandle;
+    use crate::{UniNew, UniRcLock, UniWeak};
+
+    fn needs_send_sync<H: UniRcLock<i32> + Send + Sync>(h: &H) {
+        *h.write() += 1;
+    }
+
+    #[test]
+    fn local_send() {
+        let h: LocalSendHandle<i32> = UniNew::new(1);
+        needs_send_sync(&h);
+        let weak = h.downgrade();
+        let h2 = weak.upgrade().unwrap();
+        assert!(h.ptr_eq(&h2));
+        assert_eq!(h.strong_count(), 2);
+        let h = h.try_unwrap().unwrap_err();
+        drop(h2);
+        assert_eq!(h.try_unwrap().unwrap(), 2);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn local_send_other_thread() {
+        let h: LocalSendHandle<i32> = UniNew::new(1);
+        let h2 = h.clone();
+        // Moving the handle is allowed, but using it panics
+        let res = thread::spawn(move || {
+            let r = h2.read();
+            *r
+        })
+        .join();
+        assert!(res.is_err());
+        // Still usable in the creating thread
+        assert_eq!(*h.read(), 1);
+    }
+
+    #[test]
+    fn local_send_drop_other_thread() {
+        let h: LocalSendHandle<i32> = UniNew::new(1);
+        let h2 = h.clone();
+        let res = thread::spawn(move || drop(h2)).join();
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// Implementation for seqlock::SeqLock.
+// Readers copy the value out optimistically and retry if a writer
+// intervened, so they never block the writer. Only Copy types are supported.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak, ValueGuard};
+use seqlock::{SeqLock, SeqLockGuard};
+use std::sync::{Arc, Weak};
+
+/// Handle to a small `Copy` value protected by a sequence lock.
+pub type SeqLockHandle<T> = Arc<SeqLock<T>>;
+
+/// Implementation for `Arc<seqlock::SeqLock<T>>`.
+///
+/// `read()` never blocks: it returns a [ValueGuard] with a consistent copy
+/// of the value, which is never torn by a concurrent write. Reads are retried
+/// while a write is in progress, so they are cheap only if writes are short.
+/// `write()` locks the writer mutex and modifies the value in place.
+impl<T: Copy> UniRcLock<T> for Arc<SeqLock<T>> {
+    type OutRead<'a> = ValueGuard<T> where T: 'a;
+    type OutWrite<'a> = SeqLockGuard<'a, T> where T: 'a;
+    type Weak = Weak<SeqLock<T>>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<Box<T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<SeqLockGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        ValueGuard::new(SeqLock::read(self))
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.lock_write()
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(UniRcLock::read(self))
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        self.try_lock_write().ok_or(LockError::WouldBlock)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Arc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(SeqLock::into_inner)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self).map(SeqLock::get_mut)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        // The read guards hold copies, so the address is taken from the write guard
+        &*self.lock_write() as *const T
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the copy lives in the box, not in the guard
+        unsafe { MappedReadGuard::new(Box::new(SeqLock::read(self)), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.lock_write(), f) }
+    }
+}
+
+impl<T: Copy> UniNew<T> for Arc<SeqLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(SeqLock::new(value))
+    }
+}
+
+impl<T: Copy> UniWeak<T> for Weak<SeqLock<T>> {
+    type Strong = Arc<SeqLock<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        Weak::upgrade(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    use super::SeqLockHandle;
+    use crate::{LockError, UniNew, UniRcLock};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Pose {
+        pos: [u64; 3],
+        stamp: u64,
+    }
+
+    fn step(h: &impl UniRcLock<Pose>) {
+        let mut w = h.write();
+        w.stamp += 1;
+        w.pos = [w.stamp; 3];
+    }
+
+    #[test]
+    fn seqlock() {
+        let h: SeqLockHandle<Pose> = UniNew::new(Pose {
+            pos: [0; 3],
+            stamp: 0,
+        });
+        step(&h);
+        let r = UniRcLock::read(&h);
+        step(&h);
+        // The copy is not changed by the write
+        assert_eq!(r.stamp, 1);
+        assert_eq!(UniRcLock::read(&h).stamp, 2);
+        assert_eq!(*h.map_read(|p| &p.pos[1]), 2);
+        {
+            let _w = UniRcLock::write(&h);
+            assert_eq!(h.try_write().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(h.try_unwrap().ok().unwrap().pos, [2; 3]);
+    }
+
+    #[test]
+    fn seqlock_no_torn_reads() {
+        let h: SeqLockHandle<Pose> = UniNew::new(Pose {
+            pos: [0; 3],
+            stamp: 0,
+        });
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut last = 0;
+                    while !done.load(Ordering::Relaxed) {
+                        let p = UniRcLock::read(&h);
+                        assert_eq!(p.pos, [p.stamp; 3]);
+                        assert!(p.stamp >= last);
+                        last = p.stamp;
+                    }
+                });
+            }
+            s.spawn(|| {
+                for _ in 0..100000 {
+                    step(&h);
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+        });
+        assert_eq!(UniRcLock::read(&h).stamp, 100000);
+    }
+}
@@ -0,0 +1,555 @@
+// Implementation for a plain-old-data value shared between processes
+// in a memory-mapped file. The processes are synchronized by the advisory
+// lock of the file, and the handles within a process by a RwLock,
+// since the file locks don't exclude the users of the same open file.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use bytemuck::Pod;
+use memmap2::MmapRaw;
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        self,
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError,
+    },
+};
+
+const MAGIC: [u8; 8] = *b"UniRcShm";
+const VERSION: u32 = 1;
+
+// The header at the start of the file, followed by the value
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Header {
+    magic: [u8; 8],
+    version: u32,
+    size: u32,
+}
+
+impl Header {
+    fn new<T>() -> Self {
+        Self {
+            magic: MAGIC,
+            version: VERSION,
+            size: u32::try_from(mem::size_of::<T>()).expect("The value should fit in 4 GiB"),
+        }
+    }
+}
+
+// The offset of the value in the file, aligned for T.
+// The mapping itself is page-aligned.
+const fn value_offset<T>() -> usize {
+    mem::size_of::<Header>().next_multiple_of(mem::align_of::<T>())
+}
+
+const fn file_len<T>() -> usize {
+    value_offset::<T>() + mem::size_of::<T>()
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// The mapped file shared by the clones of ShmHandle
+struct Shm<T> {
+    map: MmapRaw,
+    file: File,
+    // Excludes the writers of this process from its readers
+    local: RwLock<()>,
+    // Number of the read guards in this process, which share the file lock
+    readers: Mutex<usize>,
+    _value: PhantomData<T>,
+}
+
+impl<T: Pod> Shm<T> {
+    fn value(&self) -> *mut T {
+        // SAFETY: the file length is validated, so the value is in the mapping
+        unsafe { self.map.as_mut_ptr().add(value_offset::<T>()).cast() }
+    }
+
+    // The first reader of this process locks the file for all of them
+    fn lock_shared(&self, blocking: bool) -> Result<(), LockError> {
+        let mut readers = self.readers.lock().unwrap_or_else(PoisonError::into_inner);
+        if *readers == 0 {
+            if blocking {
+                self.file.lock_shared().expect("Shared memory file should be locked");
+            } else {
+                match self.file.try_lock_shared() {
+                    Ok(()) => {}
+                    Err(fs::TryLockError::WouldBlock) => return Err(LockError::WouldBlock),
+                    Err(fs::TryLockError::Error(err)) => {
+                        panic!("Shared memory file should be locked: {err}")
+                    }
+                }
+            }
+        }
+        *readers += 1;
+        Ok(())
+    }
+
+    // The last reader of this process unlocks the file
+    fn unlock_shared(&self) {
+        let mut readers = self.readers.lock().unwrap_or_else(PoisonError::into_inner);
+        *readers -= 1;
+        if *readers == 0 {
+            self.file.unlock().expect("Shared memory file should be unlocked");
+        }
+    }
+
+    fn read_guard<'a>(&'a self, local: RwLockReadGuard<'a, ()>) -> ShmReadGuard<'a, T> {
+        ShmReadGuard {
+            shm: self,
+            _local: local,
+        }
+    }
+}
+
+/// Handle to a plain-old-data value shared between processes
+/// in a memory-mapped file.
+///
+/// Intended for small status blocks shared by several worker processes.
+/// Each process creates or opens the file by its path, with
+/// [create](Self::create) or [open](Self::open), and the clones of the handle
+/// share the mapping within the process. The file starts with a header
+/// holding the format version and the size of the value, which [open](Self::open)
+/// validates, so the processes built with a different `T` don't misread it.
+///
+/// The guards lock the advisory lock of the file, shared for reading
+/// and exclusive for writing, so they exclude the guards of the other processes,
+/// and deref directly into the mapped memory. The lock is released when
+/// the guard is dropped, or by the OS if the process dies. Only the processes
+/// using `ShmHandle` respect the lock: the file must not be written otherwise.
+///
+/// The counts and `ptr_eq()` only account for the handles of this process,
+/// and `get_mut()` always returns `None` since other processes may access
+/// the value. `try_unwrap()` copies the value out of the mapping.
+/// `new()` maps a new temporary file, which is not shared with other processes.
+pub struct ShmHandle<T> {
+    inner: Arc<Shm<T>>,
+}
+
+impl<T: Pod> ShmHandle<T> {
+    /// Create the file at the path with the initial value and map it.
+    ///
+    /// An existing file is replaced, so it must not be mapped by other processes:
+    /// create the file once and [open](Self::open) it everywhere else.
+    pub fn create(path: impl AsRef<Path>, init: T) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        // The concurrent open() waits until the file is initialized
+        file.lock()?;
+        file.set_len(file_len::<T>() as u64)?;
+        let map = MmapRaw::map_raw(&file)?;
+        // SAFETY: the mapping has the length of the header and the value,
+        // and nobody else accesses it while the file is locked
+        unsafe {
+            map.as_mut_ptr().cast::<Header>().write_unaligned(Header::new::<T>());
+            map.as_mut_ptr().add(value_offset::<T>()).cast::<T>().write(init);
+        }
+        file.unlock()?;
+        Ok(Self::from_map(map, file))
+    }
+
+    /// Map the file created by [create](Self::create), possibly in another process.
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the file is not a shared memory
+    /// file, or its version or the size of the value don't match.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        file.lock_shared()?;
+        let map = Self::validate(&file);
+        file.unlock()?;
+        Ok(Self::from_map(map?, file))
+    }
+
+    fn validate(file: &File) -> io::Result<MmapRaw> {
+        if file.metadata()?.len() != file_len::<T>() as u64 {
+            return Err(invalid("Shared memory file has a wrong length"));
+        }
+        let map = MmapRaw::map_raw(file)?;
+        // SAFETY: the mapping is at least as long as the header
+        let header = unsafe { map.as_ptr().cast::<Header>().read_unaligned() };
+        if header.magic != MAGIC {
+            return Err(invalid("Not a shared memory file"));
+        }
+        if header.version != VERSION {
+            return Err(invalid("Shared memory file has an unsupported version"));
+        }
+        if header != Header::new::<T>() {
+            return Err(invalid("Shared memory file holds a value of a different size"));
+        }
+        Ok(map)
+    }
+
+    fn from_map(map: MmapRaw, file: File) -> Self {
+        Self {
+            inner: Arc::new(Shm {
+                map,
+                file,
+                local: RwLock::new(()),
+                readers: Mutex::new(0),
+                _value: PhantomData,
+            }),
+        }
+    }
+
+    // A unique path for the private file of new()
+    fn temp_path() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("uni_rc_lock-{}-{n}.shm", process::id()))
+    }
+}
+
+impl<T> Clone for ShmHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// Weak counterpart of [ShmHandle]
+pub struct ShmWeak<T> {
+    inner: sync::Weak<Shm<T>>,
+}
+
+impl<T> Clone for ShmWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: sync::Weak::clone(&self.inner),
+        }
+    }
+}
+
+/// Read guard of [ShmHandle]. Keeps the file locked for reading.
+pub struct ShmReadGuard<'a, T: Pod> {
+    shm: &'a Shm<T>,
+    // Released after the file is unlocked
+    _local: RwLockReadGuard<'a, ()>,
+}
+
+impl<T: Pod> Deref for ShmReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: no process writes while the file is locked for reading
+        unsafe { &*self.shm.value() }
+    }
+}
+
+impl<T: Pod> Drop for ShmReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.shm.unlock_shared();
+    }
+}
+
+/// Write guard of [ShmHandle]. Keeps the file locked for writing.
+pub struct ShmWriteGuard<'a, T: Pod> {
+    shm: &'a Shm<T>,
+    // Released after the file is unlocked
+    _local: RwLockWriteGuard<'a, ()>,
+}
+
+impl<T: Pod> Deref for ShmWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: no other process accesses the value while the file is locked
+        unsafe { &*self.shm.value() }
+    }
+}
+
+impl<T: Pod> DerefMut for ShmWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: no other process accesses the value while the file is locked
+        unsafe { &mut *self.shm.value() }
+    }
+}
+
+impl<T: Pod> Drop for ShmWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.shm
+            .file
+            .unlock()
+            .expect("Shared memory file should be unlocked");
+    }
+}
+
+// Implementation for ShmHandle<T>
+//
+// Any bytes are a valid Pod value, so a panic in a write guard can't
+// leave the value broken, and the local lock poisoning is ignored.
+impl<T: Pod> UniRcLock<T> for ShmHandle<T> {
+    type OutRead<'a> = ShmReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = ShmWriteGuard<'a, T> where T: 'a;
+    type Weak = ShmWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<ShmReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<ShmWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let local = self.inner.local.read().unwrap_or_else(PoisonError::into_inner);
+        // Only the non-blocking locking fails
+        let _ = self.inner.lock_shared(true);
+        self.inner.read_guard(local)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let local = self.inner.local.write().unwrap_or_else(PoisonError::into_inner);
+        self.inner.file.lock().expect("Shared memory file should be locked");
+        ShmWriteGuard {
+            shm: &self.inner,
+            _local: local,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let local = match self.inner.local.try_read() {
+            Ok(local) => local,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(LockError::WouldBlock),
+        };
+        self.inner.lock_shared(false)?;
+        Ok(self.inner.read_guard(local))
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let local = match self.inner.local.try_write() {
+            Ok(local) => local,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(LockError::WouldBlock),
+        };
+        match self.inner.file.try_lock() {
+            Ok(()) => Ok(ShmWriteGuard {
+                shm: &self.inner,
+                _local: local,
+            }),
+            Err(fs::TryLockError::WouldBlock) => Err(LockError::WouldBlock),
+            Err(fs::TryLockError::Error(err)) => {
+                panic!("Shared memory file should be locked: {err}")
+            }
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.inner)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        ShmWeak {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        if Arc::strong_count(&self.inner) != 1 {
+            return Err(self);
+        }
+        let value = *self.read();
+        Ok(value)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the mapping, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the mapping, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Pod> UniNew<T> for ShmHandle<T> {
+    fn new(value: T) -> Self {
+        let path = Self::temp_path();
+        let handle = Self::create(&path, value).expect("Shared memory file should be created");
+        // The mapping stays valid without the name where the OS allows removing
+        // an open file, and the file is left in the temporary directory otherwise
+        let _ = fs::remove_file(path);
+        handle
+    }
+}
+
+impl<T: Pod> UniWeak<T> for ShmWeak<T> {
+    type Strong = ShmHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.inner.upgrade().map(|inner| ShmHandle { inner })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs, io,
+        path::PathBuf,
+        process::{Command, Stdio},
+        thread,
+    };
+
+    use bytemuck::{Pod, Zeroable};
+
+    use super::ShmHandle;
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Status {
+        counter: u64,
+        busy: u32,
+        workers: u32,
+    }
+
+    // SAFETY: repr(C) without padding, and any bytes are valid
+    unsafe impl Zeroable for Status {}
+    unsafe impl Pod for Status {}
+
+    fn temp_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("uni_rc_lock-test-{}-{name}", std::process::id()))
+    }
+
+    const INCREMENTS: u64 = 200;
+
+    // Increments the counter non-atomically, so the lost updates
+    // would show the broken mutual exclusion
+    fn work<H: UniRcLock<Status>>(h: &H) {
+        h.write().workers += 1;
+        for _ in 0..INCREMENTS {
+            let mut s = h.write();
+            assert_eq!(s.busy, 0, "Another writer is active");
+            s.busy = 1;
+            let counter = s.counter;
+            thread::yield_now();
+            s.counter = counter + 1;
+            s.busy = 0;
+        }
+    }
+
+    // Runs in the child process spawned by shm_processes(),
+    // and does nothing when run as a test by itself
+    #[test]
+    fn shm_child() {
+        if let Ok(path) = env::var("UNI_RC_LOCK_SHM") {
+            let h = ShmHandle::<Status>::open(path).unwrap();
+            work(&h);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn shm_processes() {
+        let path = temp_file("processes");
+        let h = ShmHandle::create(&path, Status {
+            counter: 0,
+            busy: 0,
+            workers: 0,
+        })
+        .unwrap();
+        let children: Vec<_> = (0..2)
+            .map(|_| {
+                Command::new(env::current_exe().unwrap())
+                    .args(["--exact", "backends::shm::tests::shm_child"])
+                    .env("UNI_RC_LOCK_SHM", &path)
+                    .stdout(Stdio::null())
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        let local = h.clone();
+        let t = thread::spawn(move || work(&local));
+        work(&h);
+        t.join().unwrap();
+        for mut child in children {
+            assert!(child.wait().unwrap().success());
+        }
+        let s = *h.read();
+        assert_eq!(s.workers, 4);
+        assert_eq!(s.counter, 4 * INCREMENTS);
+        drop(h);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn shm_locks() {
+        let path = temp_file("locks");
+        let h1 = ShmHandle::create(&path, 1u64).unwrap();
+        // Opened separately, like by another process
+        let h2 = ShmHandle::<u64>::open(&path).unwrap();
+        assert!(!h1.ptr_eq(&h2));
+        {
+            let h3 = h1.clone();
+            let r1 = h1.read();
+            let r2 = h3.try_read().unwrap();
+            assert_eq!(*h2.try_read().unwrap(), 1);
+            assert_eq!(h2.try_write().err(), Some(LockError::WouldBlock));
+            drop(r1);
+            // Still locked by the other reader of this process
+            assert_eq!(h2.try_write().err(), Some(LockError::WouldBlock));
+            drop(r2);
+        }
+        *h2.write() += 1;
+        {
+            let _w = h1.write();
+            assert_eq!(h2.try_read().err(), Some(LockError::WouldBlock));
+            assert_eq!(h1.try_read().err(), Some(LockError::WouldBlock));
+        }
+        assert_eq!(*h1.map_read(|v| v), 2);
+
+        let w = h1.downgrade();
+        assert_eq!(*w.upgrade().unwrap().read(), 2);
+        assert_eq!(h1.try_unwrap().ok(), Some(2));
+        assert!(w.upgrade().is_none());
+        drop(h2);
+        fs::remove_file(path).unwrap();
+
+        let h = ShmHandle::new(3u32);
+        *h.write() *= 2;
+        assert_eq!(h.try_unwrap().ok(), Some(6));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn shm_validation() {
+        let path = temp_file("validation");
+        ShmHandle::create(&path, 0u64).unwrap();
+        let err = ShmHandle::<u32>::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = ShmHandle::<[u64; 2]>::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(*ShmHandle::<u64>::open(&path).unwrap().read(), 0);
+
+        fs::write(&path, [0u8; 24]).unwrap();
+        let err = ShmHandle::<u64>::open(&path).err().unwrap();
+        assert_eq!(err.to_string(), "Not a shared memory file");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+// Implementation for the shuttle mock RwLock. Available with the `shuttle` feature
+// or the `shuttle` cfg. shuttle runs the threads under a randomized scheduler
+// and explores more interleavings than the real scheduler in a test run.
+// It is intended for testing only and can't be used for real concurrency.
+// shuttle's Arc is std Arc, so Arc<shuttle::sync::RwLock<T>> is a UniRcLock
+// handle through the UniCell implementation.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniCell};
+use shuttle::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Implementation for `shuttle::sync::RwLock<T>`, shared by `Arc<shuttle::sync::RwLock<T>>`,
+/// which is [MultiThreaded](crate::MultiThreaded) with the `shuttle` feature.
+///
+/// Poisoning is handled like for std `RwLock<T>`.
+impl<T> UniCell for RwLock<T> {
+    type Value = T;
+    type Read<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type Write<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where T: 'a;
+
+    fn new(value: T) -> Self {
+        RwLock::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        RwLock::read(self).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        RwLock::write(self).expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        Ok(RwLock::try_read(self)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        Ok(RwLock::try_write(self)?)
+    }
+
+    fn into_inner(self) -> T {
+        RwLock::into_inner(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        RwLock::get_mut(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn clear_poison(&self) {
+        RwLock::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniCell::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniCell::write(self), f) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shuttle::{sync::RwLock, thread};
+    use std::sync::Arc;
+
+    use crate::{UniNew, UniRcLock};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    // Generic code under test, unaware of shuttle
+    fn incr<H: UniRcLock<State>>(h: &H) {
+        let v = h.read().val;
+        h.write().val = v + 1;
+    }
+
+    #[test]
+    fn shuttle_threads() {
+        shuttle::check_random(
+            || {
+                let st: Arc<RwLock<State>> = UniNew::new(State { val: 0 });
+                let threads: Vec<_> = (0..10)
+                    .map(|_| {
+                        let h = st.clone();
+                        thread::spawn(move || h.write().val += 1)
+                    })
+                    .collect();
+                for t in threads {
+                    t.join().unwrap();
+                }
+                assert_eq!(st.read().val, 10);
+                assert!(st.downgrade().upgrade().is_some());
+            },
+            100,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn shuttle_finds_race() {
+        // Read-then-write is not atomic, so some schedule loses an increment
+        shuttle::check_random(
+            || {
+                let st: Arc<RwLock<State>> = UniNew::new(State { val: 0 });
+                let threads: Vec<_> = (0..2)
+                    .map(|_| {
+                        let h = st.clone();
+                        thread::spawn(move || incr(&h))
+                    })
+                    .collect();
+                for t in threads {
+                    t.join().unwrap();
+                }
+                assert_eq!(st.read().val, 2);
+            },
+            1000,
+        );
+    }
+}
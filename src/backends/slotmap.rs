@@ -0,0 +1,387 @@
+// Implementations for the handles to the slots of a shared slotmap::SlotMap.
+// Each handle holds the shared arena and the key of its slot, and its guards
+// lock the whole arena, projected to the slot.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniCell, UniNew, UniRcLock, UniWeak};
+use slotmap::{DefaultKey, SlotMap};
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    marker::PhantomData,
+    rc::Rc,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A single-threaded arena shared by [ArenaHandle]s
+pub type LocalArena<T> = Rc<RefCell<SlotMap<DefaultKey, T>>>;
+
+/// A thread-safe arena shared by [SyncArenaHandle]s
+pub type SyncArena<T> = Arc<RwLock<SlotMap<DefaultKey, T>>>;
+
+/// Handle to a value in a slot of an arena shared by many values,
+/// like the entities of an entity system.
+///
+/// The handle is cheap: it holds the shared arena, `Rc<RefCell<SlotMap<DefaultKey, T>>>`
+/// by default, and the key of the slot. `read()` and `write()` borrow the whole
+/// arena and return the guards projected to the slot, so a write guard
+/// of one value conflicts with any other guard to the same arena, even
+/// for a different key: for `Rc<RefCell<..>>` it panics like `RefCell`,
+/// and for [SyncArenaHandle] it blocks until the other guard is dropped.
+///
+/// The value may be removed from the arena while the handles to it are alive,
+/// after that `read()` and `write()` panic. [is_alive](Self::is_alive)
+/// checks if the value is still in the arena.
+///
+/// The handles share the arena rather than the value, so `strong_count()`
+/// counts all the handles to the arena. `try_unwrap()` succeeds only if this
+/// handle is the only one left, and drops the other values of the arena.
+/// `new()` creates a new arena with a single value.
+pub struct ArenaHandle<T, A = LocalArena<T>> {
+    arena: A,
+    key: DefaultKey,
+    _value: PhantomData<fn() -> T>,
+}
+
+/// Handle to a value in a slot of a thread-safe arena, `Arc<RwLock<SlotMap<DefaultKey, T>>>`.
+/// See [ArenaHandle].
+pub type SyncArenaHandle<T> = ArenaHandle<T, SyncArena<T>>;
+
+impl<T, A: UniRcLock<SlotMap<DefaultKey, T>>> ArenaHandle<T, A> {
+    /// Insert the value into the arena and return a handle to it
+    pub fn insert(arena: &A, value: T) -> Self {
+        let key = arena.write().insert(value);
+        Self::from_key(arena, key)
+    }
+
+    /// Create a handle to the value already stored in the arena
+    pub fn from_key(arena: &A, key: DefaultKey) -> Self {
+        Self {
+            arena: arena.clone(),
+            key,
+            _value: PhantomData,
+        }
+    }
+
+    /// The key of the slot in the arena
+    pub fn key(&self) -> DefaultKey {
+        self.key
+    }
+
+    /// The arena holding the value
+    pub fn arena(&self) -> &A {
+        &self.arena
+    }
+
+    /// Returns `true` if the value is still in the arena.
+    /// Locks the arena for reading.
+    pub fn is_alive(&self) -> bool {
+        self.arena.with_read(|arena| arena.contains_key(self.key))
+    }
+}
+
+impl<T, A: Clone> Clone for ArenaHandle<T, A> {
+    fn clone(&self) -> Self {
+        Self {
+            arena: self.arena.clone(),
+            key: self.key,
+            _value: PhantomData,
+        }
+    }
+}
+
+/// Weak counterpart of [ArenaHandle]
+#[derive(Clone)]
+pub struct ArenaWeak<W> {
+    arena: W,
+    key: DefaultKey,
+}
+
+#[cold]
+fn dangling(key: DefaultKey) -> ! {
+    panic!("The value with key {key:?} should not be removed from the arena")
+}
+
+fn slot<T>(arena: &SlotMap<DefaultKey, T>, key: DefaultKey) -> &T {
+    arena.get(key).unwrap_or_else(|| dangling(key))
+}
+
+fn slot_mut<T>(arena: &mut SlotMap<DefaultKey, T>, key: DefaultKey) -> &mut T {
+    arena.get_mut(key).unwrap_or_else(|| dangling(key))
+}
+
+fn unwrap_arena<T, A: UniRcLock<SlotMap<DefaultKey, T>>>(
+    handle: ArenaHandle<T, A>,
+) -> Result<T, ArenaHandle<T, A>> {
+    let key = handle.key;
+    match handle.arena.try_unwrap() {
+        Ok(mut arena) => Ok(arena.remove(key).unwrap_or_else(|| dangling(key))),
+        Err(arena) => Err(ArenaHandle::from_key(&arena, key)),
+    }
+}
+
+// The counting functions are the same for both arenas
+macro_rules! impl_arena_counts {
+    () => {
+        fn strong_count(&self) -> usize {
+            self.arena.strong_count()
+        }
+
+        fn weak_count(&self) -> usize {
+            self.arena.weak_count()
+        }
+
+        fn ptr_eq(&self, other: &Self) -> bool {
+            self.arena.ptr_eq(&other.arena) && self.key == other.key
+        }
+
+        fn downgrade(&self) -> Self::Weak {
+            ArenaWeak {
+                arena: self.arena.downgrade(),
+                key: self.key,
+            }
+        }
+
+        fn try_unwrap(self) -> Result<T, Self> {
+            unwrap_arena(self)
+        }
+
+        fn get_mut(&mut self) -> Option<&mut T> {
+            let key = self.key;
+            UniRcLock::get_mut(&mut self.arena).map(|arena| slot_mut(arena, key))
+        }
+    };
+}
+
+// Implementation for ArenaHandle<T, Rc<RefCell<SlotMap<DefaultKey, T>>>>
+impl<T> UniRcLock<T> for ArenaHandle<T, LocalArena<T>> {
+    type OutRead<'a> = Ref<'a, T> where Self: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where Self: 'a;
+    type Weak = ArenaWeak<<LocalArena<T> as UniRcLock<SlotMap<DefaultKey, T>>>::Weak>;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where Self: 'a;
+
+    impl_arena_counts!();
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Ref::map(self.arena.borrow(), |arena| slot(arena, self.key))
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RefMut::map(self.arena.borrow_mut(), |arena| slot_mut(arena, self.key))
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let arena = self.arena.try_borrow().map_err(|_| LockError::WouldBlock)?;
+        Ok(Ref::map(arena, |arena| slot(arena, self.key)))
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let arena = self
+            .arena
+            .try_borrow_mut()
+            .map_err(|_| LockError::WouldBlock)?;
+        Ok(RefMut::map(arena, |arena| slot_mut(arena, self.key)))
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(self.write(), f)
+    }
+}
+
+// Implementation for ArenaHandle<T, Arc<RwLock<SlotMap<DefaultKey, T>>>>
+//
+// The std guards can't be projected, so the mapped guards are used.
+impl<T> UniRcLock<T> for ArenaHandle<T, SyncArena<T>> {
+    type OutRead<'a> = MappedReadGuard<RwLockReadGuard<'a, SlotMap<DefaultKey, T>>, T> where Self: 'a;
+    type OutWrite<'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, SlotMap<DefaultKey, T>>, T> where Self: 'a;
+    type Weak = ArenaWeak<<SyncArena<T> as UniRcLock<SlotMap<DefaultKey, T>>>::Weak>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<RwLockReadGuard<'a, SlotMap<DefaultKey, T>>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, SlotMap<DefaultKey, T>>, U> where Self: 'a;
+
+    impl_arena_counts!();
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.map_read(|value| value)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.map_write(|value| value)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let arena = UniCell::try_read(&*self.arena)?;
+        // SAFETY: the value lives in the lock, not in the guard
+        Ok(unsafe { MappedReadGuard::new(arena, |arena| slot(arena, self.key)) })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let arena = UniCell::try_write(&*self.arena)?;
+        // SAFETY: the value lives in the lock, not in the guard
+        Ok(unsafe { MappedWriteGuard::new(arena, |arena| slot_mut(arena, self.key)) })
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        let arena = UniCell::read(&*self.arena);
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(arena, |arena| f(slot(arena, self.key))) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        let arena = UniCell::write(&*self.arena);
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(arena, |arena| f(slot_mut(arena, self.key))) }
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.arena.is_poisoned()
+    }
+
+    fn clear_poison(&self) {
+        self.arena.clear_poison()
+    }
+}
+
+// Each new handle gets a new arena with the single value
+impl<T, A> UniNew<T> for ArenaHandle<T, A>
+where
+    A: UniNew<SlotMap<DefaultKey, T>>,
+    Self: UniRcLock<T>,
+{
+    fn new(value: T) -> Self {
+        let mut arena = SlotMap::new();
+        let key = arena.insert(value);
+        Self::from_key(&A::new(arena), key)
+    }
+}
+
+impl<T, W> UniWeak<T> for ArenaWeak<W>
+where
+    W: UniWeak<SlotMap<DefaultKey, T>>,
+    ArenaHandle<T, W::Strong>: UniRcLock<T, Weak = Self>,
+{
+    type Strong = ArenaHandle<T, W::Strong>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.arena
+            .upgrade()
+            .map(|arena| ArenaHandle::from_key(&arena, self.key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use slotmap::SlotMap;
+
+    use super::{ArenaHandle, LocalArena, SyncArena, SyncArenaHandle};
+    use crate::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Entity {
+        hp: i32,
+    }
+
+    // The generic system, which knows nothing about the arena
+    fn damage<H: UniRcLock<Entity>>(entities: &[H], amount: i32) {
+        for e in entities {
+            e.write().hp -= amount;
+        }
+    }
+
+    #[test]
+    fn arena() {
+        let arena: LocalArena<Entity> = UniNew::new(SlotMap::new());
+        let a = ArenaHandle::insert(&arena, Entity { hp: 10 });
+        let b = ArenaHandle::insert(&arena, Entity { hp: 20 });
+        damage(&[a.clone(), b.clone()], 3);
+        assert_eq!(a.read().hp, 7);
+        assert_eq!(*b.map_read(|e| &e.hp), 17);
+        assert_eq!(arena.read()[b.key()].hp, 17);
+
+        // Several read guards into the same arena coexist
+        let (ra, rb) = (a.read(), b.read());
+        assert_eq!(ra.hp + rb.hp, 24);
+        assert_eq!(a.try_write().err(), Some(LockError::WouldBlock));
+        drop((ra, rb));
+
+        assert!(a.ptr_eq(&a.clone()));
+        assert!(!a.ptr_eq(&b));
+        let w = b.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&b));
+
+        arena.write().remove(b.key());
+        assert!(a.is_alive());
+        assert!(!b.is_alive());
+        drop(arena);
+        let a = a.try_unwrap().err().unwrap();
+        drop(b);
+        assert_eq!(a.try_unwrap().ok().unwrap(), Entity { hp: 7 });
+        assert!(w.upgrade().is_none());
+
+        let mut c = ArenaHandle::<_, LocalArena<_>>::new(Entity { hp: 1 });
+        c.get_mut().unwrap().hp += 1;
+        assert_eq!(c.read().hp, 2);
+    }
+
+    // The guards borrow the whole arena, so the write guards
+    // to different values conflict
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn arena_conflict() {
+        let arena: LocalArena<Entity> = UniNew::new(SlotMap::new());
+        let a = ArenaHandle::insert(&arena, Entity { hp: 10 });
+        let b = ArenaHandle::insert(&arena, Entity { hp: 20 });
+        let _wa = a.write();
+        let _wb = b.write();
+    }
+
+    #[test]
+    #[should_panic(expected = "should not be removed from the arena")]
+    fn arena_dangling() {
+        let arena: LocalArena<Entity> = UniNew::new(SlotMap::new());
+        let a = ArenaHandle::insert(&arena, Entity { hp: 10 });
+        arena.write().remove(a.key());
+        a.read();
+    }
+
+    #[test]
+    fn arena_sync() {
+        let arena: SyncArena<Entity> = UniNew::new(SlotMap::new());
+        let entities: Vec<SyncArenaHandle<_>> = (0..4)
+            .map(|_| ArenaHandle::insert(&arena, Entity { hp: 100 }))
+            .collect();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| damage(&entities, 1));
+            }
+        });
+        assert!(entities.iter().all(|e| e.read().hp == 96));
+
+        let r = entities[0].read();
+        assert_eq!(entities[1].try_write().err(), Some(LockError::WouldBlock));
+        assert_eq!(entities[1].try_read().unwrap().hp, 96);
+        drop(r);
+        arena.write().remove(entities[3].key());
+        assert!(!entities[3].is_alive());
+        assert!(entities[2].downgrade().upgrade().unwrap().ptr_eq(&entities[2]));
+    }
+}
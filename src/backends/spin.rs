@@ -0,0 +1,281 @@
+// Implementations for spin locks.
+// Spin locks busy-wait instead of parking the thread and are never poisoned.
+// Only core and alloc are used here, so these backends are usable without std.
+// With the `portable-atomic` feature, portable_atomic_util::Arc is supported as well
+// for the targets without atomic compare-and-swap, where alloc::sync::Arc is missing.
+// The implementations for both Arcs are generated by the same macro.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, MutexBackend, UniNew, UniRcLock, UniWeak};
+use ::spin::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+// Arc is missing on the targets without atomic pointers
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::{Arc, Weak};
+use core::ops::Deref;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic_util::{Arc as PortableArc, Weak as PortableWeak};
+
+/// Handle to a value in `spin::RwLock`.
+///
+/// This is `Arc<spin::RwLock<T>>`, where `Arc` is `alloc::sync::Arc`
+/// or `portable_atomic_util::Arc` with the `portable-atomic` feature,
+/// so the code using the alias doesn't depend on the feature.
+/// Both `Arc`s are handles regardless of the alias.
+#[cfg(all(not(feature = "portable-atomic"), target_has_atomic = "ptr"))]
+pub type SpinHandle<T> = Arc<RwLock<T>>;
+/// Handle to a value in `spin::RwLock`.
+///
+/// This is `Arc<spin::RwLock<T>>`, where `Arc` is `alloc::sync::Arc`
+/// or `portable_atomic_util::Arc` with the `portable-atomic` feature,
+/// so the code using the alias doesn't depend on the feature.
+/// Both `Arc`s are handles regardless of the alias.
+#[cfg(feature = "portable-atomic")]
+pub type SpinHandle<T> = PortableArc<RwLock<T>>;
+
+/// Handle to a value in `spin::Mutex`, the same way as [SpinHandle].
+#[cfg(all(not(feature = "portable-atomic"), target_has_atomic = "ptr"))]
+pub type SpinMutexHandle<T> = Arc<Mutex<T>>;
+/// Handle to a value in `spin::Mutex`, the same way as [SpinHandle].
+#[cfg(feature = "portable-atomic")]
+pub type SpinMutexHandle<T> = PortableArc<Mutex<T>>;
+
+macro_rules! impl_spin {
+    ($arc:ident, $weak:ident) => {
+        // Implementation for Arc<spin::RwLock<T>>
+        impl<T> UniRcLock<T> for $arc<RwLock<T>> {
+            type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+            type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+            type Weak = $weak<RwLock<T>>;
+            type MappedRead<'a, U: ?Sized + 'a> =
+                MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+            type MappedWrite<'a, U: ?Sized + 'a> =
+                MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where T: 'a;
+
+            fn read<'a>(&'a self) -> Self::OutRead<'a> {
+                $arc::deref(self).read()
+            }
+
+            fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+                $arc::deref(self).write()
+            }
+
+            fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+                $arc::deref(self).try_read().ok_or(LockError::WouldBlock)
+            }
+
+            fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+                $arc::deref(self).try_write().ok_or(LockError::WouldBlock)
+            }
+
+            fn strong_count(&self) -> usize {
+                $arc::strong_count(self)
+            }
+
+            fn weak_count(&self) -> usize {
+                $arc::weak_count(self)
+            }
+
+            fn ptr_eq(&self, other: &Self) -> bool {
+                $arc::ptr_eq(self, other)
+            }
+
+            fn downgrade(&self) -> Self::Weak {
+                $arc::downgrade(self)
+            }
+
+            fn try_unwrap(self) -> Result<T, Self> {
+                $arc::try_unwrap(self).map(|lock| lock.into_inner())
+            }
+
+            fn get_mut(&mut self) -> Option<&mut T> {
+                $arc::get_mut(self).map(|lock| lock.get_mut())
+            }
+
+            fn as_ptr(&self) -> *const T {
+                RwLock::as_mut_ptr(self)
+            }
+
+            fn map_read<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&T) -> &U,
+            ) -> Self::MappedRead<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedReadGuard::new(self.read(), f) }
+            }
+
+            fn map_write<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&mut T) -> &mut U,
+            ) -> Self::MappedWrite<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedWriteGuard::new(self.write(), f) }
+            }
+        }
+
+        impl<T> UniNew<T> for $arc<RwLock<T>> {
+            fn new(value: T) -> Self {
+                $arc::new(RwLock::new(value))
+            }
+        }
+
+        impl<T> UniWeak<T> for $weak<RwLock<T>> {
+            type Strong = $arc<RwLock<T>>;
+
+            fn upgrade(&self) -> Option<Self::Strong> {
+                $weak::upgrade(self)
+            }
+        }
+
+        // Implementation for Arc<spin::Mutex<T>>.
+        // Like for std Mutex, read() is exclusive.
+        impl<T> UniRcLock<T> for $arc<Mutex<T>> {
+            type OutRead<'a> = MutexGuard<'a, T> where T: 'a;
+            type OutWrite<'a> = MutexGuard<'a, T> where T: 'a;
+            type Weak = $weak<Mutex<T>>;
+            type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<MutexGuard<'a, T>, U> where T: 'a;
+            type MappedWrite<'a, U: ?Sized + 'a> =
+                MappedWriteGuard<MutexGuard<'a, T>, U> where T: 'a;
+            const ALLOWS_CONCURRENT_READS: bool = false;
+
+            fn read<'a>(&'a self) -> Self::OutRead<'a> {
+                $arc::deref(self).lock()
+            }
+
+            fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+                $arc::deref(self).lock()
+            }
+
+            fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+                $arc::deref(self).try_lock().ok_or(LockError::WouldBlock)
+            }
+
+            fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+                $arc::deref(self).try_lock().ok_or(LockError::WouldBlock)
+            }
+
+            fn strong_count(&self) -> usize {
+                $arc::strong_count(self)
+            }
+
+            fn weak_count(&self) -> usize {
+                $arc::weak_count(self)
+            }
+
+            fn ptr_eq(&self, other: &Self) -> bool {
+                $arc::ptr_eq(self, other)
+            }
+
+            fn downgrade(&self) -> Self::Weak {
+                $arc::downgrade(self)
+            }
+
+            fn try_unwrap(self) -> Result<T, Self> {
+                $arc::try_unwrap(self).map(|lock| lock.into_inner())
+            }
+
+            fn get_mut(&mut self) -> Option<&mut T> {
+                $arc::get_mut(self).map(|lock| lock.get_mut())
+            }
+
+            fn map_read<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&T) -> &U,
+            ) -> Self::MappedRead<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedReadGuard::new(self.read(), f) }
+            }
+
+            fn map_write<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&mut T) -> &mut U,
+            ) -> Self::MappedWrite<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedWriteGuard::new(self.write(), f) }
+            }
+        }
+
+        impl<T> UniNew<T> for $arc<Mutex<T>> {
+            fn new(value: T) -> Self {
+                $arc::new(Mutex::new(value))
+            }
+        }
+
+        impl<T> MutexBackend<T> for $arc<Mutex<T>> {}
+
+        impl<T> UniWeak<T> for $weak<Mutex<T>> {
+            type Strong = $arc<Mutex<T>>;
+
+            fn upgrade(&self) -> Option<Self::Strong> {
+                $weak::upgrade(self)
+            }
+        }
+    };
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl_spin!(Arc, Weak);
+#[cfg(feature = "portable-atomic")]
+impl_spin!(PortableArc, PortableWeak);
+
+#[cfg(test)]
+mod tests {
+    use super::{SpinHandle, SpinMutexHandle};
+    use crate::{LockError, UniNew, UniRcLock};
+
+    #[derive(Debug)]
+    struct Foo(i32);
+
+    fn incr_foo(v: impl UniRcLock<Foo>) {
+        v.write().0 += 1;
+    }
+
+    #[test]
+    fn spin_rwlock() {
+        let ptr: SpinHandle<Foo> = UniNew::new(Foo(0));
+        incr_foo(ptr.clone());
+        assert_eq!(ptr.read().0, 1);
+        let _r = ptr.read();
+        assert!(ptr.try_read().is_ok());
+        assert_eq!(ptr.try_write().err(), Some(LockError::WouldBlock));
+    }
+
+    #[test]
+    fn spin_mutex() {
+        let ptr: SpinMutexHandle<Foo> = UniNew::new(Foo(0));
+        incr_foo(ptr.clone());
+        assert_eq!(ptr.read().0, 1);
+        let _r = ptr.read();
+        assert_eq!(ptr.try_read().err(), Some(LockError::WouldBlock));
+        const { assert!(!SpinMutexHandle::<Foo>::ALLOWS_CONCURRENT_READS) };
+        const { assert!(SpinHandle::<Foo>::ALLOWS_CONCURRENT_READS) };
+    }
+
+    // Both Arcs are handles, whichever the aliases name
+    #[cfg(feature = "portable-atomic")]
+    #[test]
+    fn spin_both_arcs() {
+        use ::spin::RwLock;
+        let std_arc: std::sync::Arc<RwLock<Foo>> = UniNew::new(Foo(0));
+        let portable: portable_atomic_util::Arc<RwLock<Foo>> = UniNew::new(Foo(0));
+        incr_foo(std_arc.clone());
+        incr_foo(portable.clone());
+        assert_eq!(std_arc.read().0 + portable.read().0, 2);
+    }
+
+    #[test]
+    fn threads_test_spin() {
+        use std::thread;
+        let ptr: SpinHandle<Foo> = UniNew::new(Foo(0));
+        let threads: Vec<_> = (0..10)
+            .map(|_| {
+                let h = ptr.clone();
+                thread::spawn(move || incr_foo(h))
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(ptr.read().0, 10);
+    }
+}
@@ -0,0 +1,674 @@
+// Implementations for std::sync locks. Available with the `std` feature.
+
+use crate::{
+    map_into, CheckedResult, LockError, MappedReadGuard, MappedWriteGuard, MutexBackend, NoWeak,
+    UniCell, UniLockError, UniNew, UniPtr, UniRcCopy, UniRcLock, UniWeak,
+};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        TryLockError, Weak,
+    },
+};
+
+/// Conversion of any [UniRcLock] handle to `Arc<RwLock<T>>`.
+///
+/// This is mostly useful for building the value single-threaded
+/// in `Rc<RefCell<T>>` and then sending it to other threads.
+/// Implemented for all handles.
+pub trait IntoArc<T>: UniRcLock<T> {
+    /// Move the value to a new `Arc<RwLock<T>>` if this is the only strong handle.
+    /// Otherwise the handle is returned back in `Err`.
+    fn into_arc(self) -> Result<Arc<RwLock<T>>, Self>
+    where
+        T: Send + Sync,
+    {
+        self.try_unwrap().map(|value| Arc::new(RwLock::new(value)))
+    }
+}
+
+impl<T, H: UniRcLock<T>> IntoArc<T> for H {}
+
+/// Move the value of `Rc<RefCell<T>>` to a new `Arc<RwLock<T>>`,
+/// for example to compare the single-threaded state with the thread-safe one.
+///
+/// The value is moved rather than cloned, so this requires the unique
+/// ownership: if there are other strong handles, the handle is returned
+/// back in `Err`. The weak handles can't be upgraded anymore after that.
+/// See [arc_to_rc] for the way back.
+pub fn rc_to_arc<T: Send + Sync>(handle: Rc<RefCell<T>>) -> Result<Arc<RwLock<T>>, Rc<RefCell<T>>> {
+    map_into(handle, |value| value)
+}
+
+/// Move the value of `Arc<RwLock<T>>` to a new `Rc<RefCell<T>>`,
+/// the reverse of [rc_to_arc].
+///
+/// Requires the unique ownership the same way: if there are other strong
+/// handles, possibly in other threads, the handle is returned back in `Err`.
+/// The value of a poisoned lock is moved as well.
+pub fn arc_to_rc<T>(handle: Arc<RwLock<T>>) -> Result<Rc<RefCell<T>>, Arc<RwLock<T>>> {
+    map_into(handle, |value| value)
+}
+
+impl<G> From<TryLockError<G>> for LockError {
+    fn from(err: TryLockError<G>) -> Self {
+        match err {
+            TryLockError::WouldBlock => LockError::WouldBlock,
+            TryLockError::Poisoned(_) => LockError::Poisoned,
+        }
+    }
+}
+
+// Implementation for RwLock<T>, shared by Arc<RwLock<T>> or any other UniPtr
+impl<T> UniCell for RwLock<T> {
+    type Value = T;
+    type Read<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type Write<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where T: 'a;
+
+    fn new(value: T) -> Self {
+        RwLock::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        RwLock::read(self).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        RwLock::write(self).expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        Ok(RwLock::try_read(self)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        Ok(RwLock::try_write(self)?)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::Read<'a>> {
+        RwLock::read(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::Write<'a>> {
+        RwLock::write(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn into_inner(self) -> T {
+        RwLock::into_inner(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        RwLock::get_mut(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        RwLock::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        RwLock::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniCell::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniCell::write(self), f) }
+    }
+}
+
+/// `Arc<RwLock<T>>` as a thread-safe [UniRcCopy] handle, for the `Copy` values
+/// which don't fit into an atomic. Panics if the lock is poisoned.
+impl<T: Copy> UniRcCopy<T> for Arc<RwLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(RwLock::new(value))
+    }
+
+    fn get(&self) -> T {
+        *UniRcLock::read(self)
+    }
+
+    fn set(&self, value: T) {
+        *UniRcLock::write(self) = value;
+    }
+}
+
+/// Implementation for `Mutex<T>`, shared by `Arc<Mutex<T>>` or any other [UniPtr](crate::UniPtr).
+///
+/// The mutex has no shared readers: `read()` locks it exclusively
+/// exactly like `write()`, so two read guards can't coexist.
+impl<T> UniCell for Mutex<T> {
+    type Value = T;
+    type Read<'a> = MutexGuard<'a, T> where T: 'a;
+    type Write<'a> = MutexGuard<'a, T> where T: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<MutexGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = MappedWriteGuard<MutexGuard<'a, T>, U> where T: 'a;
+    const ALLOWS_CONCURRENT_READS: bool = false;
+
+    fn new(value: T) -> Self {
+        Mutex::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        self.lock().expect("Mutex should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        self.lock().expect("Mutex should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        Ok(self.try_lock()?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        Ok(self.try_lock()?)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::Read<'a>> {
+        self.lock().map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::Write<'a>> {
+        self.lock().map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn into_inner(self) -> T {
+        Mutex::into_inner(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        Mutex::get_mut(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        Mutex::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        Mutex::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniCell::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniCell::write(self), f) }
+    }
+}
+
+/// Any pointer to std `Mutex`, like `Arc<Mutex<T>>`, is an exclusive lock.
+impl<T, P> MutexBackend<T> for P where P: UniPtr<Cell = Mutex<T>> {}
+
+/// Implementation for `&RwLock<T>`, for example a `static` lock
+/// or a local one lent to the generic code.
+///
+/// Such handles can't outlive the borrow, so they can't be stored
+/// anywhere beyond it unless the reference is `'static`.
+/// A reference is not reference-counted: `strong_count()` is always 1,
+/// `try_unwrap()` always fails and `get_mut()` always returns `None`.
+/// There are no weak references, so `downgrade()` returns a [NoWeak].
+/// It can't be created from a value without leaking it, so it doesn't
+/// implement [UniNew]: borrow an existing `RwLock` instead.
+///
+/// Called on a concrete `&RwLock<T>`, the inherent methods of `RwLock`
+/// take precedence, so use `UniRcLock::read(&lock)` outside of generic code.
+impl<T> UniRcLock<T> for &RwLock<T> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where Self: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<RwLockReadGuard<'a, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RwLock::read(self).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RwLock::write(self).expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(RwLock::try_read(self)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(RwLock::try_write(self)?)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        RwLock::read(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        RwLock::write(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn strong_count(&self) -> usize {
+        1
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn is_poisoned(&self) -> bool {
+        RwLock::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        RwLock::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniRcLock::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniRcLock::write(self), f) }
+    }
+}
+
+/// Implementation for `&Arc<RwLock<T>>`, a borrowed handle lent
+/// to the generic code without cloning the `Arc`.
+///
+/// Like for `&Rc<RefCell<T>>`, the counts are the ones of the borrowed `Arc`,
+/// `try_unwrap()` always fails, `get_mut()` always returns `None`,
+/// `downgrade()` returns a [NoWeak] and it doesn't implement [UniNew].
+impl<T> UniRcLock<T> for &Arc<RwLock<T>> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where Self: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<RwLockReadGuard<'a, T>, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RwLock::read(self).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RwLock::write(self).expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(RwLock::try_read(self)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(RwLock::try_write(self)?)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        RwLock::read(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        RwLock::write(self).map_err(|err| UniLockError::Poisoned(err.into_inner()))
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn is_poisoned(&self) -> bool {
+        RwLock::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        RwLock::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(UniRcLock::read(self), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(UniRcLock::write(self), f) }
+    }
+}
+
+/// A wrapper of `Arc<RwLock<T>>` or `Arc<Mutex<T>>` which never panics
+/// on poisoning.
+///
+/// The plain handles panic in `read()` and `write()` if another thread
+/// panicked while holding a write guard, since the value may be left
+/// in an inconsistent state. The wrapped handles recover the guard
+/// from `PoisonError` instead, so the services which prefer to keep
+/// running after a panic see the value as it was left. Unlike
+/// [clear_poison](UniRcLock::clear_poison), this doesn't race with
+/// the new panics. `is_poisoned()` still reports the poisoning.
+///
+/// The weak handles are wrapped the same way: `Resilient<Weak<RwLock<T>>>`.
+#[derive(Clone, Debug, Default)]
+pub struct Resilient<H>(H);
+
+impl<H> Resilient<H> {
+    /// Wrap the handle
+    pub fn new(handle: H) -> Self {
+        Self(handle)
+    }
+
+    /// The wrapped handle, which panics on poisoning again
+    pub fn into_inner(self) -> H {
+        self.0
+    }
+
+    /// Obtain a reference to the wrapped handle
+    pub fn get_ref(&self) -> &H {
+        &self.0
+    }
+}
+
+// Implementations for Resilient<Arc<RwLock<T>>> and Resilient<Arc<Mutex<T>>>,
+// which differ only in the locking functions.
+macro_rules! impl_resilient {
+    ($lock:ident, $read_guard:ident, $write_guard:ident, $read:ident, $write:ident,
+     $try_read:ident, $try_write:ident) => {
+        impl<T> UniRcLock<T> for Resilient<Arc<$lock<T>>> {
+            type OutRead<'a> = $read_guard<'a, T> where T: 'a;
+            type OutWrite<'a> = $write_guard<'a, T> where T: 'a;
+            type Weak = Resilient<Weak<$lock<T>>>;
+            type MappedRead<'a, U: ?Sized + 'a> =
+                MappedReadGuard<$read_guard<'a, T>, U> where T: 'a;
+            type MappedWrite<'a, U: ?Sized + 'a> =
+                MappedWriteGuard<$write_guard<'a, T>, U> where T: 'a;
+            const ALLOWS_CONCURRENT_READS: bool = <$lock<T> as UniCell>::ALLOWS_CONCURRENT_READS;
+
+            fn read<'a>(&'a self) -> Self::OutRead<'a> {
+                $lock::$read(&self.0).unwrap_or_else(PoisonError::into_inner)
+            }
+
+            fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+                $lock::$write(&self.0).unwrap_or_else(PoisonError::into_inner)
+            }
+
+            fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+                match $lock::$try_read(&self.0) {
+                    Ok(guard) => Ok(guard),
+                    Err(TryLockError::Poisoned(err)) => Ok(err.into_inner()),
+                    Err(TryLockError::WouldBlock) => Err(LockError::WouldBlock),
+                }
+            }
+
+            fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+                match $lock::$try_write(&self.0) {
+                    Ok(guard) => Ok(guard),
+                    Err(TryLockError::Poisoned(err)) => Ok(err.into_inner()),
+                    Err(TryLockError::WouldBlock) => Err(LockError::WouldBlock),
+                }
+            }
+
+            fn strong_count(&self) -> usize {
+                Arc::strong_count(&self.0)
+            }
+
+            fn weak_count(&self) -> usize {
+                Arc::weak_count(&self.0)
+            }
+
+            fn ptr_eq(&self, other: &Self) -> bool {
+                Arc::ptr_eq(&self.0, &other.0)
+            }
+
+            fn downgrade(&self) -> Self::Weak {
+                Resilient(Arc::downgrade(&self.0))
+            }
+
+            fn try_unwrap(self) -> Result<T, Self> {
+                Arc::try_unwrap(self.0).map(UniCell::into_inner).map_err(Self)
+            }
+
+            fn get_mut(&mut self) -> Option<&mut T> {
+                Arc::get_mut(&mut self.0).map(UniCell::get_mut)
+            }
+
+            fn is_poisoned(&self) -> bool {
+                $lock::is_poisoned(&self.0)
+            }
+
+            fn clear_poison(&self) {
+                $lock::clear_poison(&self.0)
+            }
+
+            fn map_read<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&T) -> &U,
+            ) -> Self::MappedRead<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedReadGuard::new(self.read(), f) }
+            }
+
+            fn map_write<'a, U: ?Sized + 'a>(
+                &'a self,
+                f: impl FnOnce(&mut T) -> &mut U,
+            ) -> Self::MappedWrite<'a, U> {
+                // SAFETY: the value lives in the lock, not in the guard
+                unsafe { MappedWriteGuard::new(self.write(), f) }
+            }
+        }
+
+        impl<T> UniNew<T> for Resilient<Arc<$lock<T>>> {
+            fn new(value: T) -> Self {
+                Self(Arc::new($lock::new(value)))
+            }
+        }
+
+        impl<T> UniWeak<T> for Resilient<Weak<$lock<T>>> {
+            type Strong = Resilient<Arc<$lock<T>>>;
+
+            fn upgrade(&self) -> Option<Self::Strong> {
+                self.0.upgrade().map(Resilient)
+            }
+        }
+    };
+}
+
+impl_resilient!(RwLock, RwLockReadGuard, RwLockWriteGuard, read, write, try_read, try_write);
+impl_resilient!(Mutex, MutexGuard, MutexGuard, lock, lock, try_lock, try_lock);
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, thread};
+
+    use super::IntoArc;
+    use crate::{UniNew, UniRcLock};
+
+    #[test]
+    fn rc_into_arc() {
+        let config = Rc::new(RefCell::new(vec![1, 2]));
+        let other = config.clone();
+        config.write().push(3);
+        // Not unique yet
+        let config = config.into_arc().unwrap_err();
+        drop(other);
+        let config = config.into_arc().unwrap();
+        let sum = thread::spawn(move || config.read().iter().sum::<i32>());
+        assert_eq!(sum.join().unwrap(), 6);
+    }
+
+    #[test]
+    fn rc_arc_round_trip() {
+        use std::sync::{Arc, RwLock};
+
+        use super::{arc_to_rc, rc_to_arc};
+
+        let rc = Rc::new(RefCell::new(vec![1, 2]));
+        let weak = rc.downgrade();
+        let arc = rc_to_arc(rc).unwrap();
+        assert!(weak.upgrade().is_none());
+        arc.write().push(3);
+        let rc = arc_to_rc(arc).unwrap();
+        assert_eq!(*rc.read(), [1, 2, 3]);
+
+        // Shared handles are returned back
+        let other = rc.clone();
+        let rc = rc_to_arc(rc).unwrap_err();
+        assert!(rc.ptr_eq(&other));
+        drop(other);
+        let arc = rc_to_arc(rc).unwrap();
+        let other = arc.clone();
+        let t = thread::spawn(move || other.read().len());
+        let arc = arc_to_rc(arc).unwrap_err();
+        assert_eq!(t.join().unwrap(), 3);
+        assert_eq!(*arc_to_rc(arc).unwrap().read(), [1, 2, 3]);
+
+        // The value of a poisoned lock is still moved
+        let arc = Arc::new(RwLock::new(0));
+        let other = arc.clone();
+        thread::spawn(move || {
+            let _w = other.write();
+            panic!("poison");
+        })
+        .join()
+        .unwrap_err();
+        assert!(arc.is_poisoned());
+        assert_eq!(*arc_to_rc(arc).unwrap().read(), 0);
+    }
+
+    #[test]
+    fn resilient() {
+        use std::sync::{Arc, Mutex, RwLock};
+
+        use super::Resilient;
+        use crate::UniWeak;
+
+        let h = <Resilient<Arc<RwLock<i32>>> as UniNew<i32>>::new(1);
+        let other = h.clone();
+        thread::spawn(move || {
+            let _w = other.write();
+            panic!("poison");
+        })
+        .join()
+        .unwrap_err();
+        assert!(h.is_poisoned());
+        *h.write() += 1;
+        assert_eq!(*h.read(), 2);
+        assert_eq!(*h.try_read().ok().unwrap(), 2);
+        assert_eq!(*h.map_read(|v| v), 2);
+        let weak = h.downgrade();
+        assert_eq!(*weak.upgrade().unwrap().read(), 2);
+        // The plain handle still reports the poisoning
+        assert!(RwLock::read(h.get_ref()).is_err());
+
+        let h = Resilient::new(Arc::new(Mutex::new(0)));
+        let other = h.clone();
+        thread::spawn(move || {
+            let _w = other.write();
+            panic!("poison");
+        })
+        .join()
+        .unwrap_err();
+        assert!(h.try_write().is_ok());
+        *h.write() = 5;
+        assert_eq!(h.try_unwrap().ok().unwrap(), 5);
+    }
+
+    #[test]
+    fn borrowed_handles() {
+        use crate::UniWeak;
+        use std::sync::{Arc, RwLock};
+
+        fn incr(h: impl UniRcLock<i32>) -> usize {
+            *h.write() += 1;
+            h.strong_count()
+        }
+
+        let rc = Rc::new(RefCell::new(0));
+        let arc = Arc::new(RwLock::new(0));
+        // No clones are made
+        assert_eq!(incr(&rc), 1);
+        assert_eq!(incr(&arc), 1);
+        let arc2 = arc.clone();
+        assert_eq!(incr(&arc2), 2);
+        assert_eq!(*rc.read(), 1);
+        assert_eq!(*arc.read(), 2);
+
+        let r = &rc;
+        let _g = UniRcLock::read(&r);
+        assert!(UniRcLock::try_write(&r).is_err());
+        assert!(UniRcLock::ptr_eq(&r, &&rc));
+        // Downgrading doesn't panic, but the weak handle never upgrades
+        assert!(UniRcLock::downgrade(&r).upgrade().is_none());
+        assert!(UniRcLock::downgrade(&&arc).upgrade().is_none());
+        assert!(UniRcLock::try_unwrap(r).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+// Implementation of the handle to the per-thread values. Available with the `std` feature.
+// Each thread keeps a map from the ids of the handles to their values
+// in Rc<RefCell<T>>, so the handle itself holds no value and is Send + Sync.
+// The guards hold a clone of the Rc, so the value outlives them even if
+// the slot is removed from the map meanwhile.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use std::{
+    any::Any,
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::{
+        self,
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+thread_local! {
+    static SLOTS: RefCell<HashMap<usize, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+// The ids are never reused, so a stale slot is never found by a new handle
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// The logical slot shared by the clones of the handle
+struct Shared<T> {
+    id: usize,
+    init: Box<dyn Fn() -> T + Send + Sync>,
+}
+
+impl<T: 'static> Shared<T> {
+    // The value of the current thread, initialized on the first access
+    fn cell(&self) -> Rc<RefCell<T>> {
+        let found = SLOTS.with(|slots| slots.borrow().get(&self.id).cloned());
+        match found {
+            Some(cell) => cell.downcast().expect("Thread-local slot should hold the value type"),
+            None => {
+                // init() is called outside of the borrow, since it may access other handles
+                let cell = Rc::new(RefCell::new((self.init)()));
+                SLOTS.with(|slots| slots.borrow_mut().insert(self.id, cell.clone()));
+                cell
+            }
+        }
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    // Only the slot of the current thread can be freed here, the slots
+    // of the other threads are freed when those threads exit
+    fn drop(&mut self) {
+        // The value is dropped outside of the borrow, since it may hold other handles
+        let _value = SLOTS
+            .try_with(|slots| slots.borrow_mut().remove(&self.id))
+            .ok()
+            .flatten();
+    }
+}
+
+/// Handle to a value, which is separate for each thread.
+///
+/// Intended for the scratch buffers and caches, which are passed around
+/// as shared handles, but never contended. `read()` and `write()` access
+/// the value of the current thread, created by the initializer on the first
+/// access from the thread, so each thread transparently gets its own `T`.
+/// The clones of the handle refer to the same values.
+///
+/// **The writes are not visible to other threads**: each thread only sees
+/// its own value. The handle is `Send` and `Sync` for any `T`, since
+/// the values never leave their threads, while the guards are neither.
+/// Within a thread, the values are borrowed like `RefCell`, so `write()`
+/// panics if the value of the thread is already borrowed.
+///
+/// `new()` sets the value of the current thread, and the other threads start
+/// with `T::default()`; [with_init](Self::with_init) takes an initializer
+/// instead. The values of the threads are dropped when they exit, or when the
+/// last handle is dropped for the thread which dropped it. `try_unwrap()`
+/// returns the value of the current thread, and `get_mut()` always returns `None`.
+pub struct ThreadLocalHandle<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: 'static> ThreadLocalHandle<T> {
+    /// Create a handle, whose value is created by `init` in each thread
+    pub fn with_init(init: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                init: Box::new(init),
+            }),
+        }
+    }
+}
+
+impl<T> Clone for ThreadLocalHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+/// Weak counterpart of [ThreadLocalHandle]
+pub struct ThreadLocalWeak<T> {
+    shared: sync::Weak<Shared<T>>,
+}
+
+impl<T> Clone for ThreadLocalWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: sync::Weak::clone(&self.shared),
+        }
+    }
+}
+
+/// Read guard of [ThreadLocalHandle]
+pub struct ThreadLocalReadGuard<'a, T> {
+    // Declared first to be dropped before the cell it borrows
+    borrow: Ref<'a, T>,
+    _cell: Rc<RefCell<T>>,
+}
+
+impl<T> Deref for ThreadLocalReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+/// Write guard of [ThreadLocalHandle]
+pub struct ThreadLocalWriteGuard<'a, T> {
+    // Declared first to be dropped before the cell it borrows
+    borrow: RefMut<'a, T>,
+    _cell: Rc<RefCell<T>>,
+}
+
+impl<T> Deref for ThreadLocalWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.borrow
+    }
+}
+
+impl<T> DerefMut for ThreadLocalWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.borrow
+    }
+}
+
+impl<T: 'static> ThreadLocalHandle<T> {
+    // The cell of the current thread, borrowable for the lifetime of the handle
+    fn cell(&self) -> (Rc<RefCell<T>>, &RefCell<T>) {
+        let cell = self.shared.cell();
+        // SAFETY: the guard holds the Rc, so the cell is alive until the borrow
+        // is dropped, and the guard is not Send, so it can't leave the thread
+        let borrowed = unsafe { &*Rc::as_ptr(&cell) };
+        (cell, borrowed)
+    }
+}
+
+// Implementation for ThreadLocalHandle<T>
+impl<T: Default + 'static> UniRcLock<T> for ThreadLocalHandle<T> {
+    type OutRead<'a> = ThreadLocalReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = ThreadLocalWriteGuard<'a, T> where T: 'a;
+    type Weak = ThreadLocalWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> =
+        MappedReadGuard<ThreadLocalReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<ThreadLocalWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let (cell, borrowed) = self.cell();
+        ThreadLocalReadGuard {
+            borrow: borrowed.borrow(),
+            _cell: cell,
+        }
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let (cell, borrowed) = self.cell();
+        ThreadLocalWriteGuard {
+            borrow: borrowed.borrow_mut(),
+            _cell: cell,
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        let (cell, borrowed) = self.cell();
+        Ok(ThreadLocalReadGuard {
+            borrow: borrowed.try_borrow().map_err(|_| LockError::WouldBlock)?,
+            _cell: cell,
+        })
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        let (cell, borrowed) = self.cell();
+        Ok(ThreadLocalWriteGuard {
+            borrow: borrowed.try_borrow_mut().map_err(|_| LockError::WouldBlock)?,
+            _cell: cell,
+        })
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.shared)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.shared)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        ThreadLocalWeak {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        if Arc::strong_count(&self.shared) != 1 {
+            return Err(self);
+        }
+        let id = self.shared.id;
+        let slot = SLOTS.with(|slots| slots.borrow_mut().remove(&id));
+        match slot {
+            // No guards are alive, since they borrow the only handle
+            Some(cell) => match cell.downcast::<RefCell<T>>() {
+                Ok(cell) => Ok(Rc::try_unwrap(cell).ok().unwrap().into_inner()),
+                Err(_) => unreachable!("Thread-local slot should hold the value type"),
+            },
+            None => Ok((self.shared.init)()),
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the cell, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the cell, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Default + 'static> UniNew<T> for ThreadLocalHandle<T> {
+    fn new(value: T) -> Self {
+        let handle = Self::with_init(T::default);
+        let cell = Rc::new(RefCell::new(value));
+        SLOTS.with(|slots| slots.borrow_mut().insert(handle.shared.id, cell));
+        handle
+    }
+}
+
+impl<T: Default + 'static> UniWeak<T> for ThreadLocalWeak<T> {
+    type Strong = ThreadLocalHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.shared.upgrade().map(|shared| ThreadLocalHandle { shared })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ThreadLocalHandle;
+    use crate::{UniNew, UniRcLock, UniWeak};
+    use std::{cell::Cell, rc::Rc, thread};
+
+    #[test]
+    fn thread_local() {
+        let h = ThreadLocalHandle::new(vec![1]);
+        h.write().push(2);
+        let other = h.clone();
+        assert_eq!(*other.read(), [1, 2]);
+
+        // Each thread gets its own value, starting from the default
+        let handles: Vec<_> = (0..2)
+            .map(|i| {
+                let h = h.clone();
+                thread::spawn(move || {
+                    assert!(h.read().is_empty());
+                    h.write().push(i);
+                    h.write().push(i);
+                    h.read().clone()
+                })
+            })
+            .collect();
+        let values: Vec<_> = handles.into_iter().map(|t| t.join().unwrap()).collect();
+        assert_eq!(values, [[0, 0], [1, 1]]);
+        assert_eq!(*h.read(), [1, 2]);
+
+        let _r = h.read();
+        assert!(other.try_write().is_err());
+    }
+
+    #[test]
+    fn thread_local_init() {
+        let h = ThreadLocalHandle::with_init(|| String::from("init"));
+        let weak = h.downgrade();
+        let t = {
+            let h = h.clone();
+            thread::spawn(move || h.read().clone())
+        };
+        h.map_write(|s| s.as_mut_str()).make_ascii_uppercase();
+        assert_eq!(t.join().unwrap(), "init");
+        assert_eq!(*weak.upgrade().unwrap().read(), "INIT");
+        assert_eq!(h.try_unwrap().ok().unwrap(), "INIT");
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn thread_local_drop() {
+        struct Counted(Rc<Cell<i32>>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let h = ThreadLocalHandle::with_init(|| 0);
+        let inner = ThreadLocalHandle::new(Some(Counted(drops.clone())));
+        // The value of the slot may hold other handles
+        let outer = ThreadLocalHandle::new(Some(inner));
+        *h.write() += 1;
+        drop(outer);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(*h.read(), 1);
+    }
+}
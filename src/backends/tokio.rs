@@ -0,0 +1,643 @@
+// Implementations for the tokio async locks. Available with the `tokio` feature.
+
+use crate::{AsyncOwnedLock, AsyncUniRcLock, SpawnableAsyncLock, UniNew};
+use std::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    sync::{mpsc, Arc},
+};
+use tokio::sync::{
+    oneshot, Mutex, MutexGuard, OwnedMutexGuard, OwnedRwLockReadGuard, OwnedRwLockWriteGuard,
+    RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+
+/// Implementation for `Arc<tokio::sync::RwLock<T>>`.
+///
+/// tokio locks are never poisoned, thus `read()` and `write()` never panic.
+impl<T> AsyncUniRcLock<T> for Arc<RwLock<T>> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+
+    fn new(value: T) -> Self {
+        Arc::new(RwLock::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).read().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).write().await
+    }
+}
+
+impl<T> AsyncOwnedLock<T> for Arc<RwLock<T>> {
+    type OwnedRead = OwnedRwLockReadGuard<T>;
+    type OwnedWrite = OwnedRwLockWriteGuard<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        RwLock::read_owned(self).await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        RwLock::write_owned(self).await
+    }
+}
+
+impl<T: Send + Sync + 'static> SpawnableAsyncLock<T> for Arc<RwLock<T>> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+/// Implementation for `Arc<tokio::sync::Mutex<T>>`.
+///
+/// Like for std `Mutex`, `read()` is exclusive: both `read()` and `write()`
+/// wait for the mutex and return its guard.
+impl<T> AsyncUniRcLock<T> for Arc<Mutex<T>> {
+    type OutRead<'a> = MutexGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = MutexGuard<'a, T> where T: 'a;
+
+    fn new(value: T) -> Self {
+        Arc::new(Mutex::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        Arc::deref(self).lock().await
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        Arc::deref(self).lock().await
+    }
+}
+
+impl<T> AsyncOwnedLock<T> for Arc<Mutex<T>> {
+    type OwnedRead = OwnedMutexGuard<T>;
+    type OwnedWrite = OwnedMutexGuard<T>;
+
+    async fn read_owned(self) -> Self::OwnedRead {
+        Mutex::lock_owned(self).await
+    }
+
+    async fn write_owned(self) -> Self::OwnedWrite {
+        Mutex::lock_owned(self).await
+    }
+}
+
+impl<T: Send + 'static> SpawnableAsyncLock<T> for Arc<Mutex<T>> {
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+/// Async access to a synchronous [UniRcLock](crate::UniRcLock) handle,
+/// like `Arc<std::sync::RwLock<T>>` shared with the synchronous code,
+/// without blocking the async executor.
+///
+/// Each `read()` or `write()` runs a `spawn_blocking()` task, which acquires
+/// the lock and then keeps holding the guard until the returned async guard
+/// is dropped. The std guards have to be released by the thread which acquired
+/// them, so dropping the async guard only signals that blocking task to release
+/// the lock. Thus every guard occupies a thread of the blocking pool while
+/// it is alive and costs a couple of thread hops, which is fine for the state
+/// accessed now and then, but not for the hot paths.
+/// Must be used from within a tokio runtime.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use std::sync::{Arc, RwLock};
+/// use uni_rc_lock::{AsyncAdapter, AsyncUniRcLock, UniRcLock};
+///
+/// let state = Arc::new(RwLock::new(0));
+/// let adapter = AsyncAdapter::new(state.clone());
+/// *adapter.write().await += 1;
+/// // The synchronous code uses the same handle
+/// assert_eq!(*UniRcLock::read(&state), 1);
+/// # }
+/// ```
+pub struct AsyncAdapter<H> {
+    handle: H,
+}
+
+impl<H> AsyncAdapter<H> {
+    /// Wrap the synchronous handle
+    pub fn new(handle: H) -> Self {
+        Self { handle }
+    }
+
+    /// The wrapped synchronous handle
+    pub fn handle(&self) -> &H {
+        &self.handle
+    }
+
+    /// Unwrap the synchronous handle
+    pub fn into_inner(self) -> H {
+        self.handle
+    }
+}
+
+impl<H: Clone> Clone for AsyncAdapter<H> {
+    fn clone(&self) -> Self {
+        Self::new(self.handle.clone())
+    }
+}
+
+// The pointer to the locked value, sent from the blocking task
+struct ValuePtr<T>(NonNull<T>);
+
+// SAFETY: only sent to the async guards, which require T to be Send + Sync
+unsafe impl<T: Send + Sync> Send for ValuePtr<T> {}
+
+// Runs in the blocking task: passes the pointer to the locked value
+// to the async side and holds the guard until the async guard is dropped.
+fn hold<G, T>(
+    guard: G,
+    ptr: NonNull<T>,
+    acquired: oneshot::Sender<ValuePtr<T>>,
+    release: mpsc::Receiver<()>,
+) {
+    // If the async side gave up waiting, the lock is released immediately
+    if acquired.send(ValuePtr(ptr)).is_ok() {
+        // Returns an error once the async guard drops the sender
+        let _ = release.recv();
+    }
+    drop(guard);
+}
+
+/// Read guard returned by [AsyncAdapter]. The lock is held by a blocking
+/// task and is released when this guard is dropped.
+pub struct AdapterReadGuard<T> {
+    ptr: NonNull<T>,
+    _release: mpsc::Sender<()>,
+}
+
+// SAFETY: the guard only lends &T, like a reference
+unsafe impl<T: Sync> Send for AdapterReadGuard<T> {}
+unsafe impl<T: Sync> Sync for AdapterReadGuard<T> {}
+
+impl<T> Deref for AdapterReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: the value is kept locked by the blocking task until the guard is dropped
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AdapterReadGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Write guard returned by [AsyncAdapter]. The lock is held by a blocking
+/// task and is released when this guard is dropped.
+pub struct AdapterWriteGuard<T> {
+    ptr: NonNull<T>,
+    _release: mpsc::Sender<()>,
+    // Invariant in T like &mut T
+    _value: PhantomData<fn(T) -> T>,
+}
+
+// SAFETY: the guard lends &mut T, like a mutable reference
+unsafe impl<T: Send> Send for AdapterWriteGuard<T> {}
+unsafe impl<T: Sync> Sync for AdapterWriteGuard<T> {}
+
+impl<T> Deref for AdapterWriteGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: the value is kept locked by the blocking task until the guard is dropped
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for AdapterWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the value is kept locked for writing by the blocking task
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AdapterWriteGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Implementation for any synchronous handle wrapped into [AsyncAdapter].
+/// The guards don't borrow the adapter.
+impl<T, H> AsyncUniRcLock<T> for AsyncAdapter<H>
+where
+    H: UniNew<T> + Send + Sync + 'static,
+    T: Send + Sync + 'static,
+{
+    type OutRead<'a> = AdapterReadGuard<T> where Self: 'a;
+    type OutWrite<'a> = AdapterWriteGuard<T> where Self: 'a;
+
+    fn new(value: T) -> Self {
+        Self::new(H::new(value))
+    }
+
+    async fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        let (acquired_tx, acquired_rx) = oneshot::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let handle = self.handle.clone();
+        tokio::task::spawn_blocking(move || {
+            let guard = handle.read();
+            let ptr = NonNull::from(&*guard);
+            hold(guard, ptr, acquired_tx, release_rx);
+        });
+        let ptr = acquired_rx.await.expect("The blocking task should not panic");
+        AdapterReadGuard {
+            ptr: ptr.0,
+            _release: release_tx,
+        }
+    }
+
+    async fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        let (acquired_tx, acquired_rx) = oneshot::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let handle = self.handle.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut guard = handle.write();
+            let ptr = NonNull::from(&mut *guard);
+            hold(guard, ptr, acquired_tx, release_rx);
+        });
+        let ptr = acquired_rx.await.expect("The blocking task should not panic");
+        AdapterWriteGuard {
+            ptr: ptr.0,
+            _release: release_tx,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T, H> SpawnableAsyncLock<T> for AsyncAdapter<H>
+where
+    H: UniNew<T> + Send + Sync + 'static,
+    T: Send + Sync + 'static,
+{
+    type SendRead<'a> = Self::OutRead<'a>;
+    type SendWrite<'a> = Self::OutWrite<'a>;
+
+    fn read_send<'a>(&'a self) -> impl Future<Output = Self::SendRead<'a>> + Send {
+        AsyncUniRcLock::read(self)
+    }
+
+    fn write_send<'a>(&'a self) -> impl Future<Output = Self::SendWrite<'a>> + Send {
+        AsyncUniRcLock::write(self)
+    }
+}
+
+// Blocking access to the tokio RwLock for the synchronous code.
+// A separate feature, since with both traits in scope
+// the calls like ptr.read() become ambiguous.
+#[cfg(feature = "tokio-blocking")]
+mod blocking {
+    use crate::{LockError, UniCell};
+    use tokio::sync::{RwLock, RwLockMappedWriteGuard, RwLockReadGuard, RwLockWriteGuard};
+
+    /// Synchronous implementation for `tokio::sync::RwLock<T>`, which makes
+    /// `Arc<tokio::sync::RwLock<T>>` a [UniRcLock](crate::UniRcLock) handle
+    /// for the synchronous code, like rayon workers or `Drop` impls.
+    ///
+    /// `read()` and `write()` use `blocking_read()` and `blocking_write()`,
+    /// which panic if called from an async context, such as a runtime worker thread.
+    /// They are fine in `spawn_blocking()` tasks and in plain threads.
+    /// `try_read()` and `try_write()` never block and work everywhere.
+    impl<T> UniCell for RwLock<T> {
+        type Value = T;
+        type Read<'a> = RwLockReadGuard<'a, T> where T: 'a;
+        type Write<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+        type MappedRead<'a, U: ?Sized + 'a> = RwLockReadGuard<'a, U> where T: 'a;
+        type MappedWrite<'a, U: ?Sized + 'a> = RwLockMappedWriteGuard<'a, U> where T: 'a;
+
+        fn new(value: T) -> Self {
+            RwLock::new(value)
+        }
+
+        fn read<'a>(&'a self) -> Self::Read<'a> {
+            self.blocking_read()
+        }
+
+        fn write<'a>(&'a self) -> Self::Write<'a> {
+            self.blocking_write()
+        }
+
+        fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+            RwLock::try_read(self).map_err(|_| LockError::WouldBlock)
+        }
+
+        fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+            RwLock::try_write(self).map_err(|_| LockError::WouldBlock)
+        }
+
+        fn into_inner(self) -> T {
+            RwLock::into_inner(self)
+        }
+
+        fn get_mut(&mut self) -> &mut T {
+            RwLock::get_mut(self)
+        }
+
+        fn map_read<'a, U: ?Sized + 'a>(
+            &'a self,
+            f: impl FnOnce(&T) -> &U,
+        ) -> Self::MappedRead<'a, U> {
+            RwLockReadGuard::map(UniCell::read(self), f)
+        }
+
+        fn map_write<'a, U: ?Sized + 'a>(
+            &'a self,
+            f: impl FnOnce(&mut T) -> &mut U,
+        ) -> Self::MappedWrite<'a, U> {
+            RwLockWriteGuard::map(UniCell::write(self), f)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use tokio::sync::{Mutex, RwLock};
+
+    use crate::{AsyncOwnedLock, AsyncUniRcLock};
+
+    #[derive(Debug)]
+    struct Foo(i32);
+
+    async fn incr_foo(v: impl AsyncUniRcLock<Foo>) {
+        v.write().await.0 += 1;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn tokio_rwlock() {
+        let ptr: Arc<RwLock<Foo>> = AsyncUniRcLock::new(Foo(0));
+        let tasks: Vec<_> = (0..10)
+            .map(|_| tokio::spawn(incr_foo(ptr.clone())))
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        assert_eq!(ptr.read().await.0, 10);
+    }
+
+    #[tokio::test]
+    async fn tokio_rwlock_readers() {
+        let ptr = Arc::new(RwLock::new(Foo(1)));
+        let r1 = AsyncUniRcLock::read(&ptr).await;
+        let r2 = AsyncUniRcLock::read(&ptr).await;
+        assert_eq!(r1.0 + r2.0, 2);
+        assert!(ptr.try_write().is_err());
+    }
+
+    // A shared resource, like a database connection
+    #[derive(Debug, Default)]
+    struct Conn {
+        queries: Vec<String>,
+    }
+
+    async fn query(conn: impl AsyncUniRcLock<Conn>, q: String) -> usize {
+        let mut c = conn.write().await;
+        c.queries.push(q);
+        c.queries.len()
+    }
+
+    #[tokio::test]
+    async fn tokio_mutex_and_rwlock() {
+        async fn run<H: AsyncUniRcLock<Conn>>() -> H {
+            let conn = H::new(Conn::default());
+            for i in 0..10 {
+                assert_eq!(query(conn.clone(), format!("q{i}")).await, i + 1);
+            }
+            conn
+        }
+
+        let conn = run::<Arc<Mutex<Conn>>>().await;
+        assert_eq!(conn.read().await.queries.len(), 10);
+        {
+            let _r = AsyncUniRcLock::read(&conn).await;
+            // Reading locks the mutex exclusively
+            assert!(conn.try_lock().is_err());
+        }
+        let conn = run::<Arc<RwLock<Conn>>>().await;
+        assert_eq!(conn.read().await.queries[9], "q9");
+    }
+
+    // A snapshot which holds the value locked for reading, without a lifetime
+    struct Snapshot<H: AsyncOwnedLock<Foo>> {
+        foo: H::OwnedRead,
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn tokio_owned() {
+        let ptr: Arc<RwLock<Foo>> = AsyncUniRcLock::new(Foo(1));
+        let snap = Snapshot::<Arc<RwLock<Foo>>> {
+            foo: ptr.clone().read_owned().await,
+        };
+        tokio::task::yield_now().await;
+        let r = tokio::spawn(async move { snap.foo.0 }).await.unwrap();
+        assert_eq!(r, 1);
+
+        let mut w = ptr.clone().write_owned().await;
+        assert!(ptr.try_read().is_err());
+        tokio::spawn(async move { w.0 += 1 }).await.unwrap();
+        assert_eq!(ptr.read().await.0, 2);
+
+        let m: Arc<Mutex<Foo>> = AsyncUniRcLock::new(Foo(0));
+        let g = m.clone().read_owned().await;
+        assert!(m.try_lock().is_err());
+        drop(g);
+        m.clone().write_owned().await.0 = 5;
+        assert_eq!(m.lock().await.0, 5);
+    }
+
+    #[cfg(feature = "timeout")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn tokio_timeout() {
+        use crate::Elapsed;
+        use std::time::{Duration, Instant};
+
+        let ptr: Arc<RwLock<Foo>> = AsyncUniRcLock::new(Foo(1));
+        let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        let writer = tokio::spawn({
+            let ptr = ptr.clone();
+            async move {
+                let _w = AsyncUniRcLock::write(&ptr).await;
+                locked_tx.send(()).unwrap();
+                done_rx.await.unwrap();
+            }
+        });
+        locked_rx.await.unwrap();
+
+        let reader = tokio::spawn({
+            let ptr = ptr.clone();
+            async move {
+                let start = Instant::now();
+                let res = ptr.read_timeout(Duration::from_millis(50)).await.map(|r| r.0);
+                (res, start.elapsed())
+            }
+        });
+        let (res, elapsed) = reader.await.unwrap();
+        assert_eq!(res, Err(Elapsed));
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(5));
+        // The zero timeout doesn't wait at all
+        assert!(ptr.write_timeout(Duration::ZERO).await.is_err());
+
+        done_tx.send(()).unwrap();
+        writer.await.unwrap();
+        assert_eq!(ptr.read_timeout(Duration::ZERO).await.unwrap().0, 1);
+        ptr.write_timeout(Duration::from_millis(50)).await.unwrap().0 += 1;
+        assert_eq!(ptr.read().await.0, 2);
+    }
+
+    #[cfg(feature = "tokio-blocking")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn tokio_blocking() {
+        use crate::UniRcLock;
+
+        // Legacy synchronous code
+        fn incr_sync(v: &impl UniRcLock<Foo>) {
+            v.write().0 += 1;
+        }
+
+        let ptr: Arc<RwLock<Foo>> = AsyncUniRcLock::new(Foo(0));
+        let h = ptr.clone();
+        tokio::task::spawn_blocking(move || incr_sync(&h))
+            .await
+            .unwrap();
+        let h = ptr.clone();
+        let r = std::thread::spawn(move || {
+            incr_sync(&h);
+            *h.map_read(|f| &f.0)
+        });
+        assert_eq!(r.join().unwrap(), 2);
+
+        // Non-blocking access works in the async context as well
+        let w = AsyncUniRcLock::write(&ptr).await;
+        assert!(!UniRcLock::can_read(&ptr));
+        drop(w);
+        assert_eq!(UniRcLock::try_read(&ptr).unwrap().0, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn async_adapter() {
+        use crate::{AsyncAdapter, UniNew, UniRcLock};
+        use std::sync::RwLock as StdRwLock;
+        use std::thread;
+
+        let state: Arc<StdRwLock<Foo>> = UniNew::new(Foo(0));
+        let adapter = AsyncAdapter::new(state.clone());
+
+        // Synchronous writers in plain threads
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let h = state.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        UniRcLock::write(&h).0 += 1;
+                    }
+                })
+            })
+            .collect();
+        // Async readers and writers on the same handle
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let a = adapter.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        let r = a.read().await;
+                        assert!(r.0 >= 0);
+                        drop(r);
+                        a.write().await.0 += 1;
+                    }
+                })
+            })
+            .collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        for t in tasks {
+            t.await.unwrap();
+        }
+        assert_eq!(adapter.read().await.0, 440);
+
+        // Waiting for the lock held by the sync code doesn't block the executor
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let writer = thread::spawn({
+            let h = state.clone();
+            move || {
+                let _w = UniRcLock::write(&h);
+                locked_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+            }
+        });
+        locked_rx.recv().unwrap();
+        let reader = tokio::spawn({
+            let a = adapter.clone();
+            async move { a.read().await.0 }
+        });
+        tokio::task::yield_now().await;
+        assert!(!reader.is_finished());
+        release_tx.send(()).unwrap();
+        assert_eq!(reader.await.unwrap(), 440);
+        writer.join().unwrap();
+
+        // The async guard releases the lock for the sync code
+        let g = adapter.write().await;
+        assert!(!state.can_read());
+        drop(g);
+        let h = state.clone();
+        thread::spawn(move || UniRcLock::read(&h).0).join().unwrap();
+    }
+
+    // Fails to compile with the plain AsyncUniRcLock bound
+    fn spawn_generic<H: crate::SpawnableAsyncLock<Foo>>(h: &H) -> tokio::task::JoinHandle<i32> {
+        let h = h.clone();
+        tokio::spawn(async move {
+            let mut g = h.write_send().await;
+            tokio::task::yield_now().await;
+            g.0 += 1;
+            drop(g);
+            let g = h.read_send().await;
+            g.0
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn spawnable() {
+        async fn run<H: crate::SpawnableAsyncLock<Foo>>() -> i32 {
+            let h = H::new(Foo(0));
+            let tasks: Vec<_> = (0..10).map(|_| spawn_generic(&h)).collect();
+            for t in tasks {
+                t.await.unwrap();
+            }
+            let g = h.read_send().await;
+            g.0
+        }
+
+        assert_eq!(run::<Arc<RwLock<Foo>>>().await, 10);
+        assert_eq!(run::<Arc<Mutex<Foo>>>().await, 10);
+        assert_eq!(run::<crate::AsyncAdapter<Arc<std::sync::RwLock<Foo>>>>().await, 10);
+    }
+}
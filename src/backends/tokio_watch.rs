@@ -0,0 +1,282 @@
+// Implementation for the tokio::sync::watch channel.
+// The channel is a single cell with many readers: reading borrows the current
+// value of the receiver, while writing clones the current value into a staging
+// copy, which is sent with send_modify() when the write guard is dropped.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, UniNew, UniRcLock, UniWeak};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{self, Arc},
+};
+use tokio::sync::watch::{self, Receiver, Sender};
+
+/// Handle to a value in a `tokio::sync::watch` channel.
+///
+/// Intended for sharing the state with the async code which already waits
+/// for its changes with `watch::Receiver::changed()`: the writes made
+/// through the handle are sent to all the receivers of the channel.
+/// The handle holds the sender in an `Arc` together with its own receiver,
+/// so the clones of the handle share the sender, and [subscribe](Self::subscribe)
+/// returns new receivers for the async code.
+///
+/// `read()` borrows the current value of the channel. It blocks the writers
+/// of the channel while the guard is alive, so it shouldn't be held for long.
+/// `write()` returns a guard holding a clone of the current value, which is sent
+/// to the receivers when the guard is dropped. Like for the `arc-swap` backend,
+/// concurrent writes are not serialized: if two guards are alive at the same
+/// time, the value sent last wins and the changes made through the other guard
+/// are lost. The changes are not visible to the readers until the guard is dropped.
+pub struct WatchHandle<T> {
+    sender: Arc<Sender<T>>,
+    receiver: Receiver<T>,
+}
+
+impl<T> WatchHandle<T> {
+    /// Create a new receiver of the channel, which sees the current value
+    /// as already seen
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.sender.subscribe()
+    }
+
+    /// The sender of the channel, shared by all the clones of the handle
+    pub fn sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+}
+
+impl<T> Clone for WatchHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: Arc::clone(&self.sender),
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+/// Wrap an existing channel, for example the one created by `watch::channel()`,
+/// whose other receivers are already used by the async code.
+impl<T> From<(Sender<T>, Receiver<T>)> for WatchHandle<T> {
+    fn from((sender, receiver): (Sender<T>, Receiver<T>)) -> Self {
+        Self {
+            sender: Arc::new(sender),
+            receiver,
+        }
+    }
+}
+
+/// Weak counterpart of [WatchHandle]
+pub struct WatchWeak<T> {
+    sender: sync::Weak<Sender<T>>,
+}
+
+impl<T> Clone for WatchWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: sync::Weak::clone(&self.sender),
+        }
+    }
+}
+
+/// Write guard of [WatchHandle]. Holds a staging copy of the value,
+/// which is sent to the receivers when the guard is dropped.
+pub struct WatchWriteGuard<'a, T> {
+    sender: &'a Sender<T>,
+    // Boxed, so that the value doesn't move together with the guard
+    value: Option<Box<T>>,
+}
+
+impl<T> Deref for WatchWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for WatchWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for WatchWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // The staging copy may be half-modified if the write panicked,
+        // so it is not sent to the receivers
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(value) = self.value.take() {
+            self.sender.send_modify(|current| *current = *value);
+        }
+    }
+}
+
+// Implementation for WatchHandle<T>
+//
+// get_mut() always returns None since the channel gives no mutable access
+// to the stored value, and try_unwrap() clones the value out of it.
+impl<T: Clone> UniRcLock<T> for WatchHandle<T> {
+    type OutRead<'a> = watch::Ref<'a, T> where T: 'a;
+    type OutWrite<'a> = WatchWriteGuard<'a, T> where T: 'a;
+    type Weak = WatchWeak<T>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<watch::Ref<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<WatchWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.receiver.borrow()
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        WatchWriteGuard {
+            sender: &self.sender,
+            value: Some(Box::new(self.receiver.borrow().clone())),
+        }
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(self.read())
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(self.write())
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.sender)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(&self.sender)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.sender, &other.sender)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        WatchWeak {
+            sender: Arc::downgrade(&self.sender),
+        }
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        match Arc::try_unwrap(self.sender) {
+            Ok(sender) => Ok(sender.borrow().clone()),
+            Err(sender) => Err(Self {
+                sender,
+                receiver: self.receiver,
+            }),
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the channel, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the staging value is boxed, not stored in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T: Clone> UniNew<T> for WatchHandle<T> {
+    fn new(value: T) -> Self {
+        watch::channel(value).into()
+    }
+}
+
+impl<T: Clone> UniWeak<T> for WatchWeak<T> {
+    type Strong = WatchHandle<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        self.sender.upgrade().map(|sender| WatchHandle {
+            receiver: sender.subscribe(),
+            sender,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::watch;
+
+    use super::WatchHandle;
+    use crate::{UniNew, UniRcLock, UniWeak};
+
+    // The generic code, which knows nothing about the channel
+    fn bump<H: UniRcLock<usize>>(h: &H) {
+        *h.write() += 1;
+    }
+
+    #[test]
+    fn watch() {
+        let h = WatchHandle::new(vec![0]);
+        {
+            let mut w = h.write();
+            w.push(1);
+            // Not sent yet
+            assert_eq!(*h.read(), [0]);
+        }
+        assert_eq!(*h.read(), [0, 1]);
+        assert_eq!(*h.map_read(|v| &v[1]), 1);
+        *h.map_write(|v| &mut v[0]) = 2;
+        assert_eq!(*h.clone().read(), [2, 1]);
+
+        // A panicked write is not sent
+        let rx = h.subscribe();
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut w = h.write();
+            w.push(3);
+            panic!("interrupted write");
+        }));
+        assert!(res.is_err());
+        assert!(!rx.has_changed().unwrap());
+        assert_eq!(*h.read(), [2, 1]);
+
+        let w = h.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&h));
+        let h2 = h.clone();
+        let h = h.try_unwrap().err().unwrap();
+        drop(h2);
+        assert_eq!(h.try_unwrap().ok().unwrap(), [2, 1]);
+        assert!(w.upgrade().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn watch_receivers() {
+        let (tx, mut rx) = watch::channel(0);
+        let h = WatchHandle::from((tx, rx.clone()));
+        let mut rx2 = h.subscribe();
+        let waiter = tokio::spawn(async move {
+            *rx2.wait_for(|&v| v == 10).await.unwrap()
+        });
+        let writer = std::thread::spawn({
+            let h = h.clone();
+            move || {
+                for _ in 0..10 {
+                    bump(&h);
+                }
+            }
+        });
+        assert_eq!(waiter.await.unwrap(), 10);
+        writer.join().unwrap();
+        // The receiver given out before wrapping the channel sees the writes too
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), 10);
+        bump(&h);
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow(), 11);
+    }
+}
@@ -0,0 +1,210 @@
+// Implementations for triomphe::Arc, which is std::sync::Arc without weak references.
+// The locks and their guards are the same as for std::sync::Arc.
+
+use crate::{LockError, MappedReadGuard, MappedWriteGuard, NoWeak, UniNew, UniRcLock};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use triomphe::Arc;
+
+/// Implementation for `triomphe::Arc<RwLock<T>>`.
+///
+/// `triomphe::Arc` has no weak references, so `downgrade()` returns a [NoWeak].
+impl<T> UniRcLock<T> for Arc<RwLock<T>> {
+    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<RwLockReadGuard<'a, T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<RwLockWriteGuard<'a, T>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RwLock::read(self).expect("Read lock should not be poisoned")
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RwLock::write(self).expect("Write lock should not be poisoned")
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(RwLock::try_read(self)?)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(RwLock::try_write(self)?)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self)
+            .map(|lock| lock.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self)
+            .map(|lock| lock.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn is_poisoned(&self) -> bool {
+        RwLock::is_poisoned(self)
+    }
+
+    fn clear_poison(&self) {
+        RwLock::clear_poison(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedReadGuard::new(self.read(), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        // SAFETY: the value lives in the lock, not in the guard
+        unsafe { MappedWriteGuard::new(self.write(), f) }
+    }
+}
+
+impl<T> UniNew<T> for Arc<RwLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(RwLock::new(value))
+    }
+}
+
+/// Implementation for `triomphe::Arc<parking_lot::RwLock<T>>`.
+///
+/// `triomphe::Arc` has no weak references, so `downgrade()` returns a [NoWeak].
+#[cfg(feature = "parking_lot")]
+impl<T> UniRcLock<T> for Arc<parking_lot::RwLock<T>> {
+    type OutRead<'a> = parking_lot::RwLockReadGuard<'a, T> where T: 'a;
+    type OutWrite<'a> = parking_lot::RwLockWriteGuard<'a, T> where T: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = parking_lot::MappedRwLockReadGuard<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = parking_lot::MappedRwLockWriteGuard<'a, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        parking_lot::RwLock::read(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        parking_lot::RwLock::write(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        parking_lot::RwLock::try_read(self).ok_or(LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        parking_lot::RwLock::try_write(self).ok_or(LockError::WouldBlock)
+    }
+
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Arc::try_unwrap(self).map(|lock| lock.into_inner())
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Arc::get_mut(self).map(|lock| lock.get_mut())
+    }
+
+    fn as_ptr(&self) -> *const T {
+        parking_lot::RwLock::data_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        parking_lot::RwLockReadGuard::map(self.read(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        parking_lot::RwLockWriteGuard::map(self.write(), f)
+    }
+}
+
+impl<T> UniNew<T> for Arc<parking_lot::RwLock<T>> {
+    fn new(value: T) -> Self {
+        Arc::new(parking_lot::RwLock::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::RwLock;
+
+    use crate::UniRcLock;
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+
+    fn incr(p: impl UniRcLock<State>) -> i32 {
+        p.write().val += 1;
+        p.read().val
+    }
+
+    #[test]
+    fn triomphe_vs_std() {
+        let st1 = std::sync::Arc::new(RwLock::new(State { val: 42 }));
+        let st2 = triomphe::Arc::new(RwLock::new(State { val: 42 }));
+        assert_eq!(incr(st1.clone()), incr(st2.clone()));
+        assert_eq!(st2.strong_count(), 1);
+        assert!(st2.ptr_eq(&st2.clone()));
+        assert_eq!(st2.try_unwrap().ok().unwrap().val, 43);
+    }
+
+    #[test]
+    #[cfg(feature = "parking_lot")]
+    fn triomphe_parking_lot() {
+        let st1 = std::sync::Arc::new(parking_lot::RwLock::new(State { val: 42 }));
+        let st2 = triomphe::Arc::new(parking_lot::RwLock::new(State { val: 42 }));
+        assert_eq!(incr(st1), incr(st2.clone()));
+        assert_eq!(*st2.map_read(|st| &st.val), 43);
+    }
+
+    #[test]
+    fn triomphe_downgrade() {
+        use crate::UniWeak;
+        let st = triomphe::Arc::new(RwLock::new(State { val: 42 }));
+        assert_eq!(st.weak_count(), 0);
+        assert!(st.downgrade().upgrade().is_none());
+    }
+}
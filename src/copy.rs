@@ -0,0 +1,69 @@
+// A guard-free trait for small Copy values, separate from UniRcLock.
+
+use alloc::rc::Rc;
+use core::cell::Cell;
+
+/// A common trait for the handles of small `Copy` values, like counters
+/// or flags, which are always read and written as a whole.
+///
+/// Unlike [UniRcLock](crate::UniRcLock), there are no guards: the value
+/// is copied out by [get](UniRcCopy::get) and replaced by [set](UniRcCopy::set).
+/// This fits `Rc<Cell<T>>`, which is cheaper than `Rc<RefCell<T>>`
+/// since it never tracks borrows. Its thread-safe counterparts are
+/// `Arc<RwLock<T>>` and `Arc<crossbeam_utils::atomic::AtomicCell<T>>`
+/// with the `crossbeam` feature.
+///
+/// Each call is atomic, but a `get()` followed by a `set()` is not:
+/// a change made by another thread in between is overwritten.
+pub trait UniRcCopy<T: Copy>: Clone {
+    /// Create a new handle owning the value
+    fn new(value: T) -> Self;
+    /// Obtain a copy of the value
+    fn get(&self) -> T;
+    /// Replace the value
+    fn set(&self, value: T);
+}
+
+impl<T: Copy> UniRcCopy<T> for Rc<Cell<T>> {
+    fn new(value: T) -> Self {
+        Rc::new(Cell::new(value))
+    }
+
+    fn get(&self) -> T {
+        Cell::get(self)
+    }
+
+    fn set(&self, value: T) {
+        Cell::set(self, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::UniRcCopy;
+
+    fn count<H: UniRcCopy<u32>>(h: &H, n: u32) {
+        for _ in 0..n {
+            h.set(h.get() + 1);
+        }
+    }
+
+    #[test]
+    fn copy_rc_cell() {
+        let c: Rc<Cell<u32>> = UniRcCopy::new(0);
+        count(&c.clone(), 5);
+        assert_eq!(UniRcCopy::get(&c), 5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn copy_arc_rwlock() {
+        use std::sync::{Arc, RwLock};
+
+        let c: Arc<RwLock<u32>> = UniRcCopy::new(0);
+        count(&c, 5);
+        assert_eq!(*c.read().unwrap(), 5);
+    }
+}
@@ -0,0 +1,164 @@
+// A map keyed by the identity of the handles, for attaching data to shared values.
+
+use alloc::collections::{btree_map, BTreeMap};
+
+use crate::UniRcLock;
+
+/// A map from the handles to the values, which compares the handles
+/// by identity, like [ptr_eq](UniRcLock::ptr_eq), rather than by their values.
+/// Intended for attaching metadata to the shared nodes of a graph
+/// without adding fields to the nodes themselves.
+///
+/// The map keeps a clone of the handle with each entry, so the value
+/// of the handle can't be dropped and its address reused by another
+/// value while the entry exists. The handles are keyed by
+/// [as_ptr](UniRcLock::as_ptr), so the handles whose value moves
+/// on writes, like the snapshot backends, can't be used as the keys,
+/// and the handles which lock in `as_ptr()`, like `Arc<RwLock<T>>`,
+/// must not be locked for writing by the same thread during the lookup.
+pub struct HandleMap<H, V> {
+    entries: BTreeMap<usize, (H, V)>,
+}
+
+impl<H, V> HandleMap<H, V> {
+    /// Create an empty map
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Number of the entries in the map
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the handles and their values in an unspecified order
+    pub fn iter(&self) -> impl Iterator<Item = (&H, &V)> {
+        self.entries.values().map(|(h, v)| (h, v))
+    }
+
+    /// Insert the value for the handle. If the map already had a value
+    /// for the same handle or its clone, it is replaced and returned,
+    /// and the stored handle is kept.
+    pub fn insert<T>(&mut self, handle: &H, value: V) -> Option<V>
+    where
+        H: UniRcLock<T>,
+    {
+        match self.entries.entry(key(handle)) {
+            btree_map::Entry::Occupied(mut e) => {
+                Some(core::mem::replace(&mut e.get_mut().1, value))
+            }
+            btree_map::Entry::Vacant(e) => {
+                e.insert((handle.clone(), value));
+                None
+            }
+        }
+    }
+
+    /// Obtain the value for the handle or any of its clones
+    pub fn get<T>(&self, handle: &H) -> Option<&V>
+    where
+        H: UniRcLock<T>,
+    {
+        self.entries.get(&key(handle)).map(|(_, v)| v)
+    }
+
+    /// Obtain the mutable value for the handle or any of its clones
+    pub fn get_mut<T>(&mut self, handle: &H) -> Option<&mut V>
+    where
+        H: UniRcLock<T>,
+    {
+        self.entries.get_mut(&key(handle)).map(|(_, v)| v)
+    }
+
+    /// Returns `true` if the map has a value for the handle or any of its clones
+    pub fn contains<T>(&self, handle: &H) -> bool
+    where
+        H: UniRcLock<T>,
+    {
+        self.entries.contains_key(&key(handle))
+    }
+
+    /// Remove the entry of the handle, returning its value.
+    /// The clone of the handle stored in the entry is dropped.
+    pub fn remove<T>(&mut self, handle: &H) -> Option<V>
+    where
+        H: UniRcLock<T>,
+    {
+        self.entries.remove(&key(handle)).map(|(_, v)| v)
+    }
+}
+
+impl<H, V> Default for HandleMap<H, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The address of the value identifies the handle and all its clones
+fn key<T, H: UniRcLock<T>>(handle: &H) -> usize {
+    handle.as_ptr() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::HandleMap;
+    use crate::UniNew;
+
+    fn check<H: UniNew<String>>() {
+        let a = H::new("a".into());
+        let b = H::new("b".into());
+        let c = H::new("c".into());
+        let edges = [(&a, &b), (&a, &c), (&b, &c)];
+
+        // Count the incoming edges of each node
+        let mut incoming = HandleMap::new();
+        for node in [&a, &b, &c] {
+            incoming.insert(node, 0);
+        }
+        for (_, to) in edges {
+            *incoming.get_mut(to).unwrap() += 1;
+        }
+        assert_eq!(incoming.len(), 3);
+        assert_eq!(incoming.get(&a), Some(&0));
+        assert_eq!(incoming.get(&b.clone()), Some(&1));
+        assert_eq!(incoming.get(&c), Some(&2));
+
+        // Nodes with equal contents are still different keys
+        let d = H::new("a".into());
+        assert!(!incoming.contains(&d));
+        assert_eq!(incoming.insert(&a, 5), Some(0));
+        assert_eq!(incoming.iter().map(|(_, v)| v).sum::<i32>(), 8);
+
+        // The map keeps the node alive
+        drop(c);
+        let c = incoming.iter().find(|(_, v)| **v == 2).unwrap().0.clone();
+        assert_eq!(*c.read(), "c");
+        assert_eq!(c.strong_count(), 2);
+        assert_eq!(incoming.remove(&c), Some(2));
+        assert_eq!(c.strong_count(), 1);
+        assert_eq!(incoming.remove(&c), None);
+        assert_eq!(incoming.len(), 2);
+    }
+
+    #[test]
+    fn handle_map_rc() {
+        check::<Rc<RefCell<String>>>();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn handle_map_arc() {
+        use std::sync::{Arc, RwLock};
+
+        check::<Arc<RwLock<String>>>();
+    }
+}
@@ -21,13 +21,336 @@ smart pointer (Rc or Arc) will be passed.
 for `Rc<RefCell<T>>` and `Arc<RwLock<T>>` so that they could be used in
 a generic way in single-threaded and multi-threaded scenarious alike.
 
+`Arc<Mutex<T>>` is supported as well. Since the mutex has no notion of
+shared readers, both `read()` and `write()` lock it exclusively.
+
+Plain references `&RefCell<T>` and `&RwLock<T>` are supported as well.
+They are handy for global state in `static` variables, which doesn't need
+reference counting, or for lending a local value to the generic code
+without allocating an `Rc`. They have no weak references and can't be unwrapped.
+Borrowed handles `&Rc<RefCell<T>>` and `&Arc<RwLock<T>>` could be passed
+to the functions taking `impl UniRcLock<T>` without cloning the pointer.
+The borrowed handles can't be created from a value, so they don't implement [UniNew],
+and their `downgrade()` returns a [NoWeak], which never upgrades.
+Small `Copy` values could be stored in `Rc<Cell<T>>`, or in
+`Arc<crossbeam_utils::atomic::AtomicCell<T>>` with the `crossbeam` feature.
+These handles can't lend references to the value, so their guards hold
+a copy of it: [ValueGuard] for reading and [ValueWriteGuard], which stores
+the modified copy back when dropped, for writing. Nothing is locked,
+so the changes made meanwhile by other writers are overwritten.
+When no guards are needed at all, the [UniRcCopy] trait with plain
+`get()` and `set()` is a lighter alternative for such values.
+It is implemented for `Rc<Cell<T>>`, `Arc<RwLock<T>>` and `Arc<AtomicCell<T>>`.
+
+A value built single-threaded in `Rc<RefCell<T>>` could be moved
+to `Arc<RwLock<T>>` with [IntoArc::into_arc] to be shared with other threads.
+Handles wrapped in [Resilient] ignore the lock poisoning instead of panicking.
+
+[ThreadLocalHandle] gives each thread its own value, created on the first
+access from the thread, so the handle is shared like `Arc<RwLock<T>>`
+but never contended. The writes are not visible to the other threads.
+
+Lazily initialized globals are declared with the [uni_static!] macro
+as `LazyLock<RwLock<T>>`, or as `OnceLock<RwLock<T>>` initialized
+at runtime by [get_or_init]; references to both are handles as well.
+
+In fact, `UniRcLock` is implemented for any reference-counted pointer
+implementing [UniPtr], like `Rc` and `Arc`, which points to a cell or a lock
+implementing [UniCell], like `RefCell`, `RwLock` or `Mutex`. Thus `Rc<RwLock<T>>`
+and `Arc<RefCell<T>>` are handles as well, and a custom smart pointer
+becomes a handle by implementing the unsafe trait [UniPtr].
+
+The aliases [SingleThreaded] and [MultiThreaded] name the two canonical
+handles, `Rc<RefCell<T>>` and `Arc<RwLock<T>>`.
+
+A library could be written against the [Shared] alias, leaving the choice
+to the final binary: `Shared<T>` is `Arc<RwLock<T>>` with the `threadsafe` feature
+and `Rc<RefCell<T>>` otherwise. The `single-thread` feature selects `Rc<RefCell<T>>`
+explicitly. Enabling both features is a compile error.
+```
+# // The loom and shuttle handles only work inside their test runners
+# #[cfg(not(all(feature = "threadsafe", any(loom, feature = "loom", shuttle, feature = "shuttle"))))] {
+# use uni_rc_lock::{Shared, UniNew, UniRcLock};
+struct Node {
+    children: Vec<Shared<Node>>,
+}
+
+let root: Shared<Node> = UniNew::new(Node { children: vec![] });
+root.write().children.push(UniNew::new(Node { children: vec![] }));
+assert_eq!(root.read().children.len(), 1);
+# }
+```
+
 ## Performance
 `UniRcLock` is a zero-cost abstraction.
 
+## Optional backends
+Additional backends are enabled by cargo features:
+
+| Feature | Types |
+|---------|-------|
+| `std` (default) | `Arc<std::sync::RwLock<T>>`, `Arc<std::sync::Mutex<T>>`, [ThreadLocalHandle] |
+| `parking_lot` | `Arc<parking_lot::RwLock<T>>`, `Arc<parking_lot::Mutex<T>>`, `Arc<parking_lot::ReentrantMutex<RefCell<T>>>` |
+| `lock_api` | `Arc` or `Rc` of `lock_api::RwLock<R, T>`, `lock_api::Mutex<R, T>` for any raw lock `R` |
+| `spin` | [SpinHandle] and [SpinMutexHandle], which are `Arc<spin::RwLock<T>>` and `Arc<spin::Mutex<T>>` |
+| `crossbeam` | `Arc<crossbeam_utils::sync::ShardedLock<T>>`, `Arc<crossbeam_utils::atomic::AtomicCell<T>>`, [PaddedHandle] |
+| `crossbeam-epoch` | [RcuHandle] with read-copy-update on `crossbeam_epoch::Atomic<T>` |
+| `atomic_refcell` | `Arc<atomic_refcell::AtomicRefCell<T>>` |
+| `qcell` | [QHandle] wrapping `Rc<qcell::QCell<T>>` |
+| `ghost-cell` | [GhostHandle] wrapping `Rc<ghost_cell::GhostCell<'brand, T>>` |
+| `triomphe` | `triomphe::Arc<RwLock<T>>`, `triomphe::Arc<parking_lot::RwLock<T>>` with `parking_lot` |
+| `hybrid-rc` | `hybrid_rc::Rc<RefCell<T>>`, `hybrid_rc::Rc<RwLock<T>>`, `hybrid_rc::Arc<RwLock<T>>` |
+| `arc-swap` | [SwapHandle] wrapping `Arc<arc_swap::ArcSwap<T>>` |
+| `dashmap` | [MapEntryHandle] to an entry of `Arc<dashmap::DashMap<K, V>>` |
+| `left-right` | [LeftRightHandle] wrapping a `left_right` pair of copies |
+| `seqlock` | [SeqLockHandle], which is `Arc<seqlock::SeqLock<T>>` for `T: Copy` |
+| `slotmap` | [ArenaHandle] and [SyncArenaHandle] to a slot of a shared `slotmap::SlotMap` |
+| `send_wrapper` | [LocalSendHandle], which is `SendWrapper<Rc<RefCell<T>>>` |
+| `shm` | [ShmHandle] to a `bytemuck::Pod` value in a file mapped by several processes |
+| `either` | `either::Either<L, R>` of two handles, for choosing the backend at runtime |
+| `persistent` | [PersistentHandle], which is `Arc<RwLock<T>>` saved to a JSON or TOML file on writes |
+| `critical-section` | `&critical_section::Mutex<RefCell<T>>` |
+| `loom` | `loom::sync::Arc<loom::sync::RwLock<T>>` as [MultiThreaded], for model checking only |
+| `shuttle` | `Arc<shuttle::sync::RwLock<T>>` as [MultiThreaded], for randomized scheduling tests only |
+| `tokio` | `Arc<tokio::sync::RwLock<T>>`, `Arc<tokio::sync::Mutex<T>>` implementing [AsyncUniRcLock] |
+| `tokio-blocking` | `Arc<tokio::sync::RwLock<T>>` implementing `UniRcLock` with `blocking_read()`/`blocking_write()` |
+| `tokio-watch` | [WatchHandle] wrapping a `tokio::sync::watch` channel |
+| `embassy-sync` | `&embassy_sync::mutex::Mutex<M, T>`, `Arc<embassy_sync::mutex::Mutex<M, T>>` implementing [AsyncUniRcLock] |
+| `async-lock` | `Arc<async_lock::RwLock<T>>`, `Arc<async_lock::Mutex<T>>` implementing [AsyncUniRcLock] |
+| `futures-locks` | `futures_locks::RwLock<T>`, `futures_locks::Mutex<T>` implementing [AsyncUniRcLock] |
+
+The `parking_lot` locks are never poisoned, thus `read()` and `write()`
+never panic for them on poisoning.
+
+The `lock_api` feature covers the locks built on `lock_api` with any raw lock,
+including the `parking_lot` locks and custom ones. They are never poisoned either.
+It doesn't require `std`. The locks are [UniCell]s, so they could be shared
+by `Rc` or a custom [UniPtr] as well, and give owned guards with [OwnedLock].
+The locks with upgradable raw locks, like `Arc<parking_lot::RwLock<T>>`,
+implement [UpgradableRead] for checking the value and then writing it
+without releasing the lock. The timed locks, including the `parking_lot` ones,
+implement [TimedLock] for giving up waiting after a timeout.
+
+`Arc<parking_lot::ReentrantMutex<RefCell<T>>>` allows nested reads
+from the same thread, which is handy in callback-heavy code. Nested writes
+panic instead of deadlocking.
+
+The `spin` locks busy-wait until the lock is available instead of putting
+the thread to sleep. They are intended for bare-metal targets without `std`
+and only make sense when the critical sections are very short.
+
+`ShardedLock` from `crossbeam` is tuned for read-heavy workloads:
+concurrent reads are faster than with `RwLock`, while writes are
+considerably more expensive. It panics on poisoning like `RwLock`.
+
+[PaddedHandle] is `Arc<CachePadded<RwLock<T>>>` from `crossbeam`, which keeps
+each lock on its own cache line. This avoids false sharing when many handles
+are written side by side, like the per-thread shards made by [padded_shards].
+
+`AtomicRefCell` is a thread-safe analog of `RefCell`: it never blocks,
+and conflicting borrows panic exactly like they do for `Rc<RefCell<T>>`.
+This is useful for data which is shared between threads but
+is never accessed concurrently in practice. It doesn't require `std`.
+
+[QHandle] stores the value in `Rc<qcell::QCell<T>>` and accesses it through
+a `QCellOwner` shared by many handles. The guards borrow the owner itself,
+so any write guard excludes all other guards of the handles sharing the owner.
+[GhostHandle] does the same with the branded `GhostToken`, which confines
+the handles to the closure passed to `GhostToken::new`.
+
+`triomphe::Arc` has no weak references. Its `Weak` type is [NoWeak],
+which is returned by `downgrade()` and never upgrades.
+
+`hybrid_rc::Rc<RwLock<T>>` uses cheap non-atomic reference counting
+but could be converted to `hybrid_rc::Arc<RwLock<T>>` with `HybridRc::to_shared()`
+to be sent to other threads, while the code built on `UniRcLock` stays the same.
+
+[LocalSendHandle] is for the single-threaded platforms, like `wasm32-unknown-unknown`
+without atomics, where some dependencies still require `Send + Sync` handles.
+It is a plain `Rc<RefCell<T>>` wrapped into `send_wrapper::SendWrapper`,
+which panics if the handle is used or dropped in any thread other than
+the one which created it.
+
+[SwapHandle] is tuned for the data which is read very often and replaced rarely.
+Reads never block and return snapshots, while writes publish a modified copy
+of the value when the guard is dropped. Concurrent writes are not serialized,
+so the changes of all but the last published write guard are lost.
+
+[RcuHandle] implements read-copy-update on `crossbeam-epoch`: reads are wait-free
+and return snapshots, while writes publish a modified copy of the value when
+the guard is dropped, like for [SwapHandle]. Unlike it, the writers are serialized,
+so no write is lost. The replaced values are destroyed once no reader could see them.
+
+[WatchHandle] wraps a `tokio::sync::watch` channel, so the state written through
+the generic interface is observed by the async code waiting on the receivers
+of the channel. Writes send a modified copy of the value when the guard
+is dropped, like for [SwapHandle], and are not serialized either.
+
+[MapEntryHandle] refers to a single entry of a shared `DashMap`, so many
+independent states kept in one map, like the sessions keyed by their ids,
+are passed to the generic code as separate handles. Its guards lock only
+the shard of the map holding the key.
+
+[LeftRightHandle] keeps two copies of the value, so that reads are wait-free
+and never contend with the writer. Writes are serialized and publish
+a modified copy when the guard is dropped. The readers see the new value
+only after that, which makes the reads eventually consistent.
+
+[SeqLockHandle] is intended for small `Copy` values which are updated often
+and read by many threads. Readers never block the writer: they copy the value
+out and retry if it was changed meanwhile, so `read()` returns a [ValueGuard]
+with a consistent copy of the value instead of a reference to it.
+
+[ArenaHandle] refers to a value in a slot of an arena shared by many values,
+`Rc<RefCell<slotmap::SlotMap<DefaultKey, T>>>`, like the entities of an entity
+system, and [SyncArenaHandle] does the same for `Arc<RwLock<..>>`. Their guards
+borrow the whole arena, so a write guard to one value conflicts with the guards
+to all the other values of the same arena.
+
+[ShmHandle] shares a plain-old-data value, which implements `bytemuck::Pod`,
+between processes in a memory-mapped file. The processes open the file by its
+path, and the guards hold the advisory lock of the file, so they exclude
+the guards of the other processes as well as of the same process.
+
+[PersistentHandle] keeps a serializable value in `Arc<RwLock<T>>` and saves it
+to a JSON or TOML file each time a write guard is dropped, replacing the file
+atomically. [PersistentHandle::load_or] restores the value saved before.
+
+`&critical_section::Mutex<RefCell<T>>` is intended for the `static` state
+shared with interrupt handlers on embedded targets. Its guards keep
+the critical section entered while they are alive. It doesn't require `std`.
+
+## Async support
+The `async` feature provides [AsyncUniRcLock], an async counterpart of
+`UniRcLock` with `read()` and `write()` returning futures of the guards.
+It is implemented for `Rc<RefCell<T>>`, which resolves immediately,
+and for `Arc<tokio::sync::RwLock<T>>` and `Arc<tokio::sync::Mutex<T>>`
+with the `tokio` feature. The `async-lock` feature implements it for the
+runtime-agnostic `async_lock` locks used with smol and async-std,
+so the same generic async code runs on any executor.
+The `futures-locks` locks are reference counted themselves, so with the
+`futures-locks` feature `futures_locks::RwLock<T>` is a handle on its own,
+without an outer `Arc`.
+The `embassy-sync` feature implements it for the `embassy_sync` mutex
+on embedded targets without `std`, either in a `static` or in `Arc`.
+Beware that a guard held across an `.await` keeps the lock held
+while the task is suspended.
+
+The generic tasks spawned on a multi-threaded executor should require
+the [SpawnableAsyncLock] subtrait, whose futures and guards are `Send`.
+The [AsyncOwnedLock] subtrait provides owned guards, which don't borrow
+the handle and could be moved into a spawned task or stored in a struct.
+
+The synchronous handles shared with the sync code could be awaited
+in the async tasks by wrapping them into [AsyncAdapter] with the `tokio` feature.
+
+With the `notify` feature, any handle could be wrapped into [Notified]
+to await the changes of the value, to wait until it satisfies a predicate
+or to watch it as a stream.
+
+With the `actor` feature, [IntoActor::into_actor] turns any handle into
+an actor: all writes are sent through the cloneable [Address] to a single task,
+which applies them one by one, so the writers never contend for the lock.
+
+With the `timeout` feature, `read_timeout()` and `write_timeout()` give up
+waiting for the lock after the given time, for example to answer
+"service unavailable" instead of stalling the request.
+```
+# #[cfg(feature = "tokio")]
+# async fn f() {
+# use std::sync::Arc;
+# use uni_rc_lock::AsyncUniRcLock;
+#
+async fn incr(v: impl AsyncUniRcLock<i32>) {
+    *v.write().await += 1;
+}
+
+let ptr = Arc::new(tokio::sync::RwLock::new(0));
+incr(ptr.clone()).await;
+assert_eq!(*ptr.read().await, 1);
+# }
+```
+
+## Serialization
+With the `serde` feature, the handles could be serialized as the values
+they hold using `#[serde(with = "uni_rc_lock::serde")]`.
+See the [serde](crate::serde) module.
+
+## `no_std` support
+The crate is `no_std` and only requires `alloc` when the default `std` feature
+is disabled. `Rc<RefCell<T>>` is always available, while the thread-safe
+backends from `std::sync` are not. The `spin` feature provides thread-safe
+backends which don't need `std`:
+```toml
+uni_rc_lock = { version = "0.2", default-features = false, features = ["spin"] }
+```
+In this configuration [MultiThreaded] is `Arc<spin::RwLock<T>>`, so the code
+using it works with and without `std`.
+On the targets without atomic compare-and-swap, like `thumbv6m` or `riscv32imc`,
+`alloc::sync::Arc` is not available. The `portable-atomic` feature adds the handles
+in `portable_atomic_util::Arc`, switches [SpinHandle] and [SpinMutexHandle] to them
+and the spin locks to the `portable_atomic` atomics, which have to be provided by enabling
+the `critical-section` feature of `portable-atomic` or by the
+`portable_atomic_unsafe_assume_single_core` cfg on single-core targets:
+```toml
+uni_rc_lock = { version = "0.2", default-features = false, features = ["portable-atomic"] }
+portable-atomic = { version = "1", features = ["critical-section"] }
+```
+
+## Model checking with loom
+With the `loom` feature [MultiThreaded] becomes `loom::sync::Arc<loom::sync::RwLock<T>>`,
+so the tests of the code generic over `UniRcLock` which use [MultiThreaded]
+could run under `loom::model()` without changing the call sites.
+This is for testing only: the loom types panic outside of `loom::model()`
+and are much slower, so never enable the feature in a real build.
+Enable it in the dev-dependencies or under a separate test run instead.
+Building with `RUSTFLAGS="--cfg loom"` has the same effect as the feature,
+following the usual loom convention, so the downstream loom tests don't need
+to enable it. The crate's own model is run by `RUSTFLAGS="--cfg loom" cargo test --test loom`.
+loom's `Arc` has no weak references, so `downgrade()` returns a [NoWeak].
+
+## Randomized scheduling with shuttle
+For the state spaces too large for loom, the `shuttle` feature or
+`RUSTFLAGS="--cfg shuttle"` makes [MultiThreaded] `Arc<shuttle::sync::RwLock<T>>`
+in the same way. Its `Arc` is the std one, so weak references work.
+The handles only work inside the shuttle test runners, like `shuttle::check_random()`.
+If both loom and shuttle are enabled, loom takes precedence for [MultiThreaded].
+
 ## Limitations
-An ability to recover from lock poisoning in `RwLock<T>` is lost
-when using `UniRcLock`. The methods `read()` and `write()` will panic if
-the lock is poisoned.
+The methods `read()` and `write()` will panic if the `RwLock<T>` or `Mutex<T>`
+is poisoned. The non-blocking variants `try_read()` and `try_write()`
+do not panic on poisoned lock and return `Err(LockError::Poisoned)` instead.
+The checked variants `read_checked()` and `write_checked()` block like `read()`
+and `write()`, but return [UniLockError::Poisoned] holding the guard, so the value
+could be recovered, and report a conflicting borrow of `Rc<RefCell<T>>`
+as [UniLockError::AlreadyBorrowed] rather than panicking.
+The poisoning could still be detected with `is_poisoned()` and reset
+with `clear_poison()` after making sure that the value is consistent.
+If poisoning is not desired at all, enable the `parking_lot` feature and use
+`Arc<parking_lot::RwLock<T>>`, which is never poisoned, with the same generic code.
+
+The guards returned by `read()` and `write()` borrow the handle, so they can't
+be returned from a function or stored in a struct without a lifetime.
+The [OwnedLock] subtrait provides `read_owned()` and `write_owned()`,
+which consume a clone of the handle and keep it alive in the guard.
+
+## Reentrancy
+Reading the value again in the same thread while it is already locked
+behaves differently for different backends:
+
+| Backend | Nested `read()` | `read()` or `write()` while writing, `write()` while reading |
+|---------|-----------------|---------------------------------------------------------------|
+| `RefCell`, `AtomicRefCell` | works | panics |
+| `std::sync::RwLock` | may deadlock if a writer is waiting | deadlocks or panics |
+| `parking_lot::RwLock` | may deadlock if a writer is waiting | deadlocks |
+| `Mutex` of any kind | deadlocks | deadlocks |
+| `parking_lot::ReentrantMutex<RefCell<T>>` | works | panics |
+
+Thus the code which works with `Rc<RefCell<T>>` could deadlock after switching
+to `Arc<RwLock<T>>`. The generic code which needs nested reads should require
+the [RecursiveRead] trait and use [read_recursive](RecursiveRead::read_recursive).
+It is implemented only by the backends where a nested read can't deadlock,
+so passing any other backend is caught at compile time.
 
 # Examples
 
@@ -55,7 +378,7 @@ println!("After increment: {:?}", ptr2);
 ```
 Example of generic struct, which can hold either `Rc<RefCell<T>>` or `Arc<RwLock<T>>`:
 ```
-# use std::{rc::Rc, cell::RefCell, sync::{Arc, RwLock}};
+# use std::{rc::Rc, cell::RefCell, sync::{Arc, Mutex, RwLock}};
 # use uni_rc_lock::UniRcLock;
 # use std::thread;
 #
@@ -91,6 +414,14 @@ impl<T: UniRcLock<State>> StateHandler<T> {
     println!("{}", st_handler.state.read().val);
 }
 
+// Using with Arc<Mutex> in exactly the same way
+{
+    let st = Arc::new(Mutex::new(State { val: 42 }));
+    let st_handler = StateHandler::new(st);
+    st_handler.state.write().val += 1;
+    println!("{}", st_handler.state.read().val);
+}
+
 // Using in multiple threads with Arc
 {
     let st = Arc::new(RwLock::new(State { val: 42 }));
@@ -114,147 +445,2923 @@ impl<T: UniRcLock<State>> StateHandler<T> {
 }
 ```
 Expectibly, this example won't compile with `Rc` since it doesn't implement `Send`.
+
+Guards could be projected to a part of the value, like `Ref::map()` does:
+```
+# use std::{rc::Rc, cell::RefCell, sync::{Arc, RwLock}};
+# use uni_rc_lock::UniRcLock;
+#
+struct State {name: String, val: i32}
+
+fn incr_val(p: impl UniRcLock<State>) {
+    let mut val = p.map_write(|st| &mut st.val);
+    *val += 1;
+}
+
+let st = Arc::new(RwLock::new(State {name: "foo".into(), val: 0}));
+incr_val(st.clone());
+assert_eq!(*st.map_read(|st| &st.val), 1);
+```
+For backends without native mapped guards, such as `std::sync::RwLock`,
+[MappedReadGuard] and [MappedWriteGuard] hold the original guard together
+with the projected reference.
+
+Handles could also be constructed generically, without knowing the concrete type,
+with [UniNew], implemented by the handles owning their value, but not by the borrowed
+ones like `&RefCell<T>`:
+```
+# use std::{rc::Rc, cell::RefCell, sync::{Arc, RwLock}};
+# use uni_rc_lock::{UniNew, UniRcLock};
+#
+struct State {val: i32}
+
+struct StateHandler<T: UniRcLock<State>> {
+    state: T,
+}
+
+// Generic factory creating the inner cell
+fn make_handler<T: UniNew<State>>(val: i32) -> StateHandler<T> {
+    StateHandler { state: T::new(State { val }) }
+}
+
+let h1: StateHandler<Rc<RefCell<State>>> = make_handler(1);
+let h2 = make_handler::<Arc<RwLock<State>>>(2);
+assert_eq!(h1.state.read().val + h2.state.read().val, 3);
+```
+Such wrapper structs could be declared with the [uni_handle!] macro.
 */
 
 //===============================================================
 
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
-use std::{
-    cell::{Ref, RefCell, RefMut},
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "actor")]
+mod actor;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod backends;
+mod copy;
+mod handle_map;
+mod macros;
+mod mapped;
+#[cfg(feature = "notify")]
+mod notify;
+mod owned;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod value;
+#[cfg(feature = "actor")]
+pub use actor::{Address, IntoActor};
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncOwnedLock, AsyncUniRcLock, SpawnableAsyncLock};
+#[cfg(feature = "timeout")]
+pub use asynchronous::Elapsed;
+#[cfg(feature = "tokio")]
+pub use backends::tokio::{AdapterReadGuard, AdapterWriteGuard, AsyncAdapter};
+#[cfg(feature = "tokio-watch")]
+pub use backends::tokio_watch::{WatchHandle, WatchWeak, WatchWriteGuard};
+#[cfg(feature = "notify")]
+pub use notify::{Notified, NotifyStream, NotifyWriteGuard};
+pub use copy::UniRcCopy;
+pub use handle_map::HandleMap;
+#[doc(hidden)]
+pub use paste::paste as __paste;
+pub use mapped::{MappedReadGuard, MappedWriteGuard};
+pub use owned::{OwnedGuard, OwnedLock};
+pub use value::{ValueGuard, ValueWriteGuard};
+#[cfg(feature = "std")]
+pub use backends::lazy::get_or_init;
+#[cfg(feature = "std")]
+pub use backends::thread_local::{
+    ThreadLocalHandle, ThreadLocalReadGuard, ThreadLocalWeak, ThreadLocalWriteGuard,
+};
+#[cfg(feature = "std")]
+pub use backends::std_sync::{arc_to_rc, rc_to_arc, IntoArc, Resilient};
+#[cfg(feature = "lock_api")]
+pub use backends::lock_api::UpgradableRead;
+#[cfg(feature = "crossbeam")]
+pub use backends::crossbeam::{padded_shards, PaddedHandle};
+#[cfg(feature = "crossbeam-epoch")]
+pub use backends::crossbeam_epoch::{RcuHandle, RcuReadGuard, RcuWeak, RcuWriteGuard};
+#[cfg(feature = "parking_lot")]
+pub use backends::parking_lot::{ReentrantReadGuard, ReentrantWriteGuard};
+#[cfg(feature = "qcell")]
+pub use backends::qcell::{QHandle, QReadGuard, QWeak, QWriteGuard};
+#[cfg(feature = "ghost-cell")]
+pub use backends::ghost_cell::{GhostHandle, GhostReadGuard, GhostWeak, GhostWriteGuard};
+#[cfg(feature = "spin")]
+pub use backends::spin::{SpinHandle, SpinMutexHandle};
+#[cfg(feature = "hybrid-rc")]
+pub use backends::hybrid_rc::HybridWeak;
+#[cfg(feature = "arc-swap")]
+pub use backends::arc_swap::{SwapHandle, SwapReadGuard, SwapWeak, SwapWriteGuard};
+#[cfg(feature = "dashmap")]
+pub use backends::dashmap::{entry_handle, MapEntryHandle, MapEntryWeak};
+#[cfg(feature = "left-right")]
+pub use backends::left_right::{LeftRightHandle, LeftRightWeak, LeftRightWriteGuard};
+#[cfg(feature = "slotmap")]
+pub use backends::slotmap::{ArenaHandle, ArenaWeak, LocalArena, SyncArena, SyncArenaHandle};
+#[cfg(feature = "seqlock")]
+pub use backends::seqlock::SeqLockHandle;
+#[cfg(feature = "send_wrapper")]
+pub use backends::send_wrapper::LocalSendHandle;
+#[cfg(feature = "shm")]
+pub use backends::shm::{ShmHandle, ShmReadGuard, ShmWeak, ShmWriteGuard};
+#[cfg(feature = "persistent")]
+pub use backends::persistent::{Format, PersistentHandle, PersistentWeak, PersistentWriteGuard};
+#[cfg(feature = "critical-section")]
+pub use backends::critical_section::{CsReadGuard, CsWriteGuard};
+
+use alloc::rc::{self, Rc};
+use alloc::vec::Vec;
+use core::{
+    cell::{Cell, Ref, RefCell, RefMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
     ops::Deref,
     ops::DerefMut,
-    rc::Rc,
+    time::Duration,
 };
 
+/// The canonical single-threaded [UniRcLock] handle, `Rc<RefCell<T>>`.
+pub type SingleThreaded<T> = Rc<RefCell<T>>;
+
+/// The canonical multi-threaded [UniRcLock] handle, `Arc<RwLock<T>>`.
+#[cfg(all(
+    feature = "std",
+    not(any(loom, feature = "loom", shuttle, feature = "shuttle"))
+))]
+pub type MultiThreaded<T> = std::sync::Arc<std::sync::RwLock<T>>;
+
+/// The canonical multi-threaded [UniRcLock] handle replaced by
+/// `loom::sync::Arc<loom::sync::RwLock<T>>` for model checking.
+/// For testing only: it works only inside `loom::model()`.
+#[cfg(any(loom, feature = "loom"))]
+pub type MultiThreaded<T> = loom::sync::Arc<loom::sync::RwLock<T>>;
+
+/// The canonical multi-threaded [UniRcLock] handle replaced by
+/// `Arc<shuttle::sync::RwLock<T>>` for randomized scheduling tests.
+/// For testing only: it works only inside `shuttle::check_random()` and alike.
+#[cfg(all(any(shuttle, feature = "shuttle"), not(any(loom, feature = "loom"))))]
+pub type MultiThreaded<T> = std::sync::Arc<shuttle::sync::RwLock<T>>;
+
+/// The canonical multi-threaded [UniRcLock] handle without `std`,
+/// `Arc<spin::RwLock<T>>` provided by the `spin` feature.
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+pub type MultiThreaded<T> = SpinHandle<T>;
+
+#[cfg(all(feature = "threadsafe", feature = "single-thread"))]
+compile_error!("The features `threadsafe` and `single-thread` are mutually exclusive");
+
+/// The handle selected at compile time by the `threadsafe` feature:
+/// [MultiThreaded] if it is enabled, or [SingleThreaded] otherwise,
+/// which could be requested explicitly with the `single-thread` feature.
+#[cfg(feature = "threadsafe")]
+pub type Shared<T> = MultiThreaded<T>;
+
+/// The handle selected at compile time by the `threadsafe` feature:
+/// [MultiThreaded] if it is enabled, or [SingleThreaded] otherwise,
+/// which could be requested explicitly with the `single-thread` feature.
+#[cfg(not(feature = "threadsafe"))]
+pub type Shared<T> = SingleThreaded<T>;
+
+/// The reason why a non-blocking lock attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    /// The lock is currently held and can't be acquired without blocking.
+    /// For `Rc<RefCell<T>>` this means that the value is already borrowed.
+    WouldBlock,
+    /// The lock is poisoned because some other thread panicked while holding it.
+    /// Never returned by `Rc<RefCell<T>>`.
+    Poisoned,
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::WouldBlock => write!(f, "lock would block"),
+            LockError::Poisoned => write!(f, "lock is poisoned"),
+        }
+    }
+}
+
+impl core::error::Error for LockError {}
+
+/// The reason why [read_checked](UniRcLock::read_checked) or
+/// [write_checked](UniRcLock::write_checked) failed, where `read()`
+/// and `write()` would panic.
+///
+/// Unlike [LockError], a poisoned lock still gives the guard,
+/// so the value could be recovered, like with `std::sync::PoisonError`.
+pub enum UniLockError<G> {
+    /// The lock is poisoned because some other thread panicked while holding it.
+    /// The lock is acquired anyway, and the guard is returned here.
+    Poisoned(G),
+    /// The value is borrowed, possibly by another thread, and the handle
+    /// can't wait until it is released, like `Arc<AtomicRefCell<T>>`.
+    WouldBlock,
+    /// The value is already borrowed by the same thread in a conflicting way,
+    /// like a `RefCell` borrowed for writing.
+    AlreadyBorrowed,
+}
+
+impl<G> UniLockError<G> {
+    /// The guard of the poisoned lock, or `None` for the other errors
+    pub fn into_guard(self) -> Option<G> {
+        match self {
+            UniLockError::Poisoned(guard) => Some(guard),
+            _ => None,
+        }
+    }
+
+    /// Transform the guard of the poisoned lock
+    pub fn map<G2>(self, f: impl FnOnce(G) -> G2) -> UniLockError<G2> {
+        match self {
+            UniLockError::Poisoned(guard) => UniLockError::Poisoned(f(guard)),
+            UniLockError::WouldBlock => UniLockError::WouldBlock,
+            UniLockError::AlreadyBorrowed => UniLockError::AlreadyBorrowed,
+        }
+    }
+}
+
+// Like PoisonError, the guard is not printed, so it needs no Debug
+impl<G> fmt::Debug for UniLockError<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniLockError::Poisoned(_) => f.write_str("Poisoned(..)"),
+            UniLockError::WouldBlock => f.write_str("WouldBlock"),
+            UniLockError::AlreadyBorrowed => f.write_str("AlreadyBorrowed"),
+        }
+    }
+}
+
+impl<G> fmt::Display for UniLockError<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniLockError::Poisoned(_) => write!(f, "lock is poisoned"),
+            UniLockError::WouldBlock => write!(f, "lock would block"),
+            UniLockError::AlreadyBorrowed => write!(f, "value is already borrowed"),
+        }
+    }
+}
+
+impl<G> core::error::Error for UniLockError<G> {}
+
+/// The result of [read_checked](UniRcLock::read_checked)
+/// and [write_checked](UniRcLock::write_checked)
+pub type CheckedResult<G> = Result<G, UniLockError<G>>;
+
 /// A common trait for `Rc<RefCell<T>>` and `Arc<RwLock<T>>` 
 pub trait UniRcLock<T>: Clone {
     type OutRead<'a>: Deref<Target = T> where Self: 'a;
     type OutWrite<'a>: DerefMut<Target = T> where Self: 'a;
+    /// Read guard projected to a part of the value by [map_read](UniRcLock::map_read)
+    type MappedRead<'a, U: ?Sized + 'a>: Deref<Target = U> where Self: 'a;
+    /// Write guard projected to a part of the value by [map_write](UniRcLock::map_write)
+    type MappedWrite<'a, U: ?Sized + 'a>: DerefMut<Target = U> where Self: 'a;
+    /// Weak counterpart of the handle
+    type Weak: UniWeak<T, Strong = Self>;
+    /// `true` if several read guards may coexist, like for `RwLock`,
+    /// and `false` if `read()` is exclusive, like for `Mutex`.
+    /// See also [MutexBackend].
+    const ALLOWS_CONCURRENT_READS: bool = true;
     /// Obtain a scoped guard for reading
     fn read<'a>(&'a self) -> Self::OutRead<'a>;
     /// Obtain a scoped guard for writing
     fn write<'a>(&'a self) -> Self::OutWrite<'a>;
-}
+    /// Try to obtain a scoped guard for reading without blocking.
+    /// Returns [LockError::WouldBlock] if the lock is currently held for writing
+    /// and [LockError::Poisoned] if it is poisoned.
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError>;
+    /// Try to obtain a scoped guard for writing without blocking.
+    /// Returns [LockError::WouldBlock] if the lock is currently held by anyone
+    /// and [LockError::Poisoned] if it is poisoned.
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError>;
+    /// Obtain a scoped guard for reading, returning an error where `read()` panics.
+    ///
+    /// Blocks like `read()`, but returns [UniLockError::Poisoned] with the guard
+    /// if the lock is poisoned and [UniLockError::AlreadyBorrowed] if the value
+    /// is borrowed for writing by the same thread, like for `Rc<RefCell<T>>`.
+    /// The default implementation calls `read()`, for the handles which never fail.
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        Ok(self.read())
+    }
+    /// Obtain a scoped guard for writing, returning an error where `write()` panics.
+    ///
+    /// See [read_checked](UniRcLock::read_checked). The default implementation
+    /// calls `write()`, for the handles which never fail.
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        Ok(self.write())
+    }
+    /// Number of strong references to the managed value. Doesn't lock.
+    fn strong_count(&self) -> usize;
+    /// Number of weak references to the managed value. Doesn't lock.
+    fn weak_count(&self) -> usize;
+    /// Returns `true` if both handles point to the same allocation.
+    /// Doesn't lock, so works even if the value is currently locked.
+    fn ptr_eq(&self, other: &Self) -> bool;
+    /// Create a weak handle to the same value. Never panics: the handles
+    /// without weak references return a [NoWeak], which never upgrades.
+    fn downgrade(&self) -> Self::Weak;
+    /// Take the value out if this is the only strong handle.
+    /// Otherwise the handle is returned back in `Err`.
+    ///
+    /// The value is returned even if the lock is poisoned.
+    fn try_unwrap(self) -> Result<T, Self>;
+    /// Get a mutable reference to the value without locking
+    /// if this is the only handle, strong or weak. Otherwise returns `None`.
+    ///
+    /// The reference is returned even if the lock is poisoned.
+    fn get_mut(&mut self) -> Option<&mut T>;
+    /// Obtain a read guard projected to a part of the value, like `Ref::map()`.
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U>;
+    /// Obtain a write guard projected to a part of the value, like `RefMut::map()`.
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U>;
 
-// Implementation for Rc<RefCell<T>>
-impl<T> UniRcLock<T> for Rc<RefCell<T>> {
-    type OutRead<'a> = Ref<'a, T> where T: 'a;
-    type OutWrite<'a> = RefMut<'a, T> where T: 'a;
+    /// Obtain a strong handle from the weak one if the value is still alive.
+    /// The same as [UniWeak::upgrade], but named from the strong side,
+    /// so the generic code may write `H::from_weak(&w)`.
+    fn from_weak(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
 
-    fn read<'a>(&'a self) -> Self::OutRead<'a> {
-        Rc::deref(self).borrow()
+    /// Increment the reference count and return a new handle to the same cell.
+    ///
+    /// The same as `clone()`, but makes it obvious in the generic code
+    /// that the handle is shared rather than the value is copied.
+    fn share(&self) -> Self {
+        self.clone()
     }
 
-    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
-        Rc::deref(self).borrow_mut()
+    /// Copy the value into a new independent handle, unlike `clone()` and
+    /// [share](UniRcLock::share), which return a new handle to the same cell.
+    ///
+    /// The value is locked for reading only while it is cloned, and the new
+    /// handle is created by [new](UniNew::new), so the changes made through
+    /// either handle are not seen by the other.
+    fn deep_clone(&self) -> Self
+    where
+        Self: UniNew<T>,
+        T: Clone,
+    {
+        Self::new(self.get_cloned())
     }
-}
 
-// Implementation for Arc<RwLock<T>>
-impl<T> UniRcLock<T> for Arc<RwLock<T>> {
-    type OutRead<'a> = RwLockReadGuard<'a, T> where T: 'a;
-    type OutWrite<'a> = RwLockWriteGuard<'a, T> where T: 'a;
+    /// Returns `true` if this is the only handle to the value, strong or weak,
+    /// so a copy-on-write update could modify it in place.
+    /// Doesn't lock, like [strong_count](UniRcLock::strong_count).
+    ///
+    /// For the handles which are plain references, like `&RefCell<T>`,
+    /// the counts are fixed, so this is always `true`, although
+    /// [get_mut](UniRcLock::get_mut) returns `None` for them.
+    fn is_unique(&self) -> bool {
+        self.strong_count() == 1 && self.weak_count() == 0
+    }
 
-    fn read<'a>(&'a self) -> Self::OutRead<'a> {
-        Arc::deref(self)
-            .read()
-            .expect("Read lock should not be poisoned")
+    /// Copy-on-write access to the value, like `Rc::make_mut()`.
+    ///
+    /// If this is the only handle, strong or weak, the mutable reference
+    /// to the value is returned without locking. Otherwise the value is cloned
+    /// into a new handle, which replaces this one, so the other handles
+    /// keep seeing the old value.
+    ///
+    /// # Panics
+    /// Panics for the handles which never give out mutable references,
+    /// like `ThreadLocalHandle`, since the new handle is not unique either.
+    fn make_mut(&mut self) -> &mut T
+    where
+        Self: UniNew<T>,
+        T: Clone,
+    {
+        if self.get_mut().is_none() {
+            let value = self.read().clone();
+            *self = Self::new(value);
+        }
+        self.get_mut().expect("The new handle should be unique")
     }
 
-    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
-        Arc::deref(self)
-            .write()
-            .expect("Write lock should not be poisoned")
+    /// Returns the address of the value, for passing it to FFI code or using
+    /// it as an identity, like `Rc::as_ptr()`. Doesn't lock where the backend
+    /// gives access to the address of the value without a guard, like
+    /// `RefCell` and the `lock_api` locks, and locks briefly otherwise.
+    ///
+    /// The pointer is valid only while the handle is alive, and dereferencing it
+    /// is `unsafe`: the caller must hold a guard or otherwise ensure that no writer
+    /// is active. For the snapshot backends, like the `arc-swap` and `crossbeam-epoch`
+    /// ones, it points to the current snapshot, so the address changes and the old
+    /// snapshot may be destroyed when a new value is published.
+    /// Use [lock_id](UniRcLock::lock_id) as a stable identity instead.
+    fn as_ptr(&self) -> *const T {
+        &*self.read() as *const T
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::{
-        cell::RefCell,
-        rc::Rc,
-        sync::{Arc, RwLock},
-    };
+    /// Returns the address identifying the value shared by the clones of the handle,
+    /// which never changes while the handle is alive. [swap](UniRcLock::swap)
+    /// orders the locks of several handles by it.
+    ///
+    /// It is [as_ptr](UniRcLock::as_ptr) by default, while the snapshot backends,
+    /// whose values move when a new value is published, return the address
+    /// of their shared state instead. Not intended to be dereferenced.
+    fn lock_id(&self) -> *const () {
+        self.as_ptr().cast()
+    }
 
-    use super::UniRcLock;
+    /// Run the closure with the value locked for reading.
+    /// The lock is released as soon as the closure returns.
+    fn with_read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.read())
+    }
 
-    #[derive(Debug)]
-    struct State {
-        val: i32,
+    /// Read a part of the value out by value, for example a `Copy` field
+    /// of a large struct, without keeping the whole value locked.
+    /// Unlike [map_read](UniRcLock::map_read), which returns a guard,
+    /// the lock is released before the projected value is returned.
+    /// This is [with_read](UniRcLock::with_read) named after its intent.
+    fn read_map<U>(&self, f: impl FnOnce(&T) -> U) -> U {
+        self.with_read(f)
     }
-    //-----------------
-    // Generic handler
-    //-----------------
-    #[derive(Debug, Clone)]
-    struct StateHandler<T: UniRcLock<State>> {
-        state: T,
+
+    /// Run the closure with the value locked for writing.
+    /// The lock is released as soon as the closure returns.
+    fn with_write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.write())
     }
 
-    impl<T: UniRcLock<State>> StateHandler<T> {
-        fn new(val: T) -> Self {
-            Self { state: val }
-        }
+    /// Modify the value with the closure and return the result computed by it,
+    /// like a transaction. The lock is released before the result is returned,
+    /// so the caller can't hold it while using the result.
+    /// ```
+    /// # use std::{rc::Rc, cell::RefCell};
+    /// # use uni_rc_lock::UniRcLock;
+    /// let counter = Rc::new(RefCell::new(41));
+    /// let old = counter.update(|v| {
+    ///     *v += 1;
+    ///     *v - 1
+    /// });
+    /// assert_eq!(old, 41);
+    /// ```
+    fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut guard = self.write();
+        let result = f(&mut guard);
+        drop(guard);
+        result
     }
 
-    #[test]
-    fn rc() {
-        let st1 = Rc::new(RefCell::new(State { val: 42 }));
-        st1.write().val += 1;
-        println!("{:?}", st1.read());
+    /// Obtain a copy of the value. The value is locked for reading
+    /// only while it is cloned.
+    fn get_cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.read().clone()
     }
-    #[test]
-    fn arc() {
-        let st2 = Arc::new(RwLock::new(State { val: 42 }));
-        st2.write().val += 1;
-        println!("{:?}", st2.read());
+
+    /// Overwrite the value with a new one, like `RefCell::set()`.
+    fn set(&self, value: T) {
+        *self.write() = value;
     }
 
-    #[test]
-    fn rc_handler() {
-        let st1 = Rc::new(RefCell::new(State { val: 42 }));
-        let sth1 = StateHandler::new(st1);
-        println!("{:?}", sth1.state);
+    /// Replace the value with a new one and return the old value,
+    /// like `RefCell::replace()`.
+    fn replace(&self, value: T) -> T {
+        core::mem::replace(&mut *self.write(), value)
     }
 
-    #[test]
-    fn arc_handler() {
-        let st2 = Arc::new(RwLock::new(State { val: 42 }));
-        let sth2 = StateHandler::new(st2);
-        sth2.state.write().val += 1;
-        println!("{:?}", sth2.state);
+    /// Take the value out leaving `Default::default()` in its place,
+    /// like `RefCell::take()`.
+    fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
     }
 
-    #[test]
-    fn test_func() {
-        fn incr(p: impl UniRcLock<State>) {
-            p.write().val += 1;
+    /// Swap the values of two handles, like `RefCell::swap()`.
+    ///
+    /// Both values are locked for writing at the same time. Locking them in
+    /// arbitrary order could deadlock when two threads swap the same pair of
+    /// handles in the opposite order, so the locks are always acquired in the
+    /// order of the stable addresses given by [lock_id](UniRcLock::lock_id),
+    /// without locking where the backend allows it. Swapping a handle
+    /// with itself does nothing.
+    ///
+    /// # Panics
+    /// Panics for `Rc<RefCell<T>>` if any of the values is currently borrowed.
+    fn swap(&self, other: &Self) {
+        if self.ptr_eq(other) {
+            return;
         }
+        let (first, second) = if lock_order(self, other).is_lt() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut first = first.write();
+        let mut second = second.write();
+        core::mem::swap(&mut *first, &mut *second);
+    }
 
-        let ptr = Rc::new(RefCell::new(State { val: 0 }));
-        incr(ptr.clone());
-        println!("{:?}", ptr.read().val);
+    /// Compare the values of two handles, unlike [ptr_eq](UniRcLock::ptr_eq)
+    /// which compares their identity.
+    ///
+    /// Both values are locked for reading at the same time, in the order
+    /// of their [lock_id](UniRcLock::lock_id) like in [swap](UniRcLock::swap),
+    /// so the order doesn't change when the snapshot backends publish a new value.
+    /// If the handles point to the same value, it is locked only once, since locking it twice
+    /// would deadlock for `Arc<Mutex<T>>`. The value is still compared with
+    /// itself, so a `T` with partial equality like `f64::NAN` is not equal to itself.
+    ///
+    /// # Panics
+    /// Panics for `Rc<RefCell<T>>` if any of the values is currently borrowed for writing.
+    fn content_eq(&self, other: &Self) -> bool
+    where
+        T: PartialEq,
+    {
+        if self.ptr_eq(other) {
+            let value = self.read();
+            return T::eq(&value, &value);
+        }
+        let (first, second) = if lock_order(self, other).is_lt() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let first = first.read();
+        let second = second.read();
+        *first == *second
     }
 
-    #[test]
-    fn threads_test_arc() {
-        use std::thread;
-        let st2 = Arc::new(RwLock::new(State { val: 42 }));
-        let sth2 = StateHandler::new(st2);
+    /// Feed the value into the hasher, so that the handles with equal values
+    /// by [content_eq](UniRcLock::content_eq) have equal hashes.
+    ///
+    /// `Hash` can't be implemented for the foreign handle types, so it is
+    /// intended for the newtypes keying hash sets or maps by the values.
+    /// Mutating the value of a handle while it is in a set or a map changes
+    /// its hash, and the set or the map can't find it anymore: like for any
+    /// key, the value must not be changed until the handle is removed.
+    /// ```
+    /// use std::{cell::RefCell, collections::HashSet, hash::{Hash, Hasher}, rc::Rc};
+    /// use uni_rc_lock::UniRcLock;
+    ///
+    /// struct ByValue(Rc<RefCell<String>>);
+    ///
+    /// impl Hash for ByValue {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.0.hash_contents(state)
+    ///     }
+    /// }
+    ///
+    /// impl PartialEq for ByValue {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.0.content_eq(&other.0)
+    ///     }
+    /// }
+    ///
+    /// impl Eq for ByValue {}
+    ///
+    /// let mut set = HashSet::new();
+    /// set.insert(ByValue(Rc::new(RefCell::new("a".into()))));
+    /// assert!(!set.insert(ByValue(Rc::new(RefCell::new("a".into())))));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics for `Rc<RefCell<T>>` if the value is currently borrowed for writing.
+    fn hash_contents<H: Hasher>(&self, state: &mut H)
+    where
+        T: Hash,
+    {
+        self.read().hash(state)
+    }
 
-        let threads: Vec<_> = (0..10)
-            .map(|i| {
-                let h = sth2.clone();
-                thread::spawn(move || {
-                    h.state.write().val += 1;
-                    println!("Thread #{i} incremented");
-                })
-            })
-            .collect();
+    /// Obtain a wrapper which formats the value with `Debug`.
+    /// See [DebugHandle] for details.
+    fn debug(&self) -> DebugHandle<'_, T, Self>
+    where
+        T: fmt::Debug,
+    {
+        DebugHandle {
+            handle: self,
+            _value: core::marker::PhantomData,
+        }
+    }
 
-        for t in threads {
-            t.join().unwrap();
+    /// Returns `true` if a read guard could be obtained right now without
+    /// blocking or panicking, which is probed by [try_read](UniRcLock::try_read)
+    /// and the guard is released immediately. Useful for assertions
+    /// with a meaningful message before calling `read()`.
+    ///
+    /// For `RefCell` the answer is exact, since only the current thread
+    /// could borrow it. For the thread-safe locks it is racy: another thread
+    /// may take or release the lock right after the check.
+    fn can_read(&self) -> bool {
+        self.try_read().is_ok()
+    }
+
+    /// Returns `true` if a write guard could be obtained right now without
+    /// blocking or panicking. Racy for the thread-safe locks like
+    /// [can_read](UniRcLock::can_read).
+    fn can_write(&self) -> bool {
+        self.try_write().is_ok()
+    }
+
+    /// Returns `true` if the lock is poisoned because some thread panicked
+    /// while holding it. Always `false` for the locks which can't be poisoned,
+    /// like `RefCell` or the `parking_lot` locks.
+    fn is_poisoned(&self) -> bool {
+        false
+    }
+
+    /// Clear the poisoned state of the lock, so that `read()` and `write()`
+    /// don't panic anymore. Does nothing for the locks which can't be poisoned.
+    fn clear_poison(&self) {}
+}
+
+/// Transform the value of a unique handle into a new handle of another value.
+///
+/// The value is moved out of `handle`, passed to `f` and the result is put
+/// into a new handle, typically of the same backend, like `Rc<RefCell<T>>`
+/// into `Rc<RefCell<U>>`. This requires the unique ownership: if there
+/// are other strong handles, the original handle is returned back in `Err`.
+/// The weak handles of the original value can't be upgraded anymore.
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use uni_rc_lock::{map_into, UniRcLock};
+///
+/// let h = Rc::new(RefCell::new("42"));
+/// let other = h.clone();
+/// let h = map_into::<_, Rc<RefCell<i32>>, _, _>(h, |s| s.parse().unwrap()).unwrap_err();
+/// drop(other);
+/// let h: Rc<RefCell<i32>> = map_into(h, |s: &str| s.parse().unwrap()).unwrap();
+/// assert_eq!(*h.read(), 42);
+/// ```
+pub fn map_into<H1, H2, T, U>(handle: H1, f: impl FnOnce(T) -> U) -> Result<H2, H1>
+where
+    H1: UniRcLock<T>,
+    H2: UniNew<U>,
+{
+    handle.try_unwrap().map(|value| H2::new(f(value)))
+}
+
+/// Run `f` on the values of the handles which could be read right now.
+///
+/// Each handle is locked in turn with `try_read()`, so a pass over many handles
+/// is never blocked by one of them being written. The handles which can't be read,
+/// because they are locked for writing or poisoned, are silently skipped.
+/// Returns the number of the values passed to `f`.
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use uni_rc_lock::{for_each_readable, UniNew, UniRcLock};
+///
+/// let handles: Vec<Rc<RefCell<i32>>> = (1..=3).map(UniNew::new).collect();
+/// let _w = handles[1].write();
+/// let mut sum = 0;
+/// assert_eq!(for_each_readable(&handles, |v| sum += v), 2);
+/// assert_eq!(sum, 4);
+/// ```
+pub fn for_each_readable<H: UniRcLock<T>, T>(handles: &[H], mut f: impl FnMut(&T)) -> usize {
+    let mut count = 0;
+    for guard in handles.iter().filter_map(|h| h.try_read().ok()) {
+        f(&guard);
+        count += 1;
+    }
+    count
+}
+
+/// Lock all the handles for writing at once, without deadlocking with other
+/// threads locking some of them.
+///
+/// Locking many values in arbitrary order could deadlock when two threads lock
+/// the same values in the opposite order, so, like in [swap](UniRcLock::swap),
+/// the locks are always acquired in the order of their [lock_id](UniRcLock::lock_id).
+/// The handles to the same value appearing several times in the slice are locked
+/// once, so `Rc<RefCell<T>>` is not borrowed twice. The guards are returned in the
+/// order of the first appearance of each value, and the values are unlocked
+/// when the guards are dropped.
+///
+/// # Panics
+/// Panics for `Rc<RefCell<T>>` if any of the values is currently borrowed.
+/// ```
+/// use std::sync::{Arc, RwLock};
+/// use uni_rc_lock::{write_all, UniRcLock};
+///
+/// let a = Arc::new(RwLock::new(1));
+/// let b = Arc::new(RwLock::new(2));
+/// let handles = [b.clone(), a.clone(), b.clone()];
+/// let mut guards = write_all(&handles);
+/// assert_eq!(guards.len(), 2);
+/// *guards[0] += 10;
+/// *guards[1] += 20;
+/// drop(guards);
+/// assert_eq!((*a.read(), *b.read()), (21, 12));
+/// ```
+pub fn write_all<'a, H: UniRcLock<T>, T>(handles: &'a [H]) -> Vec<H::OutWrite<'a>> {
+    // The key of each handle is taken once, so the order can't change while sorting
+    let mut order: Vec<_> = handles.iter().map(|h| h.lock_id()).enumerate().collect();
+    order.sort_by_key(|&(i, id)| (id, i));
+    order.dedup_by_key(|&mut (_, id)| id);
+    let mut guards: Vec<_> =
+        order.into_iter().map(|(i, _)| (i, handles[i].write())).collect();
+    guards.sort_by_key(|&(i, _)| i);
+    guards.into_iter().map(|(_, guard)| guard).collect()
+}
+
+// The order in which the values of several handles are locked at the same time,
+// so two threads locking the same handles never wait for each other.
+// The stable addresses are compared, which doesn't lock for most backends.
+fn lock_order<T, H: UniRcLock<T>>(a: &H, b: &H) -> Ordering {
+    a.lock_id().cmp(&b.lock_id())
+}
+
+/// The [UniRcLock] handles which allow reading the value recursively,
+/// that is while the same thread already holds a read guard, even if
+/// another thread is waiting to write. See the crate docs on reentrancy.
+///
+/// Implemented for the handles of `RefCell` and `AtomicRefCell`, for
+/// `Arc<parking_lot::ReentrantMutex<RefCell<T>>>` and for the `lock_api`
+/// locks with recursive raw locks, like `Arc<parking_lot::RwLock<T>>`.
+/// Not implemented for `std::sync::RwLock` and the mutexes, where
+/// a recursive read may deadlock.
+pub trait RecursiveRead<T>: UniRcLock<T> {
+    /// Obtain a scoped guard for reading, which never deadlocks with
+    /// the read guards held by the same thread.
+    ///
+    /// Reading while the same thread holds a write guard for the value
+    /// either panics or deadlocks, depending on the backend: the `RefCell`
+    /// based ones panic, while the `lock_api` locks deadlock.
+    fn read_recursive<'a>(&'a self) -> Self::OutRead<'a>;
+}
+
+/// Implementation for `Rc<RefCell<T>>` and other pointers to `RefCell<T>`.
+/// Shared borrows of a `RefCell` never conflict.
+impl<T, P> RecursiveRead<T> for P
+where
+    P: UniPtr<Cell = RefCell<T>>,
+{
+    fn read_recursive<'a>(&'a self) -> Self::OutRead<'a> {
+        self.try_read()
+            .expect("Recursive read while the RefCell is borrowed for writing")
+    }
+}
+
+impl<T> RecursiveRead<T> for &RefCell<T> {
+    fn read_recursive<'a>(&'a self) -> Self::OutRead<'a> {
+        self.try_borrow()
+            .expect("Recursive read while the RefCell is borrowed for writing")
+    }
+}
+
+/// The [UniRcLock] handles which could give up waiting for the lock
+/// after a timeout instead of blocking forever.
+///
+/// The semantics depend on the backend:
+/// - The `lock_api` locks with timed raw locks, like `Arc<parking_lot::RwLock<T>>`
+///   and `Arc<parking_lot::Mutex<T>>`, park the thread up to the timeout.
+/// - `RefCell` never blocks, so the timeout is ignored and the value
+///   is borrowed right away if possible, exactly like `try_read()`.
+///
+/// std `RwLock` and `Mutex` have no timed locking, so they don't implement it.
+pub trait TimedLock<T>: UniRcLock<T> {
+    /// Obtain a scoped guard for reading, or `None` if the value couldn't be
+    /// read within the timeout
+    fn read_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutRead<'a>>;
+    /// Obtain a scoped guard for writing, or `None` if the value couldn't be
+    /// written within the timeout
+    fn write_timeout<'a>(&'a self, timeout: Duration) -> Option<Self::OutWrite<'a>>;
+}
+
+/// `RefCell` never blocks, so the timeout is ignored.
+impl<T, P> TimedLock<T> for P
+where
+    P: UniPtr<Cell = RefCell<T>>,
+{
+    fn read_timeout<'a>(&'a self, _timeout: Duration) -> Option<Self::OutRead<'a>> {
+        self.try_read().ok()
+    }
+
+    fn write_timeout<'a>(&'a self, _timeout: Duration) -> Option<Self::OutWrite<'a>> {
+        self.try_write().ok()
+    }
+}
+
+/// The [UniRcLock] handles which could be created from a value.
+///
+/// Implemented by all the handles owning their value, like `Rc<RefCell<T>>`
+/// and `Arc<RwLock<T>>`, but not by the borrowed handles, like `&RefCell<T>`
+/// or `&RwLock<T>`, which can't be created without leaking the value.
+/// Require it in the generic code which creates new handles, in addition
+/// to [UniRcLock], which is enough to use the existing ones.
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use std::{cell::RefCell, rc::Rc, sync::{Arc, RwLock}};
+/// use uni_rc_lock::{UniNew, UniRcLock};
+///
+/// fn pair<H: UniNew<i32>>(a: i32, b: i32) -> (H, H) {
+///     (H::new(a), H::new(b))
+/// }
+///
+/// let (a, b): (Rc<RefCell<i32>>, _) = pair(1, 2);
+/// let (c, d): (Arc<RwLock<i32>>, _) = pair(3, 4);
+/// assert_eq!(*a.read() + *b.read() + *c.read() + *d.read(), 10);
+/// # }
+/// ```
+pub trait UniNew<T>: UniRcLock<T> {
+    /// Create a new handle owning the value
+    fn new(value: T) -> Self;
+
+    /// Create a new handle owning `T::default()`, like `new(T::default())`.
+    fn default_new() -> Self
+    where
+        T: Default,
+    {
+        Self::new(T::default())
+    }
+}
+
+/// Marker for the [UniRcLock] handles with a true exclusive lock,
+/// where `read()` locks exactly like `write()`, so no two guards ever coexist.
+///
+/// Require it in the generic code which relies on reads being exclusive,
+/// for example to keep a read-check-then-write sequence logically atomic
+/// under a read guard. Passing a reader-writer lock then fails to compile.
+/// The handles implementing it have
+/// [ALLOWS_CONCURRENT_READS](UniRcLock::ALLOWS_CONCURRENT_READS) equal to `false`,
+/// which could be checked instead when only a runtime decision is needed.
+///
+/// Implemented for `Arc<std::sync::Mutex<T>>` and any other pointer to
+/// std `Mutex`, and for the `lock_api` and `spin` mutexes.
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use std::sync::{Arc, Mutex, RwLock};
+/// use uni_rc_lock::{MutexBackend, UniNew, UniRcLock};
+///
+/// fn exclusive<H: MutexBackend<u32>>(h: &H) {
+///     let _r = h.read();
+///     // Nobody else could even read while the guard is held
+///     assert!(h.try_read().is_err());
+/// }
+///
+/// let counter: Arc<Mutex<u32>> = UniNew::new(0);
+/// exclusive(&counter);
+/// // The same check at runtime
+/// assert!(!<Arc<Mutex<u32>> as UniRcLock<u32>>::ALLOWS_CONCURRENT_READS);
+/// assert!(<Arc<RwLock<u32>> as UniRcLock<u32>>::ALLOWS_CONCURRENT_READS);
+/// # }
+/// ```
+pub trait MutexBackend<T>: UniRcLock<T> {}
+
+/// A common trait for weak counterparts of [UniRcLock] handles,
+/// like `rc::Weak<RefCell<T>>` and `sync::Weak<RwLock<T>>`
+pub trait UniWeak<T>: Clone {
+    type Strong: UniRcLock<T, Weak = Self>;
+    /// Obtain a strong handle if the value is still alive
+    fn upgrade(&self) -> Option<Self::Strong>;
+}
+
+/// Weak handle type of the [UniRcLock] handles which don't support weak references.
+///
+/// It never upgrades, like `rc::Weak::new()`: `downgrade()` of such handles
+/// returns it without panicking, `upgrade()` always returns `None`
+/// and their `weak_count()` is always zero.
+pub struct NoWeak<S> {
+    _strong: core::marker::PhantomData<fn() -> S>,
+}
+
+impl<S> NoWeak<S> {
+    /// Create a weak handle which never upgrades
+    pub const fn new() -> Self {
+        Self {
+            _strong: core::marker::PhantomData,
         }
+    }
+}
 
-        println!("Result: {}", sth2.state.read().val);
+impl<S> Default for NoWeak<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Clone for NoWeak<S> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<S> fmt::Debug for NoWeak<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(NoWeak)")
+    }
+}
+
+impl<T, S: UniRcLock<T, Weak = Self>> UniWeak<T> for NoWeak<S> {
+    type Strong = S;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        None
+    }
+}
+
+/// Wrapper returned by [UniRcLock::debug], which formats the value
+/// of the handle with `Debug`.
+///
+/// The value is locked with `try_read()` while it is formatted, so formatting
+/// never blocks or panics. If the value is currently locked for writing,
+/// or borrowed mutably for `Rc<RefCell<T>>`, `<locked>` is printed instead.
+/// Poisoned locks are printed as `<poisoned>`.
+pub struct DebugHandle<'a, T, H: UniRcLock<T>> {
+    handle: &'a H,
+    _value: core::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: fmt::Debug, H: UniRcLock<T>> fmt::Debug for DebugHandle<'_, T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.handle.try_read() {
+            Ok(value) => fmt::Debug::fmt(&*value, f),
+            Err(LockError::WouldBlock) => f.write_str("<locked>"),
+            Err(LockError::Poisoned) => f.write_str("<poisoned>"),
+        }
+    }
+}
+
+/// A cell or a lock holding the value of a [UniRcLock] handle,
+/// like `RefCell<T>` or `RwLock<T>`.
+///
+/// Any [UniPtr] pointing to a `UniCell` is a [UniRcLock] handle, so the cells
+/// could be combined with any pointer: `Rc<RwLock<T>>` and `Arc<RefCell<T>>`
+/// work as well as `Rc<RefCell<T>>` and `Arc<RwLock<T>>`.
+/// The methods mirror those of [UniRcLock].
+pub trait UniCell {
+    /// The type of the value in the cell
+    type Value;
+    type Read<'a>: Deref<Target = Self::Value> where Self: 'a;
+    type Write<'a>: DerefMut<Target = Self::Value> where Self: 'a;
+    type MappedRead<'a, U: ?Sized + 'a>: Deref<Target = U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a>: DerefMut<Target = U> where Self: 'a;
+    /// `true` if several read guards may coexist, see [UniRcLock::ALLOWS_CONCURRENT_READS]
+    const ALLOWS_CONCURRENT_READS: bool = true;
+    /// Create a new cell holding the value
+    fn new(value: Self::Value) -> Self;
+    /// Obtain a scoped guard for reading
+    fn read<'a>(&'a self) -> Self::Read<'a>;
+    /// Obtain a scoped guard for writing
+    fn write<'a>(&'a self) -> Self::Write<'a>;
+    /// Try to obtain a scoped guard for reading without blocking
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError>;
+    /// Try to obtain a scoped guard for writing without blocking
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError>;
+    /// Obtain a scoped guard for reading, see [UniRcLock::read_checked]
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::Read<'a>> {
+        Ok(self.read())
+    }
+    /// Obtain a scoped guard for writing, see [UniRcLock::write_checked]
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::Write<'a>> {
+        Ok(self.write())
+    }
+    /// Consume the cell and return the value
+    fn into_inner(self) -> Self::Value;
+    /// Obtain a mutable reference to the value of the exclusively owned cell
+    fn get_mut(&mut self) -> &mut Self::Value;
+    /// Obtain a read guard projected to a part of the value
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&Self::Value) -> &U,
+    ) -> Self::MappedRead<'a, U>;
+    /// Obtain a write guard projected to a part of the value
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut Self::Value) -> &mut U,
+    ) -> Self::MappedWrite<'a, U>;
+    /// Returns `true` if the lock is poisoned
+    fn is_poisoned(&self) -> bool {
+        false
+    }
+    /// Clear the poisoned state of the lock
+    fn clear_poison(&self) {}
+    /// Returns the address of the value, see [UniRcLock::as_ptr]
+    fn as_ptr(&self) -> *const Self::Value {
+        &*self.read() as *const Self::Value
+    }
+}
+
+/// A reference-counted pointer to a [UniCell], like `Rc` or `Arc`.
+///
+/// Implementing it for a custom smart pointer makes the pointer
+/// a [UniRcLock] handle for all the cells it points to.
+/// The functions take the pointer as an argument rather than as `self`,
+/// like the ones of `Rc`, so they don't clash with the methods of [UniRcLock].
+///
+/// # Safety
+///
+/// The cell must live at a stable address on the heap: moving the pointer
+/// must not move the cell, and the cell must not be dropped while any strong
+/// pointer to it is alive, like in `Rc` and `Arc`. [OwnedLock] relies on it,
+/// since it keeps the guards borrowing the cell next to the moved pointer.
+pub unsafe trait UniPtr: Clone + Deref<Target = Self::Cell> {
+    /// The pointee
+    type Cell;
+    /// Weak counterpart of the pointer
+    type Weak: UniWeakPtr<Strong = Self>;
+    /// Create a new pointer owning the cell
+    fn new(cell: Self::Cell) -> Self;
+    /// Number of strong pointers to the cell
+    fn strong_count(this: &Self) -> usize;
+    /// Number of weak pointers to the cell
+    fn weak_count(this: &Self) -> usize;
+    /// Returns `true` if both pointers point to the same cell
+    fn ptr_eq(this: &Self, other: &Self) -> bool;
+    /// Create a weak pointer to the cell
+    fn downgrade(this: &Self) -> Self::Weak;
+    /// Return the cell if this is the only strong pointer, or the pointer back otherwise
+    fn try_unwrap(this: Self) -> Result<Self::Cell, Self>;
+    /// Obtain a mutable reference to the cell if there are no other pointers to it
+    fn get_mut(this: &mut Self) -> Option<&mut Self::Cell>;
+}
+
+/// Weak counterpart of a [UniPtr]
+pub trait UniWeakPtr: Clone {
+    type Strong: UniPtr<Weak = Self>;
+    /// Obtain a strong pointer if the cell still exists
+    fn upgrade(this: &Self) -> Option<Self::Strong>;
+}
+
+/// Implementation for any [UniPtr] pointing to a [UniCell], which includes
+/// `Rc<RefCell<T>>`, `Arc<RwLock<T>>`, `Arc<Mutex<T>>`, `Rc<RwLock<T>>`,
+/// `Arc<RefCell<T>>` and the custom pointers implementing [UniPtr].
+impl<T, P> UniRcLock<T> for P
+where
+    P: UniPtr,
+    P::Cell: UniCell<Value = T>,
+{
+    type OutRead<'a> = <P::Cell as UniCell>::Read<'a> where Self: 'a;
+    type OutWrite<'a> = <P::Cell as UniCell>::Write<'a> where Self: 'a;
+    type Weak = P::Weak;
+    type MappedRead<'a, U: ?Sized + 'a> = <P::Cell as UniCell>::MappedRead<'a, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        <P::Cell as UniCell>::MappedWrite<'a, U> where Self: 'a;
+    const ALLOWS_CONCURRENT_READS: bool = <P::Cell as UniCell>::ALLOWS_CONCURRENT_READS;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        UniCell::read(&**self)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        UniCell::write(&**self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        UniCell::try_read(&**self)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        UniCell::try_write(&**self)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        UniCell::read_checked(&**self)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        UniCell::write_checked(&**self)
+    }
+
+    fn strong_count(&self) -> usize {
+        P::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        P::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        P::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        P::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        P::try_unwrap(self).map(UniCell::into_inner)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        P::get_mut(self).map(UniCell::get_mut)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        UniCell::is_poisoned(&**self)
+    }
+
+    fn clear_poison(&self) {
+        UniCell::clear_poison(&**self)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        UniCell::as_ptr(&**self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        UniCell::map_read(&**self, f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        UniCell::map_write(&**self, f)
+    }
+}
+
+impl<T, P> UniNew<T> for P
+where
+    P: UniPtr,
+    P::Cell: UniCell<Value = T>,
+{
+    fn new(value: T) -> Self {
+        UniPtr::new(UniCell::new(value))
+    }
+}
+
+impl<T, W> UniWeak<T> for W
+where
+    W: UniWeakPtr,
+    <W::Strong as UniPtr>::Cell: UniCell<Value = T>,
+{
+    type Strong = W::Strong;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        W::upgrade(self)
+    }
+}
+
+// SAFETY: the cell is in the heap allocation kept alive by the strong pointers
+unsafe impl<C> UniPtr for Rc<C> {
+    type Cell = C;
+    type Weak = rc::Weak<C>;
+
+    fn new(cell: C) -> Self {
+        Rc::new(cell)
+    }
+
+    fn strong_count(this: &Self) -> usize {
+        Rc::strong_count(this)
+    }
+
+    fn weak_count(this: &Self) -> usize {
+        Rc::weak_count(this)
+    }
+
+    fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(this, other)
+    }
+
+    fn downgrade(this: &Self) -> Self::Weak {
+        Rc::downgrade(this)
+    }
+
+    fn try_unwrap(this: Self) -> Result<C, Self> {
+        Rc::try_unwrap(this)
+    }
+
+    fn get_mut(this: &mut Self) -> Option<&mut C> {
+        Rc::get_mut(this)
+    }
+}
+
+impl<C> UniWeakPtr for rc::Weak<C> {
+    type Strong = Rc<C>;
+
+    fn upgrade(this: &Self) -> Option<Rc<C>> {
+        rc::Weak::upgrade(this)
+    }
+}
+
+// Arc is missing on the targets without atomic pointers
+#[cfg(target_has_atomic = "ptr")]
+// SAFETY: the cell is in the heap allocation kept alive by the strong pointers
+unsafe impl<C> UniPtr for alloc::sync::Arc<C> {
+    type Cell = C;
+    type Weak = alloc::sync::Weak<C>;
+
+    fn new(cell: C) -> Self {
+        alloc::sync::Arc::new(cell)
+    }
+
+    fn strong_count(this: &Self) -> usize {
+        alloc::sync::Arc::strong_count(this)
+    }
+
+    fn weak_count(this: &Self) -> usize {
+        alloc::sync::Arc::weak_count(this)
+    }
+
+    fn ptr_eq(this: &Self, other: &Self) -> bool {
+        alloc::sync::Arc::ptr_eq(this, other)
+    }
+
+    fn downgrade(this: &Self) -> Self::Weak {
+        alloc::sync::Arc::downgrade(this)
+    }
+
+    fn try_unwrap(this: Self) -> Result<C, Self> {
+        alloc::sync::Arc::try_unwrap(this)
+    }
+
+    fn get_mut(this: &mut Self) -> Option<&mut C> {
+        alloc::sync::Arc::get_mut(this)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<C> UniWeakPtr for alloc::sync::Weak<C> {
+    type Strong = alloc::sync::Arc<C>;
+
+    fn upgrade(this: &Self) -> Option<Self::Strong> {
+        alloc::sync::Weak::upgrade(this)
+    }
+}
+
+// Implementation for RefCell<T>
+impl<T> UniCell for RefCell<T> {
+    type Value = T;
+    type Read<'a> = Ref<'a, T> where T: 'a;
+    type Write<'a> = RefMut<'a, T> where T: 'a;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where T: 'a;
+
+    fn new(value: T) -> Self {
+        RefCell::new(value)
+    }
+
+    fn read<'a>(&'a self) -> Self::Read<'a> {
+        self.borrow()
+    }
+
+    fn write<'a>(&'a self) -> Self::Write<'a> {
+        self.borrow_mut()
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::Read<'a>, LockError> {
+        self.try_borrow().map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::Write<'a>, LockError> {
+        self.try_borrow_mut().map_err(|_| LockError::WouldBlock)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::Read<'a>> {
+        self.try_borrow().map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::Write<'a>> {
+        self.try_borrow_mut().map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn into_inner(self) -> T {
+        RefCell::into_inner(self)
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        RefCell::get_mut(self)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        RefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(self.borrow(), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(self.borrow_mut(), f)
+    }
+}
+
+/// Implementation for `Rc<Cell<T>>` with `T: Copy`.
+///
+/// A `Cell` can't lend references to its value, so the guards own a copy
+/// of it. `read()` returns a [ValueGuard] with a copy of the value, while
+/// `write()` returns a [ValueWriteGuard], which stores the modified copy
+/// back when dropped. Nothing is borrowed, so the guards never conflict
+/// and `read()` and `write()` never panic, but a write made while
+/// another write guard is alive is overwritten when that guard is dropped.
+impl<T: Copy> UniRcLock<T> for Rc<Cell<T>> {
+    type OutRead<'a> = ValueGuard<T> where T: 'a;
+    type OutWrite<'a> = ValueWriteGuard<'a, Cell<T>, T> where T: 'a;
+    type Weak = rc::Weak<Cell<T>>;
+    type MappedRead<'a, U: ?Sized + 'a> = MappedReadGuard<alloc::boxed::Box<T>, U> where T: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> =
+        MappedWriteGuard<alloc::boxed::Box<Self::OutWrite<'a>>, U> where T: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        ValueGuard::new(self.get())
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        ValueWriteGuard::new(self, self.get(), Cell::set)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        Ok(UniRcLock::read(self))
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        Ok(UniRcLock::write(self))
+    }
+
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        Rc::downgrade(self)
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Rc::try_unwrap(self).map(Cell::into_inner)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        Rc::get_mut(self).map(Cell::get_mut)
+    }
+
+    fn as_ptr(&self) -> *const T {
+        Cell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        // SAFETY: the copy lives in the box, not in the guard
+        unsafe { MappedReadGuard::new(alloc::boxed::Box::new(self.get()), f) }
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        let guard = alloc::boxed::Box::new(UniRcLock::write(self));
+        // SAFETY: the copy lives in the boxed guard, which doesn't move
+        unsafe { MappedWriteGuard::new(guard, |g| f(&mut **g)) }
+    }
+}
+
+impl<T: Copy> UniNew<T> for Rc<Cell<T>> {
+    fn new(value: T) -> Self {
+        Rc::new(Cell::new(value))
+    }
+}
+
+impl<T: Copy> UniWeak<T> for rc::Weak<Cell<T>> {
+    type Strong = Rc<Cell<T>>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        rc::Weak::upgrade(self)
+    }
+}
+
+/// Implementation for `&RefCell<T>`, for example a value on the stack
+/// lent to the generic code, a leaked value or a `thread_local!` static.
+///
+/// Such handles can't outlive the borrow, so they can't be stored
+/// anywhere beyond it unless the reference is `'static`.
+/// A reference is not reference-counted: `strong_count()` is always 1,
+/// `try_unwrap()` always fails and `get_mut()` always returns `None`.
+/// There are no weak references, so `downgrade()` returns a [NoWeak].
+/// It can't be created from a value without leaking it, so it doesn't
+/// implement [UniNew]: borrow an existing `RefCell` instead.
+impl<T> UniRcLock<T> for &RefCell<T> {
+    type OutRead<'a> = Ref<'a, T> where Self: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        self.borrow()
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        self.borrow_mut()
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        self.try_borrow().map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        self.try_borrow_mut().map_err(|_| LockError::WouldBlock)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        self.try_borrow().map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        self.try_borrow_mut().map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn strong_count(&self) -> usize {
+        1
+    }
+
+    fn weak_count(&self) -> usize {
+        0
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        core::ptr::eq(*self, *other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn as_ptr(&self) -> *const T {
+        RefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(UniRcLock::read(self), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(UniRcLock::write(self), f)
+    }
+}
+
+/// Implementation for `&Rc<RefCell<T>>`, a borrowed handle lent
+/// to the generic code without cloning the `Rc`.
+///
+/// The reference is copied instead of cloning the `Rc`, but it can't
+/// outlive the borrow. `strong_count()` and `weak_count()` are the ones
+/// of the borrowed `Rc`. The value can't be moved out through a reference,
+/// so `try_unwrap()` always fails and `get_mut()` always returns `None`.
+/// A weak handle couldn't be upgraded to a reference, so `downgrade()`
+/// returns a [NoWeak], which never upgrades. It can't be created from a value
+/// without leaking the new `Rc`, so it doesn't implement [UniNew].
+impl<T> UniRcLock<T> for &Rc<RefCell<T>> {
+    type OutRead<'a> = Ref<'a, T> where Self: 'a;
+    type OutWrite<'a> = RefMut<'a, T> where Self: 'a;
+    type Weak = NoWeak<Self>;
+    type MappedRead<'a, U: ?Sized + 'a> = Ref<'a, U> where Self: 'a;
+    type MappedWrite<'a, U: ?Sized + 'a> = RefMut<'a, U> where Self: 'a;
+
+    fn read<'a>(&'a self) -> Self::OutRead<'a> {
+        RefCell::borrow(self)
+    }
+
+    fn write<'a>(&'a self) -> Self::OutWrite<'a> {
+        RefCell::borrow_mut(self)
+    }
+
+    fn try_read<'a>(&'a self) -> Result<Self::OutRead<'a>, LockError> {
+        RefCell::try_borrow(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn try_write<'a>(&'a self) -> Result<Self::OutWrite<'a>, LockError> {
+        RefCell::try_borrow_mut(self).map_err(|_| LockError::WouldBlock)
+    }
+
+    fn read_checked<'a>(&'a self) -> CheckedResult<Self::OutRead<'a>> {
+        RefCell::try_borrow(self).map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn write_checked<'a>(&'a self) -> CheckedResult<Self::OutWrite<'a>> {
+        RefCell::try_borrow_mut(self).map_err(|_| UniLockError::AlreadyBorrowed)
+    }
+
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(self)
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(self, other)
+    }
+
+    fn downgrade(&self) -> Self::Weak {
+        NoWeak::new()
+    }
+
+    fn try_unwrap(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        None
+    }
+
+    fn as_ptr(&self) -> *const T {
+        RefCell::as_ptr(self)
+    }
+
+    fn map_read<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> Self::MappedRead<'a, U> {
+        Ref::map(UniRcLock::read(self), f)
+    }
+
+    fn map_write<'a, U: ?Sized + 'a>(
+        &'a self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> Self::MappedWrite<'a, U> {
+        RefMut::map(UniRcLock::write(self), f)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::{Arc, Mutex, RwLock},
+    };
+
+    use super::{LockError, UniNew, UniRcLock, UniWeak};
+
+    #[derive(Debug)]
+    struct State {
+        val: i32,
+    }
+    //-----------------
+    // Generic handler
+    //-----------------
+    #[derive(Debug, Clone)]
+    struct StateHandler<T: UniRcLock<State>> {
+        state: T,
+    }
+
+    impl<T: UniRcLock<State>> StateHandler<T> {
+        fn new(val: T) -> Self {
+            Self { state: val }
+        }
+    }
+
+    // With loom or shuttle, MultiThreaded only works inside their test runners
+    #[cfg(not(any(loom, feature = "loom", shuttle, feature = "shuttle")))]
+    #[test]
+    fn aliases() {
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let st1: super::SingleThreaded<State> = UniNew::new(State { val: 0 });
+        let st2: super::MultiThreaded<State> = UniNew::new(State { val: 0 });
+        incr(st1.clone());
+        incr(st2.clone());
+        assert_eq!(st1.read().val + st2.read().val, 2);
+    }
+
+    // With loom or shuttle, MultiThreaded only works inside their test runners
+    #[cfg(not(any(loom, feature = "loom", shuttle, feature = "shuttle")))]
+    #[test]
+    fn shared() {
+        let st: super::Shared<State> = UniNew::new(State { val: 0 });
+        st.write().val += 1;
+        #[cfg(feature = "threadsafe")]
+        std::thread::spawn({
+            let st = st.clone();
+            move || st.write().val += 1
+        })
+        .join()
+        .unwrap();
+        assert!(st.read().val > 0);
+    }
+
+    #[test]
+    fn rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        st1.write().val += 1;
+        println!("{:?}", st1.read());
+    }
+    #[test]
+    fn arc() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        st2.write().val += 1;
+        println!("{:?}", st2.read());
+    }
+
+    #[test]
+    fn mutex() {
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        st3.write().val += 1;
+        println!("{:?}", st3.read());
+    }
+
+    #[test]
+    fn rc_handler() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let sth1 = StateHandler::new(st1);
+        println!("{:?}", sth1.state);
+    }
+
+    #[test]
+    fn arc_handler() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let sth2 = StateHandler::new(st2);
+        sth2.state.write().val += 1;
+        println!("{:?}", sth2.state);
+    }
+
+    #[test]
+    fn mutex_handler() {
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        let sth3 = StateHandler::new(st3);
+        sth3.state.write().val += 1;
+        println!("{:?}", sth3.state);
+    }
+
+    #[test]
+    fn test_func() {
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let ptr = Rc::new(RefCell::new(State { val: 0 }));
+        incr(ptr.clone());
+        println!("{:?}", ptr.read().val);
+    }
+
+    #[test]
+    fn threads_test_arc() {
+        use std::thread;
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let sth2 = StateHandler::new(st2);
+
+        let threads: Vec<_> = (0..10)
+            .map(|i| {
+                let h = sth2.clone();
+                thread::spawn(move || {
+                    h.state.write().val += 1;
+                    println!("Thread #{i} incremented");
+                })
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        println!("Result: {}", sth2.state.read().val);
+    }
+
+    #[test]
+    fn threads_test_mutex() {
+        use std::thread;
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        let sth3 = StateHandler::new(st3);
+
+        let threads: Vec<_> = (0..10)
+            .map(|i| {
+                let h = sth3.clone();
+                thread::spawn(move || {
+                    h.state.write().val += 1;
+                    println!("Thread #{i} incremented");
+                })
+            })
+            .collect();
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(sth3.state.read().val, 52);
+    }
+
+    #[test]
+    fn generic_new() {
+        fn make<T: UniNew<State>>() -> StateHandler<T> {
+            StateHandler::new(T::new(State { val: 42 }))
+        }
+
+        let sth1 = make::<Rc<RefCell<State>>>();
+        let sth2 = make::<Arc<RwLock<State>>>();
+        let sth3 = make::<Arc<Mutex<State>>>();
+        sth1.state.write().val += 1;
+        assert_eq!(sth1.state.read().val, 43);
+        assert_eq!(sth2.state.read().val, 42);
+        assert_eq!(sth3.state.read().val, 42);
+    }
+
+    #[test]
+    fn any_pointer_any_cell() {
+        use super::{UniPtr, UniWeakPtr};
+
+        // A custom smart pointer, which only needs to implement UniPtr
+        struct MyPtr<C>(Rc<C>);
+
+        impl<C> Clone for MyPtr<C> {
+            fn clone(&self) -> Self {
+                MyPtr(self.0.clone())
+            }
+        }
+
+        impl<C> std::ops::Deref for MyPtr<C> {
+            type Target = C;
+            fn deref(&self) -> &C {
+                &self.0
+            }
+        }
+
+        struct MyWeak<C>(std::rc::Weak<C>);
+
+        impl<C> Clone for MyWeak<C> {
+            fn clone(&self) -> Self {
+                MyWeak(self.0.clone())
+            }
+        }
+
+        // SAFETY: the cell is in the Rc allocation
+        unsafe impl<C> UniPtr for MyPtr<C> {
+            type Cell = C;
+            type Weak = MyWeak<C>;
+
+            fn new(cell: C) -> Self {
+                MyPtr(Rc::new(cell))
+            }
+
+            fn strong_count(this: &Self) -> usize {
+                Rc::strong_count(&this.0)
+            }
+
+            fn weak_count(this: &Self) -> usize {
+                Rc::weak_count(&this.0)
+            }
+
+            fn ptr_eq(this: &Self, other: &Self) -> bool {
+                Rc::ptr_eq(&this.0, &other.0)
+            }
+
+            fn downgrade(this: &Self) -> Self::Weak {
+                MyWeak(Rc::downgrade(&this.0))
+            }
+
+            fn try_unwrap(this: Self) -> Result<C, Self> {
+                Rc::try_unwrap(this.0).map_err(MyPtr)
+            }
+
+            fn get_mut(this: &mut Self) -> Option<&mut C> {
+                Rc::get_mut(&mut this.0)
+            }
+        }
+
+        impl<C> UniWeakPtr for MyWeak<C> {
+            type Strong = MyPtr<C>;
+
+            fn upgrade(this: &Self) -> Option<MyPtr<C>> {
+                this.0.upgrade().map(MyPtr)
+            }
+        }
+
+        fn exercise<P: UniNew<State>>() {
+            let mut p = P::new(State { val: 1 });
+            p.get_mut().unwrap().val += 1;
+            let c = p.clone();
+            c.write().val += 1;
+            {
+                let _r = p.read();
+                assert!(p.try_read().is_ok());
+                assert_eq!(c.try_write().err(), Some(LockError::WouldBlock));
+            }
+            assert_eq!(*p.map_read(|s| &s.val), 3);
+            *c.map_write(|s| &mut s.val) += 1;
+            assert_eq!(p.strong_count(), 2);
+            let w = p.downgrade();
+            assert!(w.upgrade().unwrap().ptr_eq(&c));
+            drop(w);
+            let p = p.try_unwrap().unwrap_err();
+            drop(c);
+            assert_eq!(p.try_unwrap().ok().unwrap().val, 4);
+        }
+
+        exercise::<Rc<RefCell<State>>>();
+        exercise::<Rc<RwLock<State>>>();
+        exercise::<Arc<RefCell<State>>>();
+        exercise::<Arc<RwLock<State>>>();
+        exercise::<MyPtr<RefCell<State>>>();
+        exercise::<MyPtr<RwLock<State>>>();
+    }
+
+    #[test]
+    fn read_recursive() {
+        use super::RecursiveRead;
+
+        fn nested(p: &impl RecursiveRead<State>) -> i32 {
+            let outer = p.read_recursive();
+            let inner = p.read_recursive();
+            outer.val + inner.val
+        }
+
+        let st1 = Rc::new(RefCell::new(State { val: 1 }));
+        assert_eq!(nested(&st1), 2);
+        let local = RefCell::new(State { val: 3 });
+        assert_eq!(nested(&&local), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Recursive read while the RefCell is borrowed for writing")]
+    fn read_recursive_while_writing() {
+        use super::RecursiveRead;
+
+        let st1 = Rc::new(RefCell::new(State { val: 1 }));
+        let _w = st1.write();
+        st1.read_recursive();
+    }
+
+    #[test]
+    fn for_each_readable_arc() {
+        use super::for_each_readable;
+        use std::sync::{Arc, RwLock};
+
+        let handles: Vec<Arc<RwLock<i32>>> = (0..4).map(UniNew::new).collect();
+        let _w = handles[0].write();
+        let _r = handles[3].read();
+        let mut seen = Vec::new();
+        assert_eq!(for_each_readable(&handles, |v| seen.push(*v)), 3);
+        assert_eq!(seen, [1, 2, 3]);
+        assert_eq!(for_each_readable::<Arc<RwLock<i32>>, _>(&[], |_| ()), 0);
+    }
+
+    #[test]
+    fn write_all_rc() {
+        use super::write_all;
+
+        let a = Rc::new(RefCell::new(1));
+        let b = Rc::new(RefCell::new(2));
+        // Duplicates are borrowed once
+        let handles = [a.clone(), b.clone(), a.clone()];
+        let mut guards = write_all(&handles);
+        assert_eq!(guards.len(), 2);
+        assert!(a.try_read().is_err());
+        *guards[0] *= 10;
+        *guards[1] *= 10;
+        drop(guards);
+        assert_eq!((*a.read(), *b.read()), (10, 20));
+        assert!(write_all::<Rc<RefCell<i32>>, _>(&[]).is_empty());
+    }
+
+    #[test]
+    fn write_all_arc() {
+        use super::write_all;
+        use std::{
+            sync::{Arc, RwLock},
+            thread,
+        };
+
+        let handles: Vec<Arc<RwLock<i32>>> = (0..4).map(UniNew::new).collect();
+        // The threads lock the same handles in the opposite order
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let mut handles = handles.clone();
+                if t % 2 == 1 {
+                    handles.reverse();
+                }
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        for mut guard in write_all(&handles) {
+                            *guard += 1;
+                        }
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert!(handles.iter().all(|h| *h.read() >= 800));
+    }
+
+    #[test]
+    fn map_into_rc() {
+        use super::map_into;
+
+        #[derive(Debug)]
+        struct Validated {
+            val: u32,
+        }
+
+        fn validate<H1, H2>(h: H1) -> Result<H2, H1>
+        where
+            H1: UniRcLock<State>,
+            H2: UniNew<Option<Validated>>,
+        {
+            map_into(h, |st: State| {
+                u32::try_from(st.val).ok().map(|val| Validated { val })
+            })
+        }
+
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let weak = st1.downgrade();
+        let st2 = st1.clone();
+        // Not unique, returned back untouched
+        let st1 = validate::<_, Rc<RefCell<_>>>(st1).unwrap_err();
+        assert!(st1.ptr_eq(&st2));
+        drop(st2);
+        let v: Rc<RefCell<Option<Validated>>> = validate(st1).unwrap();
+        assert_eq!(v.read().as_ref().unwrap().val, 42);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn timeout_refcell() {
+        use super::TimedLock;
+        use core::time::Duration;
+
+        let st1 = Rc::new(RefCell::new(State { val: 1 }));
+        let w = st1.write();
+        // Returns right away, the timeout is ignored
+        assert!(st1.read_timeout(Duration::from_secs(3600)).is_none());
+        drop(w);
+        st1.write_timeout(Duration::ZERO).unwrap().val += 1;
+        let _r = st1.read_timeout(Duration::ZERO).unwrap();
+        assert!(st1.write_timeout(Duration::from_secs(3600)).is_none());
+    }
+
+    #[test]
+    fn share() {
+        fn check<H: UniNew<State>>() {
+            let st1 = H::new(State { val: 1 });
+            let st2 = st1.share();
+            assert!(st1.ptr_eq(&st2));
+            assert_eq!(st1.strong_count(), 2);
+            st2.write().val = 2;
+            assert_eq!(st1.read().val, 2);
+        }
+
+        check::<Rc<RefCell<State>>>();
+        check::<Arc<RwLock<State>>>();
+    }
+
+    #[test]
+    fn deep_clone() {
+        fn check<H: UniNew<Vec<i32>>>() {
+            let st1 = H::new(vec![1]);
+            let st2 = st1.deep_clone();
+            assert!(!st1.ptr_eq(&st2));
+            assert_eq!(st1.strong_count(), 1);
+            st1.write().push(2);
+            st2.write().push(3);
+            assert_eq!(*st1.read(), [1, 2]);
+            assert_eq!(*st2.read(), [1, 3]);
+        }
+
+        check::<Rc<RefCell<Vec<i32>>>>();
+        check::<Arc<RwLock<Vec<i32>>>>();
+        check::<Arc<Mutex<Vec<i32>>>>();
+    }
+
+    #[test]
+    fn is_unique() {
+        fn check<H: UniNew<State>>() {
+            let mut st1 = H::new(State { val: 1 });
+            assert!(st1.is_unique());
+            let st2 = st1.clone();
+            assert!(!st1.is_unique());
+            drop(st2);
+            // Counting must not lock the value
+            {
+                let _g = st1.write();
+                assert!(st1.is_unique());
+            }
+            assert!(st1.get_mut().is_some());
+            let weak = st1.downgrade();
+            assert!(!st1.is_unique());
+            drop(weak);
+            assert!(st1.is_unique());
+        }
+
+        check::<Rc<RefCell<State>>>();
+        check::<Arc<RwLock<State>>>();
+        check::<Arc<Mutex<State>>>();
+    }
+
+    #[test]
+    fn make_mut() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Counted(i32);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, Ordering::SeqCst);
+                Counted(self.0)
+            }
+        }
+
+        fn check<H: UniNew<Counted>>() {
+            CLONES.store(0, Ordering::SeqCst);
+            let mut st1 = H::new(Counted(1));
+            // Unique, modified in place
+            st1.make_mut().0 += 1;
+            assert_eq!(CLONES.load(Ordering::SeqCst), 0);
+
+            // Shared, cloned into a new handle
+            let st2 = st1.clone();
+            st1.make_mut().0 += 1;
+            assert_eq!(CLONES.load(Ordering::SeqCst), 1);
+            assert!(!st1.ptr_eq(&st2));
+            assert_eq!(st1.read().0, 3);
+            assert_eq!(st2.read().0, 2);
+            assert!(st1.is_unique() && st2.is_unique());
+
+            // Weak handles prevent the in-place modification too
+            let weak = st1.downgrade();
+            st1.make_mut().0 += 1;
+            assert_eq!(CLONES.load(Ordering::SeqCst), 2);
+            assert!(weak.upgrade().is_none());
+        }
+
+        check::<Rc<RefCell<Counted>>>();
+        check::<Arc<RwLock<Counted>>>();
+    }
+
+    #[test]
+    fn as_ptr() {
+        use std::collections::HashMap;
+
+        fn check<H: UniRcLock<i32>>(st1: H, st2: H) {
+            // Identity map keyed by the address of the value
+            let mut names = HashMap::new();
+            names.insert(st1.as_ptr(), "first");
+            names.insert(st2.as_ptr(), "second");
+            assert_eq!(names[&st1.clone().as_ptr()], "first");
+            assert_eq!(names[&st2.as_ptr()], "second");
+            // The address is the one the guards point to
+            *st1.write() += 1;
+            assert_eq!(st1.as_ptr(), &*st1.read() as *const i32);
+            // SAFETY: no guards are alive and the handle outlives the reference
+            assert_eq!(unsafe { *st1.as_ptr() }, 2);
+        }
+
+        check(Rc::new(RefCell::new(1)), Rc::new(RefCell::new(1)));
+        check(Arc::new(RwLock::new(1)), Arc::new(RwLock::new(1)));
+        check(Arc::new(Mutex::new(1)), Arc::new(Mutex::new(1)));
+        check(&RefCell::new(1), &RefCell::new(1));
+
+        // The value guards hold copies, but the address is the one of the cell
+        let st = Rc::new(Cell::new(1));
+        *st.write() += 1;
+        // SAFETY: no guards are alive and the handle outlives the reference
+        assert_eq!(unsafe { *UniRcLock::as_ptr(&st) }, 2);
+    }
+
+    #[test]
+    fn counts_rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let c1 = st1.clone();
+        let c2 = st1.clone();
+        assert_eq!(st1.strong_count(), 3);
+        assert_eq!(st1.weak_count(), 0);
+        let _w = Rc::downgrade(&st1);
+        assert_eq!(c1.weak_count(), 1);
+        drop(c2);
+        // Counting must not lock the value
+        let _g = st1.write();
+        assert_eq!(c1.strong_count(), 2);
+    }
+
+    #[test]
+    fn counts_arc() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let c1 = st2.clone();
+        let c2 = st2.clone();
+        assert_eq!(st2.strong_count(), 3);
+        assert_eq!(st2.weak_count(), 0);
+        let _w = Arc::downgrade(&st2);
+        assert_eq!(c1.weak_count(), 1);
+        drop(c2);
+        let _g = st2.write();
+        assert_eq!(c1.strong_count(), 2);
+    }
+
+    #[test]
+    fn ptr_eq_rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let c1 = st1.clone();
+        let other = Rc::new(RefCell::new(State { val: 42 }));
+        let _g = st1.write();
+        assert!(st1.ptr_eq(&c1));
+        assert!(!st1.ptr_eq(&other));
+    }
+
+    #[test]
+    fn ptr_eq_arc() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let c1 = st2.clone();
+        let other = Arc::new(RwLock::new(State { val: 42 }));
+        let _g = st2.write();
+        assert!(st2.ptr_eq(&c1));
+        assert!(!st2.ptr_eq(&other));
+    }
+
+    #[test]
+    fn weak_rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let w = st1.downgrade();
+        assert_eq!(st1.weak_count(), 1);
+        w.upgrade().unwrap().write().val += 1;
+        assert_eq!(st1.read().val, 43);
+        drop(st1);
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn weak_graph() {
+        // Picks the handle type. Needed to name the recursive node type.
+        trait Backend: Sized {
+            type H: UniNew<Node<Self>>;
+        }
+
+        // A tree node with the weak back-edge to its parent
+        struct Node<B: Backend> {
+            name: &'static str,
+            parent: Option<<B::H as UniRcLock<Node<B>>>::Weak>,
+            children: Vec<B::H>,
+        }
+
+        fn add_child<B: Backend>(parent: &B::H, name: &'static str) -> B::H {
+            let child = B::H::new(Node {
+                name,
+                parent: Some(parent.downgrade()),
+                children: vec![],
+            });
+            parent.write().children.push(child.clone());
+            child
+        }
+
+        fn path<B: Backend>(node: &B::H) -> String {
+            let n = node.read();
+            match n.parent.as_ref().and_then(B::H::from_weak) {
+                Some(p) => format!("{}/{}", path::<B>(&p), n.name),
+                None => n.name.to_owned(),
+            }
+        }
+
+        fn run<B: Backend>() {
+            let root = B::H::new(Node {
+                name: "root",
+                parent: None,
+                children: vec![],
+            });
+            let a = add_child::<B>(&root, "a");
+            let b = add_child::<B>(&a, "b");
+            assert_eq!(path::<B>(&b), "root/a/b");
+            // Back-edges don't keep the parents alive
+            assert_eq!(root.strong_count(), 1);
+            assert_eq!(a.weak_count(), 1);
+
+            let w = b.read().parent.clone().unwrap();
+            assert!(B::H::from_weak(&w).unwrap().ptr_eq(&a));
+            assert!(w.upgrade().unwrap().ptr_eq(&a));
+            drop(root);
+            assert_eq!(path::<B>(&b), "a/b");
+            drop(a);
+            assert_eq!(path::<B>(&b), "b");
+            assert!(B::H::from_weak(&w).is_none());
+        }
+
+        struct RcBackend;
+        impl Backend for RcBackend {
+            type H = Rc<RefCell<Node<Self>>>;
+        }
+
+        struct ArcBackend;
+        impl Backend for ArcBackend {
+            type H = Arc<RwLock<Node<Self>>>;
+        }
+
+        run::<RcBackend>();
+        run::<ArcBackend>();
+    }
+
+    #[test]
+    fn weak_arc() {
+        fn alive<W: UniWeak<State>>(w: &W) -> bool {
+            w.upgrade().is_some()
+        }
+
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let c1 = st2.clone();
+        let w = st2.downgrade();
+        assert!(w.upgrade().unwrap().ptr_eq(&st2));
+        drop(st2);
+        assert!(alive(&w));
+        drop(c1);
+        assert!(!alive(&w));
+    }
+
+    #[test]
+    fn try_unwrap_rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        let c1 = st1.clone();
+        let st1 = st1.try_unwrap().unwrap_err();
+        drop(c1);
+        assert_eq!(st1.try_unwrap().unwrap().val, 42);
+    }
+
+    #[test]
+    fn try_unwrap_arc() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let c1 = st2.clone();
+        let st2 = st2.try_unwrap().unwrap_err();
+        drop(c1);
+        assert_eq!(st2.try_unwrap().unwrap().val, 42);
+    }
+
+    #[test]
+    fn try_unwrap_poisoned() {
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        let h = st3.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert_eq!(st3.try_unwrap().unwrap().val, 42);
+    }
+
+    #[test]
+    fn get_mut_rc() {
+        let mut st1 = Rc::new(RefCell::new(State { val: 42 }));
+        st1.get_mut().unwrap().val += 1;
+        let c1 = st1.clone();
+        assert!(st1.get_mut().is_none());
+        drop(c1);
+        let w = st1.downgrade();
+        assert!(st1.get_mut().is_none());
+        drop(w);
+        assert_eq!(st1.get_mut().unwrap().val, 43);
+    }
+
+    #[test]
+    fn get_mut_arc() {
+        let mut st2 = Arc::new(RwLock::new(State { val: 42 }));
+        st2.get_mut().unwrap().val += 1;
+        let c1 = st2.clone();
+        assert!(st2.get_mut().is_none());
+        drop(c1);
+        assert_eq!(st2.get_mut().unwrap().val, 43);
+    }
+
+    #[test]
+    fn with_read_write() {
+        fn incr(p: &impl UniRcLock<State>) -> i32 {
+            p.with_write(|st| {
+                st.val += 1;
+                st.val
+            })
+        }
+
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        assert_eq!(incr(&st1), 43);
+        assert_eq!(st1.with_read(|st| st.val), 43);
+        // The guard is released when the closure returns
+        assert!(st1.try_write().is_ok());
+
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        assert_eq!(incr(&st2), 43);
+        assert_eq!(st2.with_read(|st| st.val), 43);
+        assert!(st2.try_write().is_ok());
+    }
+
+    #[test]
+    fn read_map() {
+        #[derive(Debug)]
+        struct Big {
+            id: u64,
+            payload: Vec<u8>,
+        }
+
+        fn id(p: &impl UniRcLock<Big>) -> u64 {
+            let id = p.read_map(|b| b.id);
+            // Not locked anymore
+            assert!(p.try_write().is_ok());
+            id
+        }
+
+        let st1 = Rc::new(RefCell::new(Big { id: 7, payload: vec![0; 1024] }));
+        assert_eq!(id(&st1), 7);
+        let st2 = Arc::new(RwLock::new(Big { id: 8, payload: vec![] }));
+        assert_eq!(id(&st2), 8);
+        assert_eq!(st2.read_map(|b| b.payload.len()), 0);
+        assert_eq!(st1.read_map(|b| b.payload.len()), 1024);
+    }
+
+    #[test]
+    fn update() {
+        // Returns the old length and the new one
+        fn push(p: &impl UniRcLock<Vec<i32>>, v: i32) -> (usize, usize) {
+            let res = p.update(|vec| {
+                let old = vec.len();
+                vec.push(v);
+                (old, vec.len())
+            });
+            // The lock is already released
+            assert!(p.try_write().is_ok());
+            res
+        }
+
+        let st1 = Rc::new(RefCell::new(vec![1]));
+        assert_eq!(push(&st1, 2), (1, 2));
+        assert_eq!(*st1.read(), vec![1, 2]);
+
+        let st2 = Arc::new(RwLock::new(vec![]));
+        assert_eq!(push(&st2, 1), (0, 1));
+        assert_eq!(push(&st2, 2), (1, 2));
+        assert_eq!(*st2.read(), vec![1, 2]);
+    }
+
+    #[test]
+    fn get_cloned_set() {
+        fn bump(p: impl UniRcLock<Vec<i32>>) -> Vec<i32> {
+            let old = p.get_cloned();
+            p.set(old.iter().map(|v| v + 1).collect());
+            old
+        }
+
+        let st1 = Rc::new(RefCell::new(vec![1, 2]));
+        assert_eq!(bump(st1.clone()), vec![1, 2]);
+        assert_eq!(*st1.read(), vec![2, 3]);
+
+        let st2 = Arc::new(RwLock::new(vec![1, 2]));
+        let snapshot = st2.get_cloned();
+        // The value is not locked anymore
+        assert_eq!(bump(st2.clone()), snapshot);
+        assert_eq!(*st2.read(), vec![2, 3]);
+    }
+
+    #[test]
+    fn default_new() {
+        fn fresh<H: UniNew<Vec<i32>>>() -> H {
+            let h = H::default_new();
+            h.write().push(1);
+            h
+        }
+
+        let h1: Rc<RefCell<Vec<i32>>> = fresh();
+        let h2: Arc<RwLock<Vec<i32>>> = fresh();
+        assert_eq!(*h1.read(), [1]);
+        assert_eq!(h2.try_unwrap().unwrap(), [1]);
+    }
+
+    #[test]
+    fn replace_take() {
+        fn reset(p: impl UniRcLock<Vec<i32>>) -> Vec<i32> {
+            assert_eq!(p.replace(vec![1, 2]), vec![0]);
+            p.take()
+        }
+
+        let st1 = Rc::new(RefCell::new(vec![0]));
+        assert_eq!(reset(st1.clone()), vec![1, 2]);
+        assert!(st1.read().is_empty());
+
+        let st2 = Arc::new(RwLock::new(vec![0]));
+        assert_eq!(reset(st2.clone()), vec![1, 2]);
+        assert!(st2.read().is_empty());
+    }
+
+    #[test]
+    fn swap() {
+        let st1 = Rc::new(RefCell::new(State { val: 1 }));
+        let st2 = Rc::new(RefCell::new(State { val: 2 }));
+        UniRcLock::swap(&st1, &st2);
+        assert_eq!(st1.read().val, 2);
+        UniRcLock::swap(&st1, &st1.clone());
+        assert_eq!(st1.read().val, 2);
+
+        // Swapping the same pair in opposite order must not deadlock
+        let a = Arc::new(Mutex::new(State { val: 1 }));
+        let b = Arc::new(Mutex::new(State { val: 2 }));
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                let (a, b) = (&a, &b);
+                s.spawn(move || {
+                    for _ in 0..1000 {
+                        if i % 2 == 0 {
+                            a.swap(b);
+                        } else {
+                            b.swap(a);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(a.read().val + b.read().val, 3);
+    }
+
+    #[test]
+    fn content_eq() {
+        let st1 = Rc::new(RefCell::new(vec![1, 2]));
+        let st2 = Rc::new(RefCell::new(vec![1, 2]));
+        assert!(st1.content_eq(&st2));
+        assert!(!st1.ptr_eq(&st2));
+        st2.write().push(3);
+        assert!(!st1.content_eq(&st2));
+        // Aliased handles, with the value already borrowed for reading
+        let _r = st1.read();
+        assert!(st1.content_eq(&st1.clone()));
+
+        let a = Arc::new(Mutex::new(vec![1.0]));
+        let b = Arc::new(Mutex::new(vec![1.0]));
+        assert!(a.content_eq(&b));
+        b.write()[0] = 2.0;
+        assert!(!b.content_eq(&a));
+        // Locked only once, so the Mutex doesn't deadlock
+        assert!(a.content_eq(&a.clone()));
+        a.set(vec![f64::NAN]);
+        assert!(!a.content_eq(&a));
+    }
+
+    #[test]
+    fn hash_contents() {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+
+        fn hash(h: &impl UniRcLock<Vec<i32>>) -> u64 {
+            let mut state = DefaultHasher::new();
+            h.hash_contents(&mut state);
+            state.finish()
+        }
+
+        let st1 = Rc::new(RefCell::new(vec![1, 2]));
+        let st2 = Arc::new(RwLock::new(vec![1, 2]));
+        assert_eq!(hash(&st1), hash(&st2));
+        st2.write().push(3);
+        assert_ne!(hash(&st1), hash(&st2));
+        // Equal to the hash of the value itself
+        let mut state = DefaultHasher::new();
+        vec![1, 2].hash(&mut state);
+        assert_eq!(hash(&st1), state.finish());
+    }
+
+    #[test]
+    fn map_read_write() {
+        #[derive(Debug)]
+        struct Pair {
+            a: State,
+            b: Vec<i32>,
+        }
+
+        fn project<P: UniRcLock<Pair>>(p: P) {
+            p.map_write(|pair| &mut pair.a).val += 1;
+            p.map_write(|pair| &mut pair.b[..])[0] = 2;
+            let a = p.map_read(|pair| &pair.a);
+            // The projected guard keeps the lock
+            assert!(p.try_write().is_err());
+            assert_eq!(a.val, 43);
+            drop(a);
+            assert_eq!(&*p.map_read(|pair| &pair.b[..]), &[2]);
+        }
+
+        let pair = || Pair {
+            a: State { val: 42 },
+            b: vec![1],
+        };
+        project(Rc::new(RefCell::new(pair())));
+        project(Arc::new(RwLock::new(pair())));
+        project(Arc::new(Mutex::new(pair())));
+    }
+
+    #[test]
+    fn try_rc() {
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        {
+            let _w = st1.write();
+            assert_eq!(st1.try_read().err(), Some(LockError::WouldBlock));
+            assert_eq!(st1.try_write().err(), Some(LockError::WouldBlock));
+        }
+        {
+            let _r = st1.read();
+            assert!(st1.try_read().is_ok());
+            assert!(st1.try_write().is_err());
+        }
+        st1.try_write().unwrap().val += 1;
+        assert_eq!(st1.try_read().unwrap().val, 43);
+    }
+
+    #[test]
+    fn can_read_write() {
+        fn check<H: UniRcLock<State>>(h: &H) {
+            assert!(h.can_read() && h.can_write());
+            {
+                let _r = h.read();
+                assert_eq!(h.can_read(), H::ALLOWS_CONCURRENT_READS);
+                assert!(!h.can_write());
+            }
+            {
+                let _w = h.write();
+                assert!(!h.can_read() && !h.can_write());
+            }
+            // Probing doesn't keep anything locked
+            assert!(h.can_write());
+        }
+
+        check(&Rc::new(RefCell::new(State { val: 1 })));
+        check(&Arc::new(RwLock::new(State { val: 1 })));
+        check(&Arc::new(Mutex::new(State { val: 1 })));
+
+        let st = Arc::new(RwLock::new(State { val: 1 }));
+        let h = st.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        // read() would panic on the poisoned lock
+        assert!(!st.can_read());
+    }
+
+    #[test]
+    fn try_arc() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        {
+            let _w = st2.write();
+            assert_eq!(st2.try_read().err(), Some(LockError::WouldBlock));
+            assert_eq!(st2.try_write().err(), Some(LockError::WouldBlock));
+        }
+        {
+            let _r = st2.read();
+            assert!(st2.try_read().is_ok());
+            assert!(st2.try_write().is_err());
+        }
+        st2.try_write().unwrap().val += 1;
+        assert_eq!(st2.try_read().unwrap().val, 43);
+    }
+
+    #[test]
+    fn checked_poisoned() {
+        use super::UniLockError;
+
+        fn check<H: UniNew<State> + Send + 'static>() {
+            let st = H::new(State { val: 1 });
+            assert_eq!(st.read_checked().unwrap().val, 1);
+            let h = st.clone();
+            std::thread::spawn(move || {
+                let mut w = h.write();
+                w.val = 2;
+                panic!("poison the lock");
+            })
+            .join()
+            .unwrap_err();
+            // The value is recovered instead of panicking
+            match st.read_checked() {
+                Err(UniLockError::Poisoned(guard)) => assert_eq!(guard.val, 2),
+                _ => panic!("The lock should be poisoned"),
+            }
+            let err = st.write_checked().err().unwrap();
+            assert_eq!(err.to_string(), "lock is poisoned");
+            err.into_guard().unwrap().val += 1;
+            st.clear_poison();
+            assert_eq!(st.write_checked().unwrap().val, 3);
+        }
+
+        check::<Arc<RwLock<State>>>();
+        check::<Arc<Mutex<State>>>();
+    }
+
+    #[test]
+    fn checked_rc() {
+        use super::UniLockError;
+
+        let st = Rc::new(RefCell::new(State { val: 1 }));
+        {
+            let _r = st.read();
+            assert!(st.read_checked().is_ok());
+            assert!(matches!(st.write_checked(), Err(UniLockError::AlreadyBorrowed)));
+        }
+        {
+            let _w = st.write();
+            assert!(matches!(st.read_checked(), Err(UniLockError::AlreadyBorrowed)));
+            let r = &st;
+            assert!(matches!(r.read_checked(), Err(UniLockError::AlreadyBorrowed)));
+        }
+        st.write_checked().unwrap().val += 1;
+        assert_eq!(st.read_checked().unwrap().val, 2);
+    }
+
+    #[test]
+    fn try_arc_contended() {
+        use std::sync::mpsc;
+
+        let st = Arc::new(RwLock::new(State { val: 42 }));
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let h = st.clone();
+        let t = std::thread::spawn(move || {
+            let mut w = h.write();
+            locked_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            w.val += 1;
+        });
+        // The update is skipped rather than blocking while the other thread writes
+        locked_rx.recv().unwrap();
+        assert_eq!(st.try_read().err(), Some(LockError::WouldBlock));
+        assert_eq!(st.try_write().err(), Some(LockError::WouldBlock));
+        release_tx.send(()).unwrap();
+        t.join().unwrap();
+        assert_eq!(st.try_read().unwrap().val, 43);
+    }
+
+    #[test]
+    fn try_arc_poisoned() {
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let h = st2.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert_eq!(st2.try_read().err(), Some(LockError::Poisoned));
+        assert_eq!(st2.try_write().err(), Some(LockError::Poisoned));
+    }
+
+    #[test]
+    fn mutex_backend() {
+        use super::MutexBackend;
+
+        fn concurrent<H: UniRcLock<State>>() -> bool {
+            H::ALLOWS_CONCURRENT_READS
+        }
+
+        // Compiles only for the exclusive locks
+        fn exclusive<H: MutexBackend<State>>(h: &H) -> i32 {
+            const { assert!(!H::ALLOWS_CONCURRENT_READS) };
+            let r = h.read();
+            assert_eq!(h.try_read().err(), Some(LockError::WouldBlock));
+            r.val
+        }
+
+        assert!(concurrent::<Rc<RefCell<State>>>());
+        assert!(concurrent::<Arc<RwLock<State>>>());
+        assert!(!concurrent::<Arc<Mutex<State>>>());
+        assert!(!concurrent::<Rc<Mutex<State>>>());
+        assert_eq!(exclusive(&Arc::new(Mutex::new(State { val: 1 }))), 1);
+    }
+
+    #[test]
+    fn try_mutex() {
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        {
+            let _w = st3.write();
+            assert_eq!(st3.try_read().err(), Some(LockError::WouldBlock));
+            assert_eq!(st3.try_write().err(), Some(LockError::WouldBlock));
+        }
+        {
+            // Reading is exclusive for the mutex
+            let _r = st3.read();
+            assert_eq!(st3.try_read().err(), Some(LockError::WouldBlock));
+        }
+        st3.try_write().unwrap().val += 1;
+        assert_eq!(st3.try_read().unwrap().val, 43);
+
+        let h = st3.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert_eq!(st3.try_read().err(), Some(LockError::Poisoned));
+    }
+
+    #[test]
+    fn poison_arc() {
+        fn recover(p: impl UniRcLock<State>) {
+            if p.is_poisoned() {
+                p.clear_poison();
+            }
+        }
+
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        assert!(!st1.is_poisoned());
+        recover(st1.clone());
+
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        let h = st2.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert!(st2.is_poisoned());
+        recover(st2.clone());
+        assert!(!st2.is_poisoned());
+        assert_eq!(st2.read().val, 42);
+
+        let st3 = Arc::new(Mutex::new(State { val: 42 }));
+        let h = st3.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert!(st3.is_poisoned());
+        recover(st3.clone());
+        assert_eq!(st3.try_read().unwrap().val, 42);
+    }
+
+    #[test]
+    fn rc_cell() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Counter(i32);
+
+        fn incr(v: &impl UniRcLock<Counter>) {
+            v.write().0 += 1;
+        }
+
+        let st = Rc::new(Cell::new(Counter(0)));
+        incr(&st);
+        {
+            let r = st.read();
+            incr(&st);
+            assert_eq!(*r, Counter(1));
+        }
+        assert_eq!(st.get(), Counter(2));
+        *st.map_write(|c| &mut c.0) += 10;
+        assert_eq!(*st.map_read(|c| &c.0), 12);
+
+        // Dropping an unmodified guard doesn't store its copy
+        let w = st.write();
+        st.set(Counter(0));
+        drop(w);
+        assert_eq!(st.get(), Counter(0));
+        assert_eq!(st.try_unwrap().ok(), Some(Counter(0)));
+    }
+
+    #[test]
+    fn debug() {
+        fn show(p: &impl UniRcLock<State>) -> String {
+            format!("{:?}", p.debug())
+        }
+
+        let st1 = Rc::new(RefCell::new(State { val: 42 }));
+        assert_eq!(show(&st1), "State { val: 42 }");
+        {
+            let _w = st1.write();
+            assert_eq!(show(&st1), "<locked>");
+        }
+        let st2 = Arc::new(RwLock::new(State { val: 42 }));
+        {
+            let _r = st2.read();
+            assert_eq!(show(&st2), "State { val: 42 }");
+        }
+        let h = st2.clone();
+        let _ = std::thread::spawn(move || {
+            let _w = h.write();
+            panic!("poison the lock");
+        })
+        .join();
+        assert_eq!(show(&st2), "<poisoned>");
+    }
+
+    #[test]
+    fn static_rwlock() {
+        static STATE: RwLock<State> = RwLock::new(State { val: 0 });
+
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let st: &'static RwLock<State> = &STATE;
+        incr(st);
+        let sth = StateHandler::new(st);
+        // Inherent RwLock::write() shadows the trait method on a concrete reference
+        UniRcLock::write(&sth.state).val += 1;
+        assert_eq!(STATE.read().unwrap().val, 2);
+        assert!(sth.state.ptr_eq(&st));
+        assert_eq!(sth.state.strong_count(), 1);
+        assert!(st.try_unwrap().is_err());
+    }
+
+    #[test]
+    fn borrowed_refcell() {
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let cell = RefCell::new(State { val: 0 });
+        incr(&cell);
+        {
+            let sth = StateHandler::new(&cell);
+            sth.state.write().val += 1;
+            let sth2 = sth.clone();
+            assert!(sth2.state.ptr_eq(&sth.state));
+            assert_eq!(*sth2.state.map_read(|s| &s.val), 2);
+        }
+        assert_eq!(cell.into_inner().val, 2);
+    }
+
+    #[test]
+    fn borrowed_rwlock() {
+        fn incr(p: impl UniRcLock<State>) {
+            p.write().val += 1;
+        }
+
+        let lock = RwLock::new(State { val: 0 });
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| incr(&lock));
+            }
+        });
+        let sth = StateHandler::new(&lock);
+        UniRcLock::write(&sth.state).val += 1;
+        assert_eq!(lock.into_inner().unwrap().val, 5);
+    }
+
+    #[test]
+    fn static_refcell() {
+        thread_local! {
+            static STATE: &'static RefCell<State> =
+                Box::leak(Box::new(RefCell::new(State { val: 0 })));
+        }
+
+        let st = STATE.with(|st| *st);
+        let sth = StateHandler::new(st);
+        sth.state.write().val += 1;
+        assert_eq!(STATE.with(|st| st.read().val), 1);
+        let _r = st.read();
+        assert_eq!(sth.state.try_write().err(), Some(LockError::WouldBlock));
+    }
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    use super::{LockError, UniRcLock};
+
+    #[test]
+    fn rc_smoke() {
+        fn incr(p: impl UniRcLock<i32>) {
+            *p.write() += 1;
+        }
+
+        let ptr = Rc::new(RefCell::new(0));
+        incr(ptr.clone());
+        assert_eq!(*ptr.read(), 1);
+        let _g = ptr.write();
+        assert_eq!(ptr.try_read().err(), Some(LockError::WouldBlock));
+        assert!(ptr.downgrade().upgrade().is_some());
+    }
+
+    #[cfg(feature = "spin")]
+    #[test]
+    fn spin_multi_threaded() {
+        use super::UniNew;
+        let ptr: super::MultiThreaded<i32> = UniNew::new(0);
+        let h = ptr.clone();
+        std::thread::spawn(move || *h.write() += 1).join().unwrap();
+        assert_eq!(*ptr.read(), 1);
+        let _g = ptr.read();
+        assert_eq!(ptr.try_write().err(), Some(LockError::WouldBlock));
     }
 }
@@ -0,0 +1,75 @@
+// Macros for declaring the types generic over the UniRcLock backend.
+
+/// Declare a struct holding a value of the given type in any [UniRcLock](crate::UniRcLock)
+/// handle, which is the struct's generic parameter `H`.
+///
+/// `uni_handle!(StateHandler { state: State })` declares
+/// `struct StateHandler<H: UniRcLock<State>>` with the field `state: H`
+/// and generates the methods:
+/// - `new(state: H)` wrapping an existing handle,
+/// - `from_value(value: State)` creating a new handle,
+/// - `read_state()` and `write_state()` returning the guards of the handle.
+///
+/// The attributes and the visibility of the struct are passed through
+/// and the methods get the visibility of the struct.
+/// ```
+/// use std::{cell::RefCell, rc::Rc, sync::{Arc, RwLock}};
+/// use uni_rc_lock::{uni_handle, UniRcLock};
+///
+/// struct State {
+///     val: i32,
+/// }
+///
+/// uni_handle! {
+///     #[derive(Clone)]
+///     pub StateHandler { state: State }
+/// }
+///
+/// fn incr<H: UniRcLock<State>>(h: &StateHandler<H>) {
+///     h.write_state().val += 1;
+/// }
+///
+/// let h1 = StateHandler::new(Rc::new(RefCell::new(State { val: 1 })));
+/// let h2 = StateHandler::<Arc<RwLock<State>>>::from_value(State { val: 2 });
+/// incr(&h1);
+/// incr(&h2.clone());
+/// assert_eq!(h1.read_state().val + h2.read_state().val, 5);
+/// assert_eq!(h2.state.strong_count(), 1);
+/// ```
+#[macro_export]
+macro_rules! uni_handle {
+    ($(#[$attr:meta])* $vis:vis $name:ident { $field_vis:vis $field:ident: $ty:ty $(,)? }) => {
+        $(#[$attr])*
+        $vis struct $name<H: $crate::UniRcLock<$ty>> {
+            $field_vis $field: H,
+        }
+
+        $crate::__paste! {
+            #[allow(dead_code)]
+            impl<H: $crate::UniRcLock<$ty>> $name<H> {
+                /// Wrap an existing handle
+                $vis fn new($field: H) -> Self {
+                    Self { $field }
+                }
+
+                /// Create a new handle owning the value
+                $vis fn from_value(value: $ty) -> Self
+                where
+                    H: $crate::UniNew<$ty>,
+                {
+                    Self { $field: H::new(value) }
+                }
+
+                #[doc = concat!("Obtain a scoped guard for reading `", stringify!($field), "`")]
+                $vis fn [<read_ $field>](&self) -> H::OutRead<'_> {
+                    self.$field.read()
+                }
+
+                #[doc = concat!("Obtain a scoped guard for writing `", stringify!($field), "`")]
+                $vis fn [<write_ $field>](&self) -> H::OutWrite<'_> {
+                    self.$field.write()
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,106 @@
+// Mapped guards for the backends without native guard projection.
+//
+// A projected reference is computed once and stored as a pointer next to
+// the original guard, which keeps the lock held. This is sound only if the
+// guarded value doesn't live inside the guard itself, so moving the guard
+// doesn't move the value. This holds for all lock guards in this crate,
+// which point into the lock.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+/// Read guard projected to a part of the locked value by
+/// [UniRcLock::map_read](crate::UniRcLock::map_read)
+pub struct MappedReadGuard<G, U: ?Sized> {
+    ptr: NonNull<U>,
+    _guard: G,
+}
+
+impl<G: Deref, U: ?Sized> MappedReadGuard<G, U> {
+    // The target of the guard must not move when the guard is moved.
+    // Not used if only the backends with native mapped guards are enabled.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(guard: G, f: impl FnOnce(&G::Target) -> &U) -> Self {
+        Self {
+            ptr: NonNull::from(f(&*guard)),
+            _guard: guard,
+        }
+    }
+}
+
+impl<G, U: ?Sized> Deref for MappedReadGuard<G, U> {
+    type Target = U;
+    fn deref(&self) -> &U {
+        // SAFETY: the pointee is kept alive and shared by the guard
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<G, U: ?Sized + fmt::Debug> fmt::Debug for MappedReadGuard<G, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Write guard projected to a part of the locked value by
+/// [UniRcLock::map_write](crate::UniRcLock::map_write)
+///
+/// The guard is invariant in `U` like `&mut U`, so a shorter-lived value
+/// can't be written through it into the lock:
+/// ```compile_fail
+/// use std::sync::{Arc, RwLock};
+/// use uni_rc_lock::{MappedWriteGuard, UniRcLock};
+///
+/// struct S {
+///     s: &'static str,
+/// }
+///
+/// let h = Arc::new(RwLock::new(S { s: "static" }));
+/// let local = String::from("local");
+/// let mut g: MappedWriteGuard<_, &str> = h.map_write(|v| &mut v.s);
+/// *g = &local;
+/// ```
+pub struct MappedWriteGuard<G, U: ?Sized> {
+    ptr: NonNull<U>,
+    _guard: G,
+    // NonNull is covariant, while the guard lends the value mutably
+    _invariant: PhantomData<*mut U>,
+}
+
+impl<G: DerefMut, U: ?Sized> MappedWriteGuard<G, U> {
+    // The target of the guard must not move when the guard is moved.
+    // Not used if only the backends with native mapped guards are enabled.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn new(mut guard: G, f: impl FnOnce(&mut G::Target) -> &mut U) -> Self {
+        Self {
+            ptr: NonNull::from(f(&mut *guard)),
+            _guard: guard,
+            _invariant: PhantomData,
+        }
+    }
+}
+
+impl<G, U: ?Sized> Deref for MappedWriteGuard<G, U> {
+    type Target = U;
+    fn deref(&self) -> &U {
+        // SAFETY: the pointee is kept alive and locked exclusively by the guard
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<G, U: ?Sized> DerefMut for MappedWriteGuard<G, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        // SAFETY: the pointee is kept alive and locked exclusively by the guard
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<G, U: ?Sized + fmt::Debug> fmt::Debug for MappedWriteGuard<G, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
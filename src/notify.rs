@@ -0,0 +1,352 @@
+// Change notifications for any UniRcLock handle. Available with the `notify` feature.
+
+use crate::UniRcLock;
+use core::{
+    fmt,
+    future::poll_fn,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context, Poll, Waker},
+};
+use futures_core::Stream;
+use std::sync::{Arc, Mutex};
+
+// Shared by all clones of Notified
+#[derive(Default)]
+struct Notifier {
+    // Incremented by each write
+    version: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Notifier {
+    fn notify(&self) {
+        self.version.fetch_add(1, Ordering::SeqCst);
+        let wakers = core::mem::take(&mut *self.wakers.lock().unwrap());
+        for w in wakers {
+            w.wake();
+        }
+    }
+
+    // Ready if the version differs from the seen one, which is updated then.
+    // The version is checked again after registering the waker, so a write
+    // made between the checks is not missed.
+    fn poll_changed(&self, seen: &mut usize, cx: &mut Context<'_>) -> Poll<()> {
+        let check = |seen: &mut usize| {
+            let v = self.version.load(Ordering::SeqCst);
+            (v != *seen).then(|| *seen = v)
+        };
+        if check(seen).is_some() {
+            return Poll::Ready(());
+        }
+        {
+            let mut wakers = self.wakers.lock().unwrap();
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        match check(seen) {
+            Some(()) => Poll::Ready(()),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A wrapper of any [UniRcLock] handle, which notifies the async tasks
+/// about the changes of the value.
+///
+/// Each write guard obtained by [write](Notified::write) wakes the tasks
+/// awaiting [changed](Notified::changed) or polling [into_stream](Notified::into_stream)
+/// when it is dropped, after the lock is released. Writes made through
+/// the wrapped handle directly are not noticed.
+/// Clones share both the value and the notifications.
+///
+/// The notifications are coalesced: a task woken after several writes
+/// sees the latest value once, but it never misses the last write.
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use std::sync::{Arc, RwLock};
+/// use uni_rc_lock::Notified;
+///
+/// let n = Notified::new(Arc::new(RwLock::new(0)));
+/// let waiter = tokio::spawn({
+///     let n = n.clone();
+///     let changed = n.changed();
+///     async move {
+///         changed.await;
+///     }
+/// });
+/// *n.write() += 1;
+/// waiter.await.unwrap();
+/// # }
+/// ```
+pub struct Notified<H> {
+    handle: H,
+    notifier: Arc<Notifier>,
+}
+
+impl<H: Clone> Clone for Notified<H> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            notifier: self.notifier.clone(),
+        }
+    }
+}
+
+impl<H> Notified<H> {
+    /// Wrap the handle
+    pub fn new(handle: H) -> Self {
+        Self {
+            handle,
+            notifier: Default::default(),
+        }
+    }
+
+    /// The wrapped handle. Writes through it are not notified.
+    pub fn handle(&self) -> &H {
+        &self.handle
+    }
+
+    /// Obtain a scoped guard for reading
+    pub fn read<T>(&self) -> H::OutRead<'_>
+    where
+        H: UniRcLock<T>,
+    {
+        self.handle.read()
+    }
+
+    /// Obtain a scoped guard for writing, which notifies the waiting tasks when dropped
+    pub fn write<T>(&self) -> NotifyWriteGuard<'_, H::OutWrite<'_>>
+    where
+        H: UniRcLock<T>,
+    {
+        NotifyWriteGuard {
+            guard: ManuallyDrop::new(self.handle.write()),
+            notifier: &self.notifier,
+        }
+    }
+
+    /// Wait until the value is written after this call.
+    /// The write made after calling `changed()` but before awaiting
+    /// the future is not missed.
+    pub fn changed(&self) -> impl core::future::Future<Output = ()> + Send + 'static {
+        let notifier = self.notifier.clone();
+        let mut seen = notifier.version.load(Ordering::SeqCst);
+        poll_fn(move |cx| notifier.poll_changed(&mut seen, cx))
+    }
+
+    /// Wait until the value satisfies the predicate.
+    ///
+    /// The predicate is checked under a read guard right away and again
+    /// after each notified write. The guard is released before awaiting,
+    /// so the writers are never blocked by the waiting task.
+    pub async fn wait_for<T>(&self, mut pred: impl FnMut(&T) -> bool)
+    where
+        H: UniRcLock<T>,
+    {
+        self.wait_map(|v| pred(v).then_some(())).await
+    }
+
+    /// Wait until `f` returns `Some` for the value and return its result,
+    /// checking the value the same way as [wait_for](Notified::wait_for).
+    pub async fn wait_map<T, R>(&self, mut f: impl FnMut(&T) -> Option<R>) -> R
+    where
+        H: UniRcLock<T>,
+    {
+        loop {
+            // Subscribed before checking, so a write made after the check
+            // is not missed
+            let changed = self.changed();
+            if let Some(r) = f(&self.handle.read()) {
+                return r;
+            }
+            changed.await;
+        }
+    }
+
+    /// A stream yielding a copy of the value after each write.
+    /// Only the latest value is yielded after several writes made meanwhile.
+    /// The stream never ends.
+    pub fn into_stream<T: Clone>(&self) -> NotifyStream<H, T>
+    where
+        H: UniRcLock<T>,
+    {
+        NotifyStream {
+            seen: self.notifier.version.load(Ordering::SeqCst),
+            notified: self.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<H: fmt::Debug> fmt::Debug for Notified<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Notified").field(&self.handle).finish()
+    }
+}
+
+/// Write guard of [Notified], which notifies the waiting tasks when dropped.
+pub struct NotifyWriteGuard<'a, G> {
+    guard: ManuallyDrop<G>,
+    notifier: &'a Notifier,
+}
+
+impl<G> Drop for NotifyWriteGuard<'_, G> {
+    fn drop(&mut self) {
+        // SAFETY: the guard is not used after this
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        // The lock is released, so the woken tasks could read the value
+        self.notifier.notify();
+    }
+}
+
+impl<G: Deref> Deref for NotifyWriteGuard<'_, G> {
+    type Target = G::Target;
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for NotifyWriteGuard<'_, G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
+
+impl<G: Deref<Target: fmt::Debug>> fmt::Debug for NotifyWriteGuard<'_, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// The stream of the values of [Notified] returned by [into_stream](Notified::into_stream)
+pub struct NotifyStream<H, T> {
+    notified: Notified<H>,
+    seen: usize,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<H, T> Unpin for NotifyStream<H, T> {}
+
+impl<H, T> Stream for NotifyStream<H, T>
+where
+    H: UniRcLock<T>,
+    T: Clone,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        this.notified
+            .notifier
+            .poll_changed(&mut this.seen, cx)
+            .map(|()| Some(this.notified.handle.read().clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use std::{cell::RefCell, rc::Rc, sync::Arc, sync::RwLock};
+
+    use super::Notified;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn notify_stream() {
+        const N: i32 = 100;
+        let n = Notified::new(Arc::new(RwLock::new(0)));
+        let mut stream = n.into_stream();
+        let producer = tokio::spawn({
+            let n = n.clone();
+            async move {
+                for _ in 0..N {
+                    *n.write() += 1;
+                    tokio::task::yield_now().await;
+                }
+            }
+        });
+        // The values are increasing and the last one is always observed
+        let mut last = 0;
+        while last < N {
+            let v = stream.next().await.unwrap();
+            assert!(v > last);
+            last = v;
+        }
+        producer.await.unwrap();
+    }
+
+    struct State {
+        val: i32,
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn notify_wait_for() {
+        let n = Notified::new(Arc::new(RwLock::new(State { val: 0 })));
+        let waiter = tokio::spawn({
+            let n = n.clone();
+            async move {
+                n.wait_for(|s: &State| s.val >= 10).await;
+                n.read().val
+            }
+        });
+        for _ in 0..20 {
+            n.write().val += 1;
+            tokio::task::yield_now().await;
+        }
+        // Woken not before the value reached 10
+        assert!(waiter.await.unwrap() >= 10);
+
+        // Already satisfied
+        let v = n.wait_map(|s: &State| (s.val > 0).then_some(s.val)).await;
+        assert_eq!(v, 20);
+    }
+
+    #[tokio::test]
+    async fn notify_wait_exact() {
+        let n = Notified::new(Rc::new(RefCell::new(State { val: 0 })));
+        let done = std::cell::Cell::new(false);
+        let waiter = async {
+            let v = n.wait_map(|s: &State| (s.val >= 10).then_some(s.val)).await;
+            done.set(true);
+            v
+        };
+        let writer = async {
+            for _ in 0..10 {
+                // Still waiting before the 10th increment
+                assert!(!done.get());
+                n.write().val += 1;
+                tokio::task::yield_now().await;
+            }
+        };
+        let (v, ()) = tokio::join!(waiter, writer);
+        assert_eq!(v, 10);
+        assert!(done.get());
+    }
+
+    #[tokio::test]
+    async fn notify_changed() {
+        let n = Notified::new(Rc::new(RefCell::new(vec![1])));
+        let changed = n.changed();
+        // Written before awaiting, still noticed
+        n.write().push(2);
+        changed.await;
+        assert_eq!(*n.read(), [1, 2]);
+
+        let n2 = n.clone();
+        let waiter = async move {
+            n2.changed().await;
+            n2.read().len()
+        };
+        let writer = async {
+            tokio::task::yield_now().await;
+            n.write().push(3);
+        };
+        let (len, ()) = tokio::join!(waiter, writer);
+        assert_eq!(len, 3);
+    }
+}
@@ -0,0 +1,158 @@
+// Guards owning the handle, which could be returned from functions
+// or stored in structs without a lifetime.
+
+use crate::{UniCell, UniPtr, UniRcLock};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Owned guard made of a borrowing guard and the handle it borrows from,
+/// returned by [OwnedLock] and by the async backends without native owned guards.
+pub struct OwnedGuard<H, G> {
+    // Declared first to be dropped before the handle
+    guard: G,
+    _handle: H,
+}
+
+impl<H, G> OwnedGuard<H, G> {
+    // The guard must borrow from the heap allocation owned by the handle,
+    // which doesn't move when the handle is moved.
+    pub(crate) unsafe fn new(handle: H, guard: G) -> Self {
+        Self {
+            guard,
+            _handle: handle,
+        }
+    }
+}
+
+impl<H, G: Deref> Deref for OwnedGuard<H, G> {
+    type Target = G::Target;
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<H, G: DerefMut> DerefMut for OwnedGuard<H, G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
+
+impl<H, G: Deref<Target: fmt::Debug>> fmt::Debug for OwnedGuard<H, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// The [UniRcLock] handles which provide owned guards, like tokio's
+/// `OwnedRwLockReadGuard`. They keep the handle alive instead of borrowing it,
+/// so they are `'static` and could be returned from a function or stored
+/// in a struct without a lifetime.
+///
+/// Both methods consume the handle, so clone it first to keep using it.
+/// Implemented for `Arc<RwLock<T>>`, `Rc<RefCell<T>>` and any other [UniPtr]
+/// pointing to a [UniCell].
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use uni_rc_lock::{OwnedLock, UniNew};
+///
+/// struct Reader<H: OwnedLock<Vec<i32>>> {
+///     data: H::OutReadOwned,
+/// }
+///
+/// fn reader<H: OwnedLock<Vec<i32>>>(h: &H) -> Reader<H> {
+///     Reader { data: h.clone().read_owned() }
+/// }
+///
+/// let h: Rc<RefCell<Vec<i32>>> = UniNew::new(vec![1, 2]);
+/// let r = reader(&h);
+/// assert_eq!(r.data.len(), 2);
+/// ```
+pub trait OwnedLock<T>: UniRcLock<T> + 'static {
+    type OutReadOwned: Deref<Target = T>;
+    type OutWriteOwned: DerefMut<Target = T>;
+    /// Obtain a guard for reading, which keeps the handle alive
+    fn read_owned(self) -> Self::OutReadOwned;
+    /// Obtain a guard for writing, which keeps the handle alive
+    fn write_owned(self) -> Self::OutWriteOwned;
+}
+
+impl<T, P> OwnedLock<T> for P
+where
+    P: UniPtr + 'static,
+    P::Cell: UniCell<Value = T>,
+{
+    type OutReadOwned = OwnedGuard<P, <P::Cell as UniCell>::Read<'static>>;
+    type OutWriteOwned = OwnedGuard<P, <P::Cell as UniCell>::Write<'static>>;
+
+    fn read_owned(self) -> Self::OutReadOwned {
+        // SAFETY: the cell is kept alive by the pointer stored with the guard
+        // and doesn't move when the pointer is moved, as required by UniPtr
+        unsafe {
+            let cell = &*(P::deref(&self) as *const P::Cell);
+            OwnedGuard::new(self, cell.read())
+        }
+    }
+
+    fn write_owned(self) -> Self::OutWriteOwned {
+        // SAFETY: the cell is kept alive by the pointer stored with the guard
+        // and doesn't move when the pointer is moved, as required by UniPtr
+        unsafe {
+            let cell = &*(P::deref(&self) as *const P::Cell);
+            OwnedGuard::new(self, cell.write())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::OwnedLock;
+    use crate::{UniNew, UniRcLock};
+
+    struct Holder<H: OwnedLock<Vec<i32>>> {
+        data: H::OutWriteOwned,
+    }
+
+    fn holder<H: OwnedLock<Vec<i32>>>(h: &H) -> Holder<H> {
+        Holder {
+            data: h.clone().write_owned(),
+        }
+    }
+
+    #[test]
+    fn owned_rc() {
+        let ptr: Rc<RefCell<Vec<i32>>> = UniNew::new(vec![1]);
+        let mut h = holder(&ptr);
+        h.data.push(2);
+        assert!(ptr.try_read().is_err());
+        drop(h);
+        let r = ptr.clone().read_owned();
+        drop(ptr);
+        // The guard keeps the value alive
+        assert_eq!(*r, [1, 2]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn owned_arc() {
+        use std::{
+            sync::{Arc, Mutex, RwLock},
+            thread,
+        };
+
+        let ptr: Arc<RwLock<Vec<i32>>> = UniNew::new(vec![1]);
+        let h = ptr.clone();
+        let t = thread::spawn(move || holder(&h).data.push(2));
+        t.join().unwrap();
+        assert_eq!(*ptr.read(), [1, 2]);
+
+        let ptr: Arc<Mutex<Vec<i32>>> = UniNew::new(vec![1]);
+        let r = ptr.clone().read_owned();
+        assert!(ptr.try_write().is_err());
+        drop(r);
+        assert_eq!(ptr.strong_count(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+//! Serialization of [UniRcLock] handles with `serde`. Available with the `serde` feature.
+//!
+//! The handles are serialized as the values they hold, so any backend
+//! could be deserialized from the data serialized by any other backend.
+//! The functions are intended for `#[serde(with = "uni_rc_lock::serde")]`:
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use std::{cell::RefCell, rc::Rc};
+//! # use uni_rc_lock::UniNew;
+//! #[derive(Serialize, Deserialize)]
+//! struct State {
+//!     val: i32,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct StateHandler<T: UniNew<State>> {
+//!     #[serde(with = "uni_rc_lock::serde")]
+//!     state: T,
+//! }
+//!
+//! let h = StateHandler { state: Rc::new(RefCell::new(State { val: 42 })) };
+//! let json = serde_json::to_string(&h).unwrap();
+//! assert_eq!(json, r#"{"state":{"val":42}}"#);
+//! ```
+
+use crate::{UniNew, UniRcLock};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialize the value of the handle. The value is locked for reading
+/// while it is serialized.
+pub fn serialize<T, H, S>(handle: &H, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    H: UniRcLock<T>,
+    S: Serializer,
+{
+    handle.read().serialize(serializer)
+}
+
+/// Deserialize the value and create a new handle owning it with [UniNew::new].
+pub fn deserialize<'de, T, H, D>(deserializer: D) -> Result<H, D::Error>
+where
+    T: Deserialize<'de>,
+    H: UniNew<T>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(H::new)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{Arc, RwLock},
+    };
+
+    use crate::{UniNew, UniRcLock};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct State {
+        val: i32,
+        name: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct StateHandler<T: UniNew<State>> {
+        #[serde(with = "crate::serde")]
+        state: T,
+    }
+
+    fn round_trip<T: UniNew<State>>() {
+        let h = StateHandler {
+            state: T::new(State {
+                val: 42,
+                name: "answer".into(),
+            }),
+        };
+        let json = serde_json::to_string(&h).unwrap();
+        let h2: StateHandler<T> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*h2.state.read(), *h.state.read());
+        assert!(!h2.state.ptr_eq(&h.state));
+    }
+
+    #[test]
+    fn serde_rc() {
+        round_trip::<Rc<RefCell<State>>>();
+    }
+
+    #[test]
+    fn serde_arc() {
+        round_trip::<Arc<RwLock<State>>>();
+    }
+
+    #[test]
+    fn serde_across_backends() {
+        let h = StateHandler {
+            state: Rc::new(RefCell::new(State {
+                val: 1,
+                name: "rc".into(),
+            })),
+        };
+        let json = serde_json::to_string(&h).unwrap();
+        let h2: StateHandler<Arc<RwLock<State>>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(h2.state.read().val, 1);
+    }
+}
@@ -0,0 +1,91 @@
+// Guards owning a copy of the value for the backends which can't lend
+// a reference to the shared value, because it may change at any moment.
+
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Read guard owning a copy of the value taken when it was created.
+///
+/// The guard doesn't lock anything, so the shared value may be changed
+/// while the guard is alive. The copy stays unchanged.
+pub struct ValueGuard<T> {
+    value: T,
+}
+
+impl<T> ValueGuard<T> {
+    // Not used if none of the value-copying backends are enabled.
+    #[allow(dead_code)]
+    pub(crate) fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Deref for ValueGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ValueGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+/// Write guard owning a copy of the value, which is stored back
+/// to the shared cell when the guard is dropped.
+///
+/// The value is stored back only if it was accessed mutably,
+/// so dropping an unused guard doesn't overwrite the changes made meanwhile.
+/// The guard doesn't lock anything: if the shared value is changed while
+/// the guard is alive, this change is lost when the guard stores its copy.
+pub struct ValueWriteGuard<'a, C, T: Copy> {
+    cell: &'a C,
+    value: T,
+    store: fn(&C, T),
+    modified: bool,
+}
+
+impl<'a, C, T: Copy> ValueWriteGuard<'a, C, T> {
+    // Not used if none of the value-copying backends are enabled.
+    #[allow(dead_code)]
+    pub(crate) fn new(cell: &'a C, value: T, store: fn(&C, T)) -> Self {
+        Self {
+            cell,
+            value,
+            store,
+            modified: false,
+        }
+    }
+}
+
+impl<C, T: Copy> Deref for ValueWriteGuard<'_, C, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<C, T: Copy> DerefMut for ValueWriteGuard<'_, C, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        &mut self.value
+    }
+}
+
+impl<C, T: Copy> Drop for ValueWriteGuard<'_, C, T> {
+    fn drop(&mut self) {
+        if self.modified {
+            (self.store)(self.cell, self.value);
+        }
+    }
+}
+
+impl<C, T: Copy + fmt::Debug> fmt::Debug for ValueWriteGuard<'_, C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
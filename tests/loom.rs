@@ -0,0 +1,44 @@
+// Model checking of the generic code with loom.
+// Run with: RUSTFLAGS="--cfg loom" cargo test --test loom
+#![cfg(loom)]
+
+use loom::thread;
+use uni_rc_lock::{MultiThreaded, UniNew, UniRcLock};
+
+// A downstream type generic over the handle, unaware of loom
+struct Config<H: UniRcLock<Vec<u32>>> {
+    values: H,
+}
+
+impl<H: UniRcLock<Vec<u32>>> Config<H> {
+    fn push(&self, v: u32) {
+        self.values.write().push(v);
+    }
+
+    fn snapshot(&self) -> Vec<u32> {
+        self.values.read().clone()
+    }
+}
+
+#[test]
+fn reader_sees_prefix() {
+    loom::model(|| {
+        let config = Config {
+            values: <MultiThreaded<_> as UniNew<_>>::new(vec![0]),
+        };
+        let writer = {
+            let config = Config {
+                values: config.values.clone(),
+            };
+            thread::spawn(move || {
+                config.push(1);
+                config.push(2);
+            })
+        };
+        // Any interleaving shows the writes in order and never partially
+        let seen = config.snapshot();
+        assert!([&[0][..], &[0, 1], &[0, 1, 2]].contains(&&seen[..]));
+        writer.join().unwrap();
+        assert_eq!(config.snapshot(), [0, 1, 2]);
+    });
+}